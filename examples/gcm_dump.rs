@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use clap::Parser;
-use picori::{gcm, Gcm};
+use picori::{gcm, Dol, Gcm};
 
 extern crate picori;
 
@@ -17,28 +17,34 @@ extern crate picori;
 struct Args {
     /// Path to the file to dump
     #[arg()]
-    path:      PathBuf,
+    path:       PathBuf,
     /// Dump boot.bin
     #[arg(short, long)]
-    boot:      bool,
+    boot:       bool,
     /// Dump bi2.bin
     #[arg(short = '2', long)]
-    bi2:       bool,
+    bi2:        bool,
     /// Dump apploader.img
     #[arg(short = 'l', long)]
-    apploader: bool,
+    apploader:  bool,
     /// Dump fst.bin
     #[arg(short, long)]
-    fst:       bool,
+    fst:        bool,
+    /// Dump main.dol
+    #[arg(short = 'x', long)]
+    executable: bool,
     /// Dump data
     #[arg(short, long)]
-    data:      bool,
+    data:       bool,
     /// Dump all
     #[arg(short, long)]
-    all:       bool,
+    all:        bool,
     /// Column width
     #[arg(short, long, default_value = "32")]
-    width:     usize,
+    width:      usize,
+    /// Extract all files to this directory
+    #[arg(short, long)]
+    extract:    Option<PathBuf>,
 }
 
 fn hex2(value: u8) -> String { format!("\x1b[36m{:#04x}\x1b[0m", value) }
@@ -97,13 +103,20 @@ fn output_bi2(bi2: &gcm::Bi2) {
         .map(|x| (*x.0, *x.1))
         .collect::<Vec<_>>();
 
-    options.sort_by(|a, b| a.0.cmp(&b.0));
+    options.sort_by_key(|a| a.0);
     for (i, value) in options {
         println!("  [{:04x}]: {} ({})", i.index(), hex8(value), num(value));
     }
+
+    if let Some(pad_spec) = bi2.pad_spec() {
+        println!("  pad_spec: {:?}", pad_spec);
+    }
+    if let Some(debug_flag) = bi2.debug_flag() {
+        println!("  debug_flag: {}", debug_flag);
+    }
 }
 
-fn output_data(data: &Vec<u8>, width: usize) {
+fn output_data(data: &[u8], width: usize) {
     for (j, line) in data.chunks(width).enumerate() {
         print!("{:06x}: ", j * 32);
         for byte in line {
@@ -151,6 +164,19 @@ fn output_fst(fst: &gcm::Fst) {
     }
 }
 
+fn output_executable(dol: &Dol) {
+    println!("main.dol:");
+    println!("  entry point: {}", hex8(dol.entry_point()));
+    for section in &dol.sections {
+        println!(
+            "  \x1b[32m{:<8}\x1b[0m address: {} size: {}",
+            section.name,
+            hex8(section.address),
+            hex8(section.size)
+        );
+    }
+}
+
 fn main() {
     let args = Args::parse();
 
@@ -158,6 +184,7 @@ fn main() {
     let mut dump_bi2 = args.bi2;
     let mut dump_apploader = args.apploader;
     let mut dump_fst = args.fst;
+    let mut dump_executable = args.executable;
 
     let data = args.data;
     let width = match args.width {
@@ -170,9 +197,16 @@ fn main() {
         dump_bi2 = true;
         dump_apploader = true;
         dump_fst = true;
+        dump_executable = true;
     }
 
-    if !dump_boot && !dump_bi2 && !dump_apploader && !dump_fst {
+    if !dump_boot
+        && !dump_bi2
+        && !dump_apploader
+        && !dump_fst
+        && !dump_executable
+        && args.extract.is_none()
+    {
         println!("nothing to dump :(");
         return;
     }
@@ -182,18 +216,34 @@ fn main() {
     let gcm = Gcm::from_binary(&mut file).unwrap();
 
     if dump_boot {
-        output_boot(&gcm.boot());
+        output_boot(gcm.boot());
     }
 
     if dump_bi2 {
-        output_bi2(&gcm.bi2());
+        output_bi2(gcm.bi2());
     }
 
     if dump_apploader {
-        output_apploader(&gcm.apploader(), data, width);
+        output_apploader(gcm.apploader(), data, width);
     }
 
     if dump_fst {
-        output_fst(&gcm.fst());
+        output_fst(gcm.fst());
+    }
+
+    if dump_executable {
+        output_executable(&gcm.executable_as_dol(&mut file).unwrap());
+    }
+
+    if let Some(output) = args.extract {
+        gcm.extract_all(&mut file, |path, data| {
+            let path = output.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, data)?;
+            Ok(())
+        })
+        .unwrap();
     }
 }
@@ -51,7 +51,7 @@ fn hex16(value: u64) -> String {
     format!("\x1b[36m{:#018x}\x1b[0m", value)
 }
 
-fn output_data(data: &Vec<u8>, width: usize) {
+fn output_data(data: &[u8], width: usize) {
     for (j, line) in data.chunks(width).enumerate() {
         print!("{:06x}: ", j * 32);
         for byte in line {
@@ -119,14 +119,14 @@ where
             },
             rarc::Node::DirectoryBegin { name } => {
                 println!("{indent}{prefix} {}", name);
-                indent.push_str("│");
+                indent.push('│');
             },
             rarc::Node::DirectoryEnd { .. } => {
                 println!("{indent}┴");
                 indent.pop();
             },
-            rarc::Node::CurrentDirectory { .. } => {},
-            rarc::Node::ParentDirectory { .. } => {},
+            rarc::Node::CurrentDirectory => {},
+            rarc::Node::ParentDirectory => {},
         }
     }
 }
@@ -172,7 +172,7 @@ fn main() -> Result<(), picori::Error> {
     }
 
     if dump_tree {
-        output_tree(&mut reader);
+        output_tree(&reader);
     }
 
     if let Some(out_path) = out_path {
@@ -1,5 +1,4 @@
 use clap::Parser;
-use picori::rel::ImportKind;
 use picori::Rel;
 use std::path::PathBuf;
 
@@ -61,6 +60,7 @@ fn output_header(rel: &Rel) {
     println!("  name size:   {}", hex8(rel.name_size));
     println!("  alignment:     {}", hex8(rel.alignment));
     println!("  bss alignment: {}", hex8(rel.bss_alignment));
+    println!("  bss size:      {}", hex8(rel.bss_size));
     println!("  fix size:      {}", hex8(rel.fix_size));
     println!(
         "  relocation offset: {}",
@@ -108,8 +108,9 @@ fn output_sections(rel: &Rel) {
     println!("sections:");
     for (i, section) in rel.sections.iter().enumerate() {
         println!(
-            "  #{:<2} offset: {}, size: {}{}{}{}{}",
+            "  #{:<2} {:<8} offset: {}, size: {}{}{}{}{}",
             num(i as u32),
+            rel.guess_section_name(i),
             hex8(section.offset),
             hex8(section.size),
             if section.executable {
@@ -136,25 +137,6 @@ fn output_sections(rel: &Rel) {
     }
 }
 
-fn import_kind_to_string(kind: ImportKind) -> &'static str {
-    match kind {
-        ImportKind::None => "None",
-        ImportKind::Addr32 => "Addr32",
-        ImportKind::Addr24 => "Addr24",
-        ImportKind::Addr16 => "Addr16",
-        ImportKind::Addr16Lo => "Addr16Lo",
-        ImportKind::Addr16Hi => "Addr16Hi",
-        ImportKind::Addr16Ha => "Addr16Ha",
-        ImportKind::Addr14 => "Addr14",
-        ImportKind::Rel24 => "Rel24",
-        ImportKind::Rel14 => "Rel14",
-        ImportKind::DolphinNop => "DolphinNop",
-        ImportKind::DolphinSection => "DolphinSection",
-        ImportKind::DolphinEnd => "DolphinEnd",
-        ImportKind::DolphinMRKREF => "DolphinMRKREF",
-    }
-}
-
 fn output_imports(rel: &Rel) {
     println!("import tables:");
     for (i, table) in rel.import_tables.iter().enumerate() {
@@ -173,7 +155,7 @@ fn output_imports(rel: &Rel) {
             println!(
                 "  #{:<4} {:<20} section: {:>2}, offset: {}, addend: {}",
                 num(j as u32),
-                import_kind_to_string(import.kind),
+                import.kind,
                 num(import.section as u32),
                 hex4(import.offset),
                 hex8(import.addend)
@@ -190,7 +172,7 @@ fn output_relocations(rel: &Rel) {
             "  #{:<4} {:<20} target: [section: {:>2}, offset: {}], reference: [module: {:>4}, \
              section: {:>2}, offset: {}]",
             num(i as u32),
-            import_kind_to_string(relocation.kind),
+            relocation.kind,
             num(relocation.target.section),
             hex8(relocation.target.offset),
             num(relocation.module),
@@ -200,7 +182,7 @@ fn output_relocations(rel: &Rel) {
     }
 }
 
-fn output_data(data: &Vec<u8>, width: usize) {
+fn output_data(data: &[u8], width: usize) {
     for (j, line) in data.chunks(width).enumerate() {
         print!("{:06x}: ", j * 32);
         for byte in line {
@@ -277,7 +259,11 @@ fn main() -> picori::Result<()> {
     let file = std::fs::File::open(args.path)?;
     let file = std::io::BufReader::new(file);
     let file = picori::Yaz0Reader::new(file)?;
-    let rel = Rel::from_binary(file)?;
+    let rel = if dump_data {
+        Rel::from_binary(file)?
+    } else {
+        Rel::from_binary_headers_only(file)?
+    };
 
     output(
         &rel,
@@ -10,6 +10,10 @@ impl GenTableValue for u8 {
     fn gen_table_value(&self) -> String { format!("{self}") }
 }
 
+impl GenTableValue for u16 {
+    fn gen_table_value(&self) -> String { format!("{self}") }
+}
+
 impl GenTableValue for u32 {
     fn gen_table_value(&self) -> String { format!("{self}") }
 }
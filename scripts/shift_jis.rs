@@ -116,6 +116,7 @@ fn generate_table(
     name: &'static str,
     version: Version,
     data: &[Data],
+    reverse: bool,
 ) -> Result<(), Error> {
     let data = data
         .iter()
@@ -150,8 +151,8 @@ fn generate_table(
             })
             .collect::<Vec<_>>();
 
-        u1.sort_by(|a, b| a.0.cmp(&b.0));
-        u2.sort_by(|a, b| a.0.cmp(&b.0));
+        u1.sort_by_key(|a| a.0);
+        u2.sort_by_key(|a| a.0);
 
         let first = u1.iter().map(|x| x.0).chain(u2.iter().map(|x| x.0)).min();
         let last = u1.iter().map(|x| x.0).chain(u2.iter().map(|x| x.0)).max();
@@ -184,6 +185,30 @@ fn generate_table(
         }
     }
 
+    // reverse lookup sorted by unicode, if a character can be encoded in
+    // multiple ways the lowest code is used.
+    let mut reverse_lookup = data
+        .iter()
+        .filter_map(|x| match x.value {
+            Value::Unicode1(u) => Some((u, ((x.byte0 as u16) << 8) | x.byte1 as u16)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    reverse_lookup.sort();
+    reverse_lookup.dedup_by_key(|x| x.0);
+
+    // reverse lookup for characters that decode into two code points, e.g.,
+    // a base character followed by a combining mark.
+    let mut reverse_double_lookup = data
+        .iter()
+        .filter_map(|x| match x.value {
+            Value::Unicode2(u0, u1) => Some((u0, u1, ((x.byte0 as u16) << 8) | x.byte1 as u16)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    reverse_double_lookup.sort();
+    reverse_double_lookup.dedup_by_key(|x| (x.0, x.1));
+
     let mut output_file = OpenOptions::new()
         .write(true)
         .truncate(true)
@@ -194,6 +219,12 @@ fn generate_table(
 
     table_lookup.gen_table(format!("{name}_UTF8_T"), &mut buffer)?;
     single_lookup.gen_table(format!("{name}_UTF8_S"), &mut buffer)?;
+    if reverse {
+        reverse_lookup.gen_table(format!("{name}_UTF8_R"), &mut buffer)?;
+        if !reverse_double_lookup.is_empty() {
+            reverse_double_lookup.gen_table(format!("{name}_UTF8_R2"), &mut buffer)?;
+        }
+    }
     if !double_lookup.is_empty() {
         double_lookup.gen_table(format!("{name}_UTF8_D"), &mut buffer)?;
     }
@@ -213,9 +244,9 @@ pub fn generate() -> Result<(), Error> {
 
     let dir = env::var_os("OUT_DIR").unwrap();
     let path_1997 = Path::new(&dir).join("shift_jis_1997.rs");
-    generate_table(&path_1997, "SJIS_1997", Version::Since1997, &data)?;
+    generate_table(&path_1997, "SJIS_1997", Version::Since1997, &data, true)?;
     let path_2004 = Path::new(&dir).join("shift_jis_2004.rs");
-    generate_table(&path_2004, "SJIS_2004", Version::Since2004, &data)?;
+    generate_table(&path_2004, "SJIS_2004", Version::Since2004, &data, true)?;
 
     Ok(())
 }
@@ -3,15 +3,16 @@
 //! [ASCII][`Ascii`] is a 7-bit encoding designed for information interchange in
 //! English. Bytes with the eighth bit set are considered invalid and will cause
 //! an [`InvalidByte`][`crate::error::DecodingProblem::InvalidByte`] to be
-//! returned.
+//! returned, unless decoded with [`Ascii::all_lossy`] or [`Ascii::first_lossy`]
+//! which replace them with `U+FFFD`.
 
-use std::borrow::Borrow;
-use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
 
 use crate::error::{DecodingProblem::*, EncodingProblem};
-use crate::helper::{ParseStringEncoding, Parser, ProblemLocation};
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
 use crate::Result;
 
 /// [ASCII][`Ascii`] encoding.
@@ -23,8 +24,9 @@ where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter: <I as IntoIterator>::IntoIter,
-    _marker: PhantomData<&'x ()>,
+    iter:     <I as IntoIterator>::IntoIter,
+    position: usize,
+    _marker:  PhantomData<&'x ()>,
 }
 
 impl<I> Decoder<'_, I>
@@ -34,12 +36,14 @@ where
 {
     fn new<'x>(iter: I) -> Decoder<'x, I> {
         Decoder {
-            iter: iter.into_iter(),
-            _marker: PhantomData,
+            iter:     iter.into_iter(),
+            position: 0,
+            _marker:  PhantomData,
         }
     }
 
-    fn decode_byte(byte: u8) -> Option<char> {
+    /// Decode a single byte.
+    pub fn decode_byte(byte: u8) -> Option<char> {
         match byte {
             // ASCII character
             0x00..=0x7f => Some(byte as char),
@@ -58,10 +62,12 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(byte) = self.iter.next() {
-            let byte = byte.borrow();
-            Some(match Self::decode_byte(*byte) {
+            let byte = *byte.borrow();
+            let index = self.position;
+            self.position += 1;
+            Some(match Self::decode_byte(byte) {
                 Some(c) => Ok(c),
-                None => Err(InvalidByte(*byte, Location::current()).into()),
+                None => Err(InvalidByte(byte, index, Location::current()).into()),
             })
         } else {
             None
@@ -104,6 +110,29 @@ impl Ascii {
             })
             .collect()
     }
+
+    /// Decode all bytes into a string. Invalid bytes are replaced with `U+FFFD`
+    /// (replacement character) instead of returning an error.
+    pub fn all_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        iter.into_iter()
+            .map(|x| Decoder::<I>::decode_byte(*x.borrow()).unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator. Invalid bytes are replaced with `U+FFFD` (replacement
+    /// character) instead of returning an error.
+    pub fn first_lossy<I>(iter: I) -> String
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all_lossy(iter.into_iter().take_while(|x| *x.borrow() != 0))
+    }
 }
 
 /// Extension trait for iterators of bytes and adds the helper function
@@ -114,9 +143,7 @@ where
     Self::Item: Borrow<u8> + Sized,
 {
     /// Decode self iterator of bytes as [ASCII][`Ascii`].
-    fn ascii<'b>(self) -> Decoder<'b, Self> {
-        Decoder::new(self)
-    }
+    fn ascii<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
 }
 
 impl<I> IteratorExt for I
@@ -135,6 +162,14 @@ impl ParseStringEncoding for Ascii {
         Self::first(iter)
     }
 
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
     fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
         let mut i = 0;
         for c in data.chars().peekable() {
@@ -154,15 +189,7 @@ impl ParseStringEncoding for Ascii {
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
-        let buffer = BufReader::new(reader);
-        let iter = buffer
-            .bytes()
-            .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
-        Self::first(iter)
+        Self::first(parser_bytes(reader))
     }
 }
 
@@ -177,6 +204,22 @@ mod tests {
     #[test]
     fn parse_str() {
         let data = b"abc\0def";
-        assert_eq!(Ascii::parse_str(data).unwrap(), "abc".to_string());
+        assert_eq!(Ascii::parse_str(data).unwrap(), "abc");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 4];
+        assert_eq!(Ascii::write_str("abc", &mut buffer).unwrap(), 3);
+        assert_eq!(&buffer[..3], b"abc");
+        assert!(Ascii::write_str("ab\u{80}", &mut buffer).is_err());
+        assert!(Ascii::write_str("abcde", &mut buffer).is_err());
+    }
+
+    #[test]
+    fn from_binary() {
+        let mut reader = crate::SliceParser::new(b"abc\0def");
+        assert_eq!(Ascii::from_binary(&mut reader).unwrap(), "abc");
+        assert_eq!(reader.remaining(), b"def");
     }
 }
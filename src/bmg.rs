@@ -0,0 +1,297 @@
+//! BMG message files.
+//!
+//! BMG (magic `MESGbmg1`) is the text container used by most Nintendo games.
+//! The file starts with a `0x20` byte header, containing the total size, the
+//! number of sections and the text encoding, followed by the sections. Each
+//! section starts with a four character magic and its size (including the
+//! section header). Only two sections are used by this module:
+//!
+//! - `INF1` - A table of entries, one per message, with the offset of the
+//!   message in `DAT1` followed by game-specific attributes.
+//! - `DAT1` - The message data. Messages are NUL-terminated and can contain
+//!   escape sequences, which start with the character `0x1A` followed by a
+//!   byte with the length of the whole sequence.
+//!
+//! Escape sequences are used for control codes such as text color, player
+//! name or button icons. Their meaning is game-specific, so they are kept as
+//! raw bytes in [`Segment::Escape`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("message.bmg")?;
+//!     let bmg = picori::Bmg::from_binary(&mut file)?;
+//!     for message in bmg.strings() {
+//!         println!("{message}");
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::{DecodingProblem, ParseProblem};
+use crate::helper::{ensure, ParseStringEncoding, Parser, ProblemLocation, Seeker};
+use crate::{Ascii, Result, ShiftJis1997, Utf16Be, Windows1252};
+
+/// Text encoding of the messages, given by the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Not specified (`0`), used by older games. Decoded as
+    /// [ASCII][`crate::Ascii`].
+    Undefined,
+
+    /// Windows-1252 (`1`). Decoded as [Windows-1252][`crate::Windows1252`].
+    Windows1252,
+
+    /// UTF-16 big endian (`2`).
+    Utf16,
+
+    /// Shift JIS (`3`). Decoded as [Shift JIS 1997][`crate::ShiftJis1997`].
+    ShiftJis,
+
+    /// UTF-8 (`4`).
+    Utf8,
+}
+
+/// Part of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Decoded text.
+    Text(String),
+
+    /// Raw bytes of an escape sequence, including the leading `0x1A`
+    /// character and the length byte.
+    Escape(Vec<u8>),
+}
+
+/// A message from the `INF1` table.
+#[derive(Debug, Clone)]
+pub struct Message {
+    /// Offset of the message in the `DAT1` section.
+    pub offset:     u32,
+    /// Game-specific attributes following the offset in the `INF1` entry.
+    pub attributes: Vec<u8>,
+    /// The message split into text and escape sequences.
+    pub segments:   Vec<Segment>,
+}
+
+/// BMG message file.
+#[derive(Debug, Clone)]
+pub struct Bmg {
+    /// Encoding used to decode the messages.
+    pub encoding:      Encoding,
+    /// Message group identifier from the `INF1` section.
+    pub group:         u16,
+    /// Default text color from the `INF1` section.
+    pub default_color: u8,
+    /// Messages in `INF1` order.
+    pub messages:      Vec<Message>,
+}
+
+impl Encoding {
+    /// Get the [`Encoding`] from the header value, or [`None`] if the value is
+    /// unknown.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Undefined),
+            1 => Some(Self::Windows1252),
+            2 => Some(Self::Utf16),
+            3 => Some(Self::ShiftJis),
+            4 => Some(Self::Utf8),
+            _ => None,
+        }
+    }
+
+    /// Size in bytes of a code unit.
+    fn unit_size(self) -> usize {
+        match self {
+            Self::Utf16 => 2,
+            _ => 1,
+        }
+    }
+
+    fn decode(self, data: &[u8]) -> Result<String> {
+        match self {
+            Self::Undefined => Ascii::parse_all(data),
+            Self::Windows1252 => Windows1252::parse_all(data),
+            Self::ShiftJis => ShiftJis1997::parse_all(data),
+            Self::Utf8 => match std::str::from_utf8(data) {
+                Ok(text) => Ok(text.to_string()),
+                Err(e) => Err(DecodingProblem::InvalidByte(
+                    data[e.valid_up_to()],
+                    e.valid_up_to(),
+                    Location::current(),
+                )
+                .into()),
+            },
+            Self::Utf16 => Utf16Be::parse_all(data),
+        }
+    }
+}
+
+impl Message {
+    /// The text of the message without escape sequences.
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .filter_map(|x| match x {
+                Segment::Text(text) => Some(text.as_str()),
+                Segment::Escape(_) => None,
+            })
+            .collect()
+    }
+
+    fn parse(data: &[u8], offset: u32, encoding: Encoding) -> Result<Vec<Segment>> {
+        let unit_size = encoding.unit_size();
+        let unit = |index: usize| -> Result<u16> {
+            match data.get(index..index + unit_size) {
+                Some([x]) => Ok(*x as u16),
+                Some([x, y]) => Ok(u16::from_be_bytes([*x, *y])),
+                _ => Err(DecodingProblem::UnexpectedEndOfData(Location::current()).into()),
+            }
+        };
+
+        let mut segments = Vec::new();
+        let mut index = offset as usize;
+        let mut start = index;
+        loop {
+            let value = unit(index)?;
+            if value != 0 && value != 0x1A {
+                index += unit_size;
+                continue;
+            }
+
+            if start < index {
+                segments.push(Segment::Text(encoding.decode(&data[start..index])?));
+            }
+
+            if value == 0 {
+                break;
+            }
+
+            let length = *data
+                .get(index + unit_size)
+                .ok_or(DecodingProblem::UnexpectedEndOfData(Location::current()))?
+                as usize;
+            ensure!(
+                length > unit_size && index + length <= data.len(),
+                ParseProblem::InvalidData("invalid escape sequence length", Location::current())
+            );
+            segments.push(Segment::Escape(data[index..index + length].to_vec()));
+            index += length;
+            start = index;
+        }
+
+        Ok(segments)
+    }
+}
+
+impl Bmg {
+    /// Parse [`Bmg`] from binary stream, the messages are decoded with the
+    /// encoding given by the header.
+    pub fn from_binary<D: Parser + Seeker>(reader: &mut D) -> Result<Self> {
+        Self::parse(reader, None)
+    }
+
+    /// Parse [`Bmg`] from binary stream, the messages are decoded with
+    /// `encoding` regardless of the encoding given by the header.
+    pub fn from_binary_with_encoding<D: Parser + Seeker>(
+        reader: &mut D,
+        encoding: Encoding,
+    ) -> Result<Self> {
+        Self::parse(reader, Some(encoding))
+    }
+
+    /// The text of every message without escape sequences, see
+    /// [`Message::text`].
+    pub fn strings(&self) -> Vec<String> { self.messages.iter().map(|x| x.text()).collect() }
+
+    fn parse<D: Parser + Seeker>(reader: &mut D, encoding: Option<Encoding>) -> Result<Self> {
+        let base = reader.position()?;
+        let magic = reader.u8_array::<8>()?;
+        let _size = reader.bu32()?;
+        let section_count = reader.bu32()?;
+        let header_encoding = reader.u8()?;
+
+        ensure!(
+            &magic == b"MESGbmg1",
+            ParseProblem::InvalidMagic("invalid BMG magic", Location::current())
+        );
+
+        ensure!(
+            section_count <= 0x100,
+            ParseProblem::InvalidHeader("invalid section count", Location::current())
+        );
+
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::from_u8(header_encoding).ok_or(ParseProblem::InvalidHeader(
+                "unknown encoding",
+                Location::current(),
+            ))?,
+        };
+
+        let mut info = None;
+        let mut data = None;
+        let mut offset = base + 0x20;
+        for _ in 0..section_count {
+            reader.goto(offset)?;
+            let magic = reader.u8_array::<4>()?;
+            let size = reader.bu32()?;
+            ensure!(
+                size >= 8,
+                ParseProblem::InvalidData("invalid section size", Location::current())
+            );
+
+            let content = reader.read_as_vec_max(size as usize - 8, 0x1000000)?;
+            match &magic {
+                b"INF1" => info = Some(content),
+                b"DAT1" => data = Some(content),
+                _ => {},
+            }
+            offset += size as u64;
+        }
+
+        let (Some(info), Some(data)) = (info, data) else {
+            return Err(
+                ParseProblem::InvalidData("missing INF1 or DAT1", Location::current()).into(),
+            );
+        };
+
+        ensure!(
+            info.len() >= 8,
+            ParseProblem::InvalidData("INF1 section too small", Location::current())
+        );
+        let count = u16::from_be_bytes([info[0], info[1]]) as usize;
+        let entry_size = u16::from_be_bytes([info[2], info[3]]) as usize;
+        let group = u16::from_be_bytes([info[4], info[5]]);
+        let default_color = info[6];
+        ensure!(
+            entry_size >= 4 && 8 + count * entry_size <= info.len(),
+            ParseProblem::InvalidData("INF1 entries out of bounds", Location::current())
+        );
+
+        let messages = info[8..8 + count * entry_size]
+            .chunks_exact(entry_size)
+            .map(|entry| {
+                let offset = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                Ok(Message {
+                    offset,
+                    attributes: entry[4..].to_vec(),
+                    segments: Message::parse(&data, offset, encoding)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            encoding,
+            group,
+            default_color,
+            messages,
+        })
+    }
+}
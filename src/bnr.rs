@@ -0,0 +1,183 @@
+//! Disc banner (`opening.bnr`).
+//!
+//! The banner is shown by the GameCube main menu and contains the icon of the
+//! game and a description in one or more languages. The file starts with the
+//! magic `BNR1` or `BNR2`, followed by `0x1C` bytes of padding, the image and
+//! the metadata:
+//!
+//! - The image is `96x32` pixels in the
+//!   [`RGB5A3`][`crate::texture::GxTextureFormat::RGB5A3`] texture format,
+//!   `0x1800` bytes at offset `0x20`.
+//! - `BNR1` has a single metadata block at offset `0x1820`, used by NTSC
+//!   discs. The strings are decoded as [Shift JIS][`crate::ShiftJis1997`].
+//! - `BNR2` has six metadata blocks at offset `0x1820`, one per
+//!   [`Language`], used by PAL discs. The strings are decoded as
+//!   [Latin-1][`crate::Latin1`].
+//!
+//! Each metadata block is `0x140` bytes of NUL-terminated, fixed-size
+//! strings: the short title and maker (`0x20` bytes each), the long title and
+//! maker (`0x40` bytes each) and the description (`0x80` bytes).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("opening.bnr")?;
+//!     let bnr = picori::Bnr::from_binary(&mut file)?;
+//!     println!("{}", bnr.metas[0].long_title);
+//!     let rgba = bnr.image.decode()?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{ParseStringEncoding, Parser, ProblemLocation};
+use crate::texture::{self, GxTextureFormat};
+use crate::{Latin1, Result, ShiftJis1997};
+
+/// Width of the banner image in pixels.
+pub const IMAGE_WIDTH: u32 = 96;
+
+/// Height of the banner image in pixels.
+pub const IMAGE_HEIGHT: u32 = 32;
+
+/// Size of a `BNR1` banner in bytes.
+pub const BNR1_SIZE: usize = 0x1960;
+
+/// Size of a `BNR2` banner in bytes.
+pub const BNR2_SIZE: usize = 0x1FA0;
+
+/// Banner version, given by the magic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Version {
+    /// `BNR1`, a single metadata block.
+    Bnr1,
+    /// `BNR2`, one metadata block per [`Language`].
+    Bnr2,
+}
+
+/// Language of a `BNR2` metadata block, in the order they are stored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Language {
+    /// English, the first block.
+    English,
+    /// German.
+    German,
+    /// French.
+    French,
+    /// Spanish.
+    Spanish,
+    /// Italian.
+    Italian,
+    /// Dutch, the last block.
+    Dutch,
+}
+
+/// Banner image as raw texture data.
+#[derive(Debug, Clone)]
+pub struct Image {
+    /// Texture format, always [`GxTextureFormat::RGB5A3`].
+    pub format: GxTextureFormat,
+    /// Width in pixels.
+    pub width:  u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Tiled texture data.
+    pub data:   Vec<u8>,
+}
+
+/// Metadata block with the strings shown for the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meta {
+    /// Short name of the game.
+    pub short_title: String,
+    /// Short name of the developer or publisher.
+    pub short_maker: String,
+    /// Full name of the game.
+    pub long_title:  String,
+    /// Full name of the developer or publisher.
+    pub long_maker:  String,
+    /// Description of the game.
+    pub description: String,
+}
+
+/// Disc banner.
+#[derive(Debug, Clone)]
+pub struct Bnr {
+    /// Version of the banner.
+    pub version: Version,
+    /// Banner image.
+    pub image:   Image,
+    /// Metadata blocks, one for `BNR1` and six for `BNR2`.
+    pub metas:   Vec<Meta>,
+}
+
+impl Image {
+    /// Decode the image to row-major RGBA8, see [`texture::decode`].
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        texture::decode(self.format, self.width, self.height, &self.data)
+    }
+}
+
+impl Meta {
+    fn from_binary<E: ParseStringEncoding, D: Parser>(reader: &mut D) -> Result<Self> {
+        Ok(Self {
+            short_title: E::parse_str(reader.u8_array::<0x20>()?)?,
+            short_maker: E::parse_str(reader.u8_array::<0x20>()?)?,
+            long_title:  E::parse_str(reader.u8_array::<0x40>()?)?,
+            long_maker:  E::parse_str(reader.u8_array::<0x40>()?)?,
+            description: E::parse_str(reader.u8_array::<0x80>()?)?,
+        })
+    }
+}
+
+impl Bnr {
+    /// Parse [`Bnr`] from binary stream.
+    pub fn from_binary<D: Parser>(reader: &mut D) -> Result<Self> {
+        let magic = reader.u8_array::<4>()?;
+        let version = match &magic {
+            b"BNR1" => Version::Bnr1,
+            b"BNR2" => Version::Bnr2,
+            _ => {
+                return Err(
+                    ParseProblem::InvalidMagic("invalid BNR magic", Location::current()).into(),
+                )
+            },
+        };
+
+        let _padding = reader.u8_array::<0x1C>()?;
+        let size = GxTextureFormat::RGB5A3.data_size(IMAGE_WIDTH, IMAGE_HEIGHT)?;
+        let data = reader.read_as_vec(size)?;
+
+        let metas = match version {
+            Version::Bnr1 => vec![Meta::from_binary::<ShiftJis1997, _>(reader)?],
+            Version::Bnr2 => (0..6)
+                .map(|_| Meta::from_binary::<Latin1, _>(reader))
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        Ok(Self {
+            version,
+            image: Image {
+                format: GxTextureFormat::RGB5A3,
+                width: IMAGE_WIDTH,
+                height: IMAGE_HEIGHT,
+                data,
+            },
+            metas,
+        })
+    }
+
+    /// The metadata block for `language`. `BNR1` banners only have one block,
+    /// which is returned for every language.
+    pub fn meta(&self, language: Language) -> Option<&Meta> {
+        match self.version {
+            Version::Bnr1 => self.metas.first(),
+            Version::Bnr2 => self.metas.get(language as usize),
+        }
+    }
+}
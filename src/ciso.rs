@@ -12,7 +12,10 @@
 //! will parse the file header to determine how many blocks that are used. What
 //! you do with the blocks is up to you, they can be access via
 //! [`CisoReader::blocks`]. To decompress the whole file at once, use
-//! [`CisoReader::decompress`].
+//! [`CisoReader::decompress`]. [`CisoReader`] also implements
+//! [`Read`][`std::io::Read`] and [`Seek`][`std::io::Seek`] over the
+//! decompressed data, so it can be passed directly to other parsers, e.g.,
+//! [`Gcm::from_binary`][`crate::Gcm::from_binary`].
 //!
 //! ## Example
 //!
@@ -34,16 +37,40 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Build
+//!
+//! [`CisoBuilder`] does the opposite, it reads an uncompressed stream block by
+//! block and writes a [CISO][`crate::ciso`] file where all-zero blocks are
+//! omitted. [`ZeroPolicy::Threshold`] also omits blocks that are mostly
+//! zero, which is lossy.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut input = File::open("disc.iso")?;
+//!     let mut output = File::create("compact_disc.iso")?;
+//!     picori::CisoBuilder::new(0x200000)?.build(&mut input, &mut output)?;
+//!     Ok(())
+//! }
+//! ```
 
-use std::io::Write;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
 use std::panic::Location;
 
-use crate::helper::{ParseProblem, Parser, ProblemLocation, Seeker};
-use crate::Result;
+use crate::error::BuildProblem;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::{Error, Reader, Result};
 
 /// [CISO][`crate::ciso`] magic number representing the four characters "CISO".
 static MAGIC: u32 = 0x4349534F;
 
+/// Maximum number of blocks that fit in the block map.
+const MAX_BLOCKS: usize = 0x8000 - 8;
+
 #[derive(Debug)]
 struct Header {
     block_size: usize,
@@ -66,7 +93,7 @@ impl Header {
             .into());
         }
 
-        let block_map = input.u8_array::<{ 0x8000 - 8 }>()?;
+        let block_map = input.u8_array::<MAX_BLOCKS>()?;
         let Some(last_block_index) = block_map
             .iter()
             .enumerate()
@@ -95,11 +122,23 @@ impl Header {
     }
 }
 
+/// Check if the given data is a [CISO][`crate::ciso`] image by looking for the
+/// `CISO` magic. The stream position is restored.
+pub fn is_ciso<D: Parser + Seeker>(input: &mut D) -> bool {
+    let Ok(base) = input.position() else {
+        return false;
+    };
+
+    let is_ciso = input.bu32().map(|x| x == MAGIC).unwrap_or(false);
+    input.goto(base).is_ok() && is_ciso
+}
+
 /// Reader for [CISO][`crate::ciso`] files.
 pub struct CisoReader<'reader, D: Parser + Seeker> {
     header:      Header,
     reader:      &'reader mut D,
     data_offset: u64,
+    position:    u64,
 }
 
 impl<'reader, D: Parser + Seeker> CisoReader<'reader, D> {
@@ -111,6 +150,7 @@ impl<'reader, D: Parser + Seeker> CisoReader<'reader, D> {
             header,
             reader,
             data_offset,
+            position: 0,
         })
     }
 
@@ -127,11 +167,20 @@ impl<'reader, D: Parser + Seeker> CisoReader<'reader, D> {
         let mut buffer = vec![0; self.header.block_size];
         if has_data {
             self.reader.goto(self.data_offset + offset)?;
-            self.reader.read_into(&mut buffer)?;
+            self.reader.read_exact_into(&mut buffer)?;
         }
         Ok(buffer)
     }
 
+    /// Get the number of blocks.
+    pub fn block_count(&self) -> usize { self.header.blocks.len() }
+
+    /// Returns `true` if the block at index `index` has data stored in the
+    /// file, or `false` if it is omitted (an all-zero block) or out of range.
+    pub fn block_present(&self, index: usize) -> bool {
+        self.header.blocks.get(index).is_some_and(|x| x.1)
+    }
+
     /// Return an iterator over all blocks that returns their data.
     pub fn blocks<'this>(&'this mut self) -> BlockIterator<'this, 'reader, D> {
         BlockIterator {
@@ -140,6 +189,16 @@ impl<'reader, D: Parser + Seeker> CisoReader<'reader, D> {
         }
     }
 
+    /// Return an iterator over all blocks that returns the block index and
+    /// its data, or [`None`] if the block is omitted, i.e., all zeros. Omitted
+    /// blocks are not read or allocated.
+    pub fn blocks_sparse<'this>(&'this mut self) -> SparseBlockIterator<'this, 'reader, D> {
+        SparseBlockIterator {
+            reader: self,
+            index:  0,
+        }
+    }
+
     /// Decompress all [CISO][`crate::ciso`] block and write the data to a
     /// [`std::io::Write`]. If you need to know the final size of the
     /// decompressed file, use [`CisoReader::total_size`].
@@ -151,6 +210,59 @@ impl<'reader, D: Parser + Seeker> CisoReader<'reader, D> {
     }
 }
 
+impl<D: Parser + Seeker> Read for CisoReader<'_, D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let total_size = self.total_size() as u64;
+        if self.position >= total_size || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let block_size = self.header.block_size as u64;
+        let index = (self.position / block_size) as usize;
+        let within = self.position % block_size;
+        let size = (buf.len() as u64)
+            .min(block_size - within)
+            .min(total_size - self.position) as usize;
+
+        let (offset, has_data) = self.header.blocks[index];
+        if has_data {
+            self.reader
+                .goto(self.data_offset + offset + within)
+                .and_then(|_| self.reader.read_exact_into(&mut buf[..size]))
+                .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+        } else {
+            buf[..size].fill(0);
+        }
+
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+impl<D: Parser + Seeker> Seek for CisoReader<'_, D> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.total_size() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl<D: Parser + Seeker> Reader for CisoReader<'_, D> {}
+impl<D: Parser + Seeker> Seeker for CisoReader<'_, D> {}
+impl<D: Parser + Seeker> Parser for CisoReader<'_, D> {}
+
 /// Iterator over all blocks of a [CISO][`crate::ciso`] file.
 pub struct BlockIterator<'reader, 'x, D: Parser + Seeker> {
     reader: &'reader mut CisoReader<'x, D>,
@@ -170,3 +282,176 @@ impl<'reader, 'x, D: Parser + Seeker> Iterator for BlockIterator<'reader, 'x, D>
         }
     }
 }
+
+/// Iterator over all blocks of a [CISO][`crate::ciso`] file, see
+/// [`CisoReader::blocks_sparse`].
+pub struct SparseBlockIterator<'reader, 'x, D: Parser + Seeker> {
+    reader: &'reader mut CisoReader<'x, D>,
+    index:  usize,
+}
+
+impl<'reader, 'x, D: Parser + Seeker> Iterator for SparseBlockIterator<'reader, 'x, D> {
+    type Item = Result<(usize, Option<Vec<u8>>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.reader.block_count() {
+            None
+        } else {
+            let index = self.index;
+            self.index += 1;
+            if self.reader.block_present(index) {
+                Some(self.reader.read_block(index).map(|x| (index, Some(x))))
+            } else {
+                Some(Ok((index, None)))
+            }
+        }
+    }
+}
+
+/// Decides which blocks [`CisoBuilder`] treats as zero blocks and omits.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ZeroPolicy {
+    /// Only blocks where every byte is zero are omitted. The compression is
+    /// lossless.
+    #[default]
+    Exact,
+
+    /// Blocks where at least the given fraction (`0.0..=1.0`) of the bytes
+    /// are zero are omitted, e.g., `Threshold(0.999)` omits blocks that are
+    /// at least 99.9% zero.
+    ///
+    /// **This is lossy.** The non-zero bytes of an omitted block are
+    /// discarded and the block is decompressed as all zeros. Only use this
+    /// if that data is known to be unused, e.g., leftover garbage in the
+    /// unused regions of a disc image. `Threshold(1.0)` is the same as
+    /// [`ZeroPolicy::Exact`].
+    Threshold(f32),
+}
+
+impl ZeroPolicy {
+    /// Returns `true` if `block` should be omitted.
+    fn is_zero(&self, block: &[u8]) -> bool {
+        match self {
+            ZeroPolicy::Exact => block.iter().all(|x| *x == 0),
+            ZeroPolicy::Threshold(threshold) => {
+                let zeros = block.iter().filter(|x| **x == 0).count();
+                zeros as f64 >= *threshold as f64 * block.len() as f64
+            },
+        }
+    }
+}
+
+/// Builder for [CISO][`crate::ciso`] files.
+#[derive(Debug, Clone, Copy)]
+pub struct CisoBuilder {
+    block_size:  usize,
+    zero_policy: ZeroPolicy,
+}
+
+impl CisoBuilder {
+    /// Create a new [CISO][`crate::ciso`] builder that splits the input into
+    /// blocks of `block_size` bytes. The block size must be in the range
+    /// `0 < block_size <= 0x8000000`.
+    #[track_caller]
+    pub fn new(block_size: usize) -> Result<Self> {
+        ensure!(
+            block_size > 0 && block_size <= 0x8000000,
+            BuildProblem::InvalidRange("0 < block size <= 0x8000000", Location::current())
+        );
+        Ok(Self {
+            block_size,
+            zero_policy: ZeroPolicy::Exact,
+        })
+    }
+
+    /// Get the block size used by the builder.
+    pub fn block_size(&self) -> usize { self.block_size }
+
+    /// Set the [`ZeroPolicy`] that decides which blocks are omitted. The
+    /// default is [`ZeroPolicy::Exact`], see [`ZeroPolicy::Threshold`] for
+    /// the lossy alternative.
+    pub fn zero_policy(&mut self, zero_policy: ZeroPolicy) -> &mut Self {
+        self.zero_policy = zero_policy;
+        self
+    }
+
+    /// Read all data from `input` and write it as a [CISO][`crate::ciso`] file
+    /// to `output`. Blocks that only contain zero bytes (or are treated as
+    /// such by the [`ZeroPolicy`]) are omitted, except for the last block
+    /// which is always stored, as zeros, so that the decompressed size is
+    /// preserved. If the input size is not a multiple of the block size, the
+    /// last block is padded with zeros.
+    ///
+    /// The input must be non-empty and fit in 32760 blocks. Returns
+    /// [`BuildProblem::InvalidRange`] if the [`ZeroPolicy::Threshold`] is
+    /// not in `0.0..=1.0`.
+    pub fn build<R: Read, W: Writer + Seeker>(&self, input: &mut R, output: &mut W) -> Result<()> {
+        if let ZeroPolicy::Threshold(threshold) = self.zero_policy {
+            ensure!(
+                (0.0..=1.0).contains(&threshold),
+                BuildProblem::InvalidRange("0.0 <= zero threshold <= 1.0", Location::current())
+            );
+        }
+
+        let start = output.position()?;
+        output.bu32(MAGIC)?;
+        output.lu32(self.block_size as u32)?;
+        output.zeros(MAX_BLOCKS)?;
+
+        let mut block_map = vec![0_u8; MAX_BLOCKS];
+        let mut buffer = vec![0_u8; self.block_size];
+        let mut count = 0;
+        let mut last_is_zero = false;
+        loop {
+            buffer.fill(0);
+            if read_block(input, &mut buffer)? == 0 {
+                break;
+            }
+
+            ensure!(
+                count < MAX_BLOCKS,
+                BuildProblem::InvalidRange("0 < block count <= 32760", Location::current())
+            );
+
+            last_is_zero = self.zero_policy.is_zero(&buffer);
+            if !last_is_zero {
+                block_map[count] = 1;
+                output.u8_array(&buffer)?;
+            }
+            count += 1;
+        }
+
+        ensure!(
+            count > 0,
+            BuildProblem::InvalidRange("0 < block count <= 32760", Location::current())
+        );
+
+        if last_is_zero {
+            buffer.fill(0);
+            block_map[count - 1] = 1;
+            output.u8_array(&buffer)?;
+        }
+
+        let end = output.position()?;
+        output.goto(start + 8)?;
+        output.u8_array(&block_map)?;
+        output.goto(end)?;
+        Ok(())
+    }
+}
+
+/// Fill `buffer` with data from `input`. Returns the number of bytes read,
+/// which is less than the buffer size only at the end of the input.
+#[track_caller]
+fn read_block<R: Read>(input: &mut R, buffer: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match input.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(Error::ReadFailed(buffer.len(), e, Location::caller())),
+        }
+    }
+    Ok(filled)
+}
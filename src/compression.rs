@@ -0,0 +1,104 @@
+//! Transparent decompression of [Yaz0][`crate::yaz0`] and
+//! [Yay0][`crate::yay0`] data.
+//!
+//! [`AutoDecompressReader`] sniffs the magic at the current position of the
+//! input and decompresses the data if it's Yaz0 or Yay0 compressed. Data that
+//! isn't compressed is passed through as is. This makes it possible to parse
+//! files that may or may not be compressed without checking first, e.g.,
+//! archives that are sometimes shipped compressed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::{AutoDecompressReader, RarcReader};
+//!
+//! fn main() -> Result<()> {
+//!     let file = File::open("archive.arc")?;
+//!     let archive = RarcReader::new(AutoDecompressReader::new(file)?)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Cursor, Read, Seek};
+
+use crate::helper::{Parser, Reader, Seeker};
+use crate::{yay0, yaz0, Result};
+
+/// Compression detected by [`AutoDecompressReader`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    /// Not compressed, the input is passed through.
+    None,
+    /// Yaz0 compressed, see [`crate::yaz0`].
+    Yaz0,
+    /// Yay0 compressed, see [`crate::yay0`].
+    Yay0,
+}
+
+/// Reader that decompresses Yaz0 or Yay0 compressed data up front, or passes
+/// the input through if it isn't compressed.
+///
+/// When the input is compressed, positions are relative to the start of the
+/// decompressed data. Otherwise, positions are those of the underlying
+/// reader.
+pub struct AutoDecompressReader<D: Parser + Seeker> {
+    reader:       D,
+    compression:  Compression,
+    decompressed: Option<Cursor<Vec<u8>>>,
+}
+
+impl<D: Parser + Seeker> AutoDecompressReader<D> {
+    /// Creates a new reader, decompressing the input if it starts with a Yaz0
+    /// or Yay0 header.
+    pub fn new(mut reader: D) -> Result<AutoDecompressReader<D>> {
+        let compression = if yaz0::is_yaz0(&mut reader) {
+            Compression::Yaz0
+        } else if yay0::is_yay0(&mut reader) {
+            Compression::Yay0
+        } else {
+            Compression::None
+        };
+
+        let decompressed = match compression {
+            Compression::Yaz0 => Some(yaz0::decompress_all(&mut reader)?),
+            Compression::Yay0 => Some(yay0::decompress(&mut reader)?),
+            Compression::None => None,
+        };
+
+        Ok(AutoDecompressReader {
+            reader,
+            compression,
+            decompressed: decompressed.map(Cursor::new),
+        })
+    }
+
+    /// The compression of the input.
+    pub fn compression(&self) -> Compression { self.compression }
+
+    /// Consumes the reader, returning the underlying reader.
+    pub fn into_inner(self) -> D { self.reader }
+}
+
+impl<D: Parser + Seeker> Read for AutoDecompressReader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.decompressed.as_mut() {
+            Some(decompressed) => decompressed.read(buf),
+            None => self.reader.read(buf),
+        }
+    }
+}
+
+impl<D: Parser + Seeker> Seek for AutoDecompressReader<D> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        match self.decompressed.as_mut() {
+            Some(decompressed) => decompressed.seek(pos),
+            None => self.reader.seek(pos),
+        }
+    }
+}
+
+impl<D: Parser + Seeker> Reader for AutoDecompressReader<D> {}
+impl<D: Parser + Seeker> Seeker for AutoDecompressReader<D> {}
+impl<D: Parser + Seeker> Parser for AutoDecompressReader<D> {}
@@ -22,13 +22,16 @@
 //! ```
 
 use std::io::Cursor;
+use std::ops::Range;
+use std::panic::Location;
 
+use crate::error::BuildProblem;
 use crate::helper::alignment::AlignPowerOfTwo;
-use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
-use crate::Result;
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker, Writer};
+use crate::{elf, Elf, Result};
 
 /// Dolphin executable header.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Header {
     /// Offset of the text sections.
     pub text_offset: [u32; 7],
@@ -73,7 +76,7 @@ pub enum SectionKind {
 }
 
 /// Dolphin executable section.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Section {
     /// The kind of section this is (text, data, or bss).
     pub kind: SectionKind,
@@ -108,7 +111,7 @@ pub struct Section {
 /// such as, the original unaligned section size. At startup the
 /// [`__rom_copy_info`][`RomCopyInfo`] is used to copy each entry from the ROM
 /// to the RAM.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct RomCopyInfo {
     /// Read Only Memory (ROM) address of the section.
     pub rom_address: u32,
@@ -121,7 +124,7 @@ pub struct RomCopyInfo {
 }
 
 /// List of [`RomCopyInfo`][`RomCopyInfo`] entries.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RomCopyInfoList {
     /// The offset of `__rom_copy_info` in the `.init` section.
     pub offset: u32,
@@ -138,7 +141,7 @@ pub struct RomCopyInfoList {
 /// `.bss` section with the size of the sum of all the `.bss` sections. At
 /// startup the [`__bss_init_info`][`BssInitInfo`] is used to zero out the
 /// `.bss` section in RAM.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct BssInitInfo {
     /// Random Access Memory (RAM) address of the section.
     pub ram_address: u32,
@@ -148,7 +151,7 @@ pub struct BssInitInfo {
 }
 
 /// List of [`BssInitInfo`][`BssInitInfo`] entries.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BssInitInfoList {
     /// The offset of `__bss_init_info` in the `.init` section.
     pub offset: u32,
@@ -158,7 +161,7 @@ pub struct BssInitInfoList {
 }
 
 /// `.dol` file object.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dol {
     /// Header.
     pub header: Header,
@@ -241,6 +244,37 @@ fn rom_copy_info_search(data: &[u8], address: u32) -> Option<RomCopyInfoList> {
     None
 }
 
+/// Check if the given data looks like a [DOL][`crate::dol`] executable. DOL
+/// files have no magic, so this checks that there is at least one text section
+/// and that every text and data section is located after the header and fits
+/// in the file. The stream position is restored.
+pub fn is_dol<D: Parser + Seeker>(input: &mut D) -> bool {
+    let Ok(base) = input.position() else {
+        return false;
+    };
+
+    let mut check = || -> Result<bool> {
+        let text_offset = input.bu32_array::<7>()?;
+        let data_offset = input.bu32_array::<11>()?;
+        let _ = input.bu32_array::<7>()?;
+        let _ = input.bu32_array::<11>()?;
+        let text_size = input.bu32_array::<7>()?;
+        let data_size = input.bu32_array::<11>()?;
+        let end = input.seek(std::io::SeekFrom::End(0))? - base;
+
+        let text_sections = text_offset.iter().zip(text_size.iter());
+        let data_sections = data_offset.iter().zip(data_size.iter());
+        let valid = text_sections
+            .chain(data_sections)
+            .filter(|(_, size)| **size > 0)
+            .all(|(offset, size)| *offset >= 0x100 && *offset as u64 + *size as u64 <= end);
+        Ok(valid && text_size.iter().any(|x| *x > 0))
+    };
+
+    let is_dol = check().unwrap_or(false);
+    input.goto(base).is_ok() && is_dol
+}
+
 /// Search 0x200 bytes from the end of `data` (from the `.init` section)
 /// until we find all `__bss_init_info` entries.
 fn bss_init_info_search(data: &[u8], address: u32) -> Option<BssInitInfoList> {
@@ -292,17 +326,9 @@ impl Section {
     }
 
     fn read_data<D: Parser + Seeker>(&mut self, reader: &mut D, base: u64) -> Result<()> {
-        if self.size > 0 && self.offset.is_some() {
-            ensure!(
-                self.size <= 0x2000000,
-                ParseProblem::InvalidRange(
-                    "section size (too large)",
-                    std::panic::Location::current()
-                )
-            );
-
-            reader.goto(base + self.offset.unwrap() as u64)?;
-            self.data = reader.read_as_vec(self.size as usize)?;
+        if let (true, Some(offset)) = (self.size > 0, self.offset) {
+            reader.goto(base + offset as u64)?;
+            self.data = reader.read_as_vec_max(self.size as usize, 0x2000000)?;
         }
 
         Ok(())
@@ -386,11 +412,18 @@ impl Dol {
             .map(|(kind, index, (offset, address, size))| {
                 ensure!(
                     offset.checked_add(size).is_some(),
-                    ParseProblem::InvalidData(
+                    ParseProblem::InvalidRange(
                         "offset + size overflow",
                         std::panic::Location::current()
                     )
                 );
+                ensure!(
+                    address.checked_add(size).is_some(),
+                    ParseProblem::InvalidRange(
+                        "address + size overflow",
+                        std::panic::Location::current()
+                    )
+                );
                 let section = Section::new(kind, index, offset, address, size, size);
                 match section {
                     Ok(mut section) => {
@@ -491,6 +524,45 @@ impl Dol {
     #[inline]
     pub fn entry_point(&self) -> u32 { self.header.entry_point }
 
+    /// Returns the entries of the `__rom_copy_info` symbol if it was found at
+    /// the end of the `.init` section while parsing, or [`None`] otherwise,
+    /// e.g., for DOLs converted from `.elf` files. The list including its
+    /// offset in `.init` is available via [`Dol::rom_copy_info`][field].
+    ///
+    /// [field]: #structfield.rom_copy_info
+    #[inline]
+    pub fn rom_copy_info(&self) -> Option<&[RomCopyInfo]> {
+        self.rom_copy_info.as_ref().map(|x| x.entries.as_slice())
+    }
+
+    /// Returns the entries of the `__bss_init_info` symbol if it was found at
+    /// the end of the `.init` section while parsing, or [`None`] otherwise.
+    /// The list including its offset in `.init` is available via
+    /// [`Dol::bss_init_info`][field].
+    ///
+    /// [field]: #structfield.bss_init_info
+    #[inline]
+    pub fn bss_init_info(&self) -> Option<&[BssInitInfo]> {
+        self.bss_init_info.as_ref().map(|x| x.entries.as_slice())
+    }
+
+    /// Returns the offset where the section data of the [DOL][`crate::dol`]
+    /// file ends, i.e., the largest `offset + size` of all text and data
+    /// sections, or the header size if there are no sections. `.dol` files are
+    /// often padded (e.g. when stored on disc), anything after this offset is
+    /// not part of the executable.
+    pub fn data_end(&self) -> u32 {
+        let header = &self.header;
+        let text_sections = header.text_offset.iter().zip(header.text_size.iter());
+        let data_sections = header.data_offset.iter().zip(header.data_size.iter());
+        text_sections
+            .chain(data_sections)
+            .filter(|(_, size)| **size > 0)
+            .map(|(offset, size)| offset.saturating_add(*size))
+            .max()
+            .unwrap_or(0x100)
+    }
+
     /// Returns an [`Some(&Section)`] if the [DOL][`crate::dol`] file contains a
     /// section with the given name `name` or [`None`] otherwise. Section
     /// names are not information provided by the `.dol` format, instead we
@@ -501,6 +573,45 @@ impl Dol {
         self.sections.iter().find(|x| x.name == name)
     }
 
+    /// Iterate over the text and data sections in the order they appear in
+    /// the file, i.e., sorted by [`Section::offset`]. `.bss` sections have
+    /// no file offset and are skipped.
+    pub fn sections_by_offset(&self) -> impl Iterator<Item = &Section> {
+        let mut sections = self
+            .sections
+            .iter()
+            .filter(|x| x.offset.is_some())
+            .collect::<Vec<_>>();
+        sections.sort_by_key(|x| x.offset);
+        sections.into_iter()
+    }
+
+    /// File ranges between the end of the header and [`Dol::data_end`] that
+    /// aren't covered by any text or data section, e.g., alignment padding or
+    /// leftover data. Data appended after the last section isn't included,
+    /// compare the file size with [`Dol::data_end`] to detect it.
+    pub fn gaps(&self) -> Vec<Range<u32>> {
+        let header = &self.header;
+        let text_sections = header.text_offset.iter().zip(header.text_size.iter());
+        let data_sections = header.data_offset.iter().zip(header.data_size.iter());
+        let mut ranges = text_sections
+            .chain(data_sections)
+            .filter(|(_, size)| **size > 0)
+            .map(|(offset, size)| *offset..offset.saturating_add(*size))
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|x| x.start);
+
+        let mut gaps = Vec::new();
+        let mut position = 0x100;
+        for range in ranges {
+            if range.start > position {
+                gaps.push(position..range.start);
+            }
+            position = position.max(range.end);
+        }
+        gaps
+    }
+
     /// Returns an [`Some(&Section)`] if the [DOL][`crate::dol`] file contains a
     /// section with that contains the given address `address` or [`None`]
     /// otherwise.
@@ -508,6 +619,319 @@ impl Dol {
     pub fn section_by_address(&self, address: u32) -> Option<&Section> {
         self.sections
             .iter()
-            .find(|x| address >= x.address && address < x.address + x.size)
+            .find(|x| address >= x.address && address - x.address < x.size)
+    }
+
+    /// Returns the offset in the [DOL][`crate::dol`] file of the byte loaded
+    /// at the virtual address `address`, i.e., [`Section::offset`] plus the
+    /// distance from [`Section::address`], or [`None`] if the address isn't
+    /// inside a text or data section. `.bss` sections have no file offset.
+    pub fn address_to_offset(&self, address: u32) -> Option<u32> {
+        self.sections.iter().find_map(|x| {
+            let offset = x.offset?;
+            let delta = address.checked_sub(x.address)?;
+            (delta < x.size).then(|| offset + delta)
+        })
+    }
+
+    /// Returns the `len` bytes loaded at the virtual address `address`.
+    ///
+    /// The whole range must be inside a single text or data section, otherwise
+    /// [`ParseProblem::InvalidRange`] is returned. This includes ranges that
+    /// cross a section boundary or that are inside a `.bss` section.
+    pub fn read(&self, address: u32, len: usize) -> Result<&[u8]> {
+        let section = self
+            .sections
+            .iter()
+            .filter(|x| x.kind != SectionKind::Bss)
+            .find(|x| address >= x.address && address - x.address < x.size)
+            .ok_or(ParseProblem::InvalidRange(
+                "address not in a text or data section",
+                std::panic::Location::current(),
+            ))?;
+
+        let start = (address - section.address) as usize;
+        let end = section.data.len().min(section.size as usize);
+        ensure!(
+            start <= end && len <= end - start,
+            ParseProblem::InvalidRange(
+                "range crosses a section boundary",
+                std::panic::Location::current()
+            )
+        );
+
+        Ok(&section.data[start..start + len])
+    }
+
+    /// Build the memory image the [DOL][`crate::dol`] file would produce when
+    /// loaded. Returns the lowest address covered by any section and a
+    /// buffer where the data of every text and data section is copied to
+    /// `address - base`. `.bss` sections are zero-filled. Sections without
+    /// any size are ignored.
+    ///
+    /// Returns [`BuildProblem::OverlappingSections`] if two text/data sections
+    /// or two `.bss` sections cover the same memory. A `.bss` section is
+    /// allowed to overlap data sections, without `__bss_init_info` the single
+    /// `.bss` section from the header usually spans `.sdata` and `.sdata2`.
+    pub fn to_memory_image(&self) -> Result<(u32, Vec<u8>)> {
+        let mut ranges = self
+            .sections
+            .iter()
+            .map(|x| {
+                let size = match x.kind {
+                    SectionKind::Bss => x.size,
+                    _ => x.data.len() as u32,
+                };
+                (x, x.address as u64, x.address as u64 + size as u64)
+            })
+            .filter(|(_, start, end)| start < end)
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|(_, start, _)| *start);
+
+        for is_bss in [false, true] {
+            let ranges = ranges
+                .iter()
+                .filter(|x| (x.0.kind == SectionKind::Bss) == is_bss)
+                .collect::<Vec<_>>();
+            for pair in ranges.windows(2) {
+                let (a, _, a_end) = pair[0];
+                let (b, b_start, _) = pair[1];
+                ensure!(
+                    a_end <= b_start,
+                    BuildProblem::OverlappingSections(
+                        a.name,
+                        b.name,
+                        std::panic::Location::current()
+                    )
+                );
+            }
+        }
+
+        let base = ranges.first().map_or(0, |x| x.1);
+        let end = ranges.iter().map(|x| x.2).max().unwrap_or(0);
+        ensure!(
+            end - base <= 0x2000000,
+            BuildProblem::InvalidRange("memory image too large", std::panic::Location::current())
+        );
+
+        let mut image = vec![0_u8; (end - base) as usize];
+        for (section, start, _) in ranges {
+            if section.kind != SectionKind::Bss {
+                let offset = (start - base) as usize;
+                image[offset..offset + section.data.len()].copy_from_slice(&section.data);
+            }
+        }
+
+        Ok((base as u32, image))
+    }
+
+    /// Convert an [ELF][`crate::elf`] executable to a [`Dol`].
+    ///
+    /// Allocated `SHT_PROGBITS` sections are assigned, in section header
+    /// order, to the text slots if they are executable and to the data slots
+    /// otherwise. All allocated `SHT_NOBITS` sections are merged into the
+    /// single `.bss` range of the header. Section data is placed after the
+    /// header, each section aligned to 32 bytes and padded to a multiple of 32
+    /// bytes. The entry point is taken from the ELF header.
+    ///
+    /// Returns [`BuildProblem::InvalidRange`] if there are more than 7 text
+    /// sections or more than 11 data sections.
+    pub fn from_elf(elf: &Elf) -> Result<Dol> {
+        let allocated = elf
+            .sections
+            .iter()
+            .filter(|x| x.is_alloc() && x.size > 0)
+            .collect::<Vec<_>>();
+        let progbits = allocated
+            .iter()
+            .filter(|x| x.kind == elf::SectionKind::ProgBits);
+        let text = progbits.clone().filter(|x| x.is_executable()).collect::<Vec<_>>();
+        let data = progbits.filter(|x| !x.is_executable()).collect::<Vec<_>>();
+        let bss = allocated
+            .iter()
+            .filter(|x| x.kind == elf::SectionKind::NoBits)
+            .collect::<Vec<_>>();
+
+        ensure!(
+            text.len() <= 7,
+            BuildProblem::InvalidRange("too many text sections (max 7)", Location::current())
+        );
+        ensure!(
+            data.len() <= 11,
+            BuildProblem::InvalidRange("too many data sections (max 11)", Location::current())
+        );
+
+        let mut header = Header {
+            text_offset:  [0; 7],
+            data_offset:  [0; 11],
+            text_address: [0; 7],
+            data_address: [0; 11],
+            text_size:    [0; 7],
+            data_size:    [0; 11],
+            bss_address:  0,
+            bss_size:     0,
+            entry_point:  elf.entry_point(),
+        };
+
+        let mut sections = Vec::new();
+        let mut offset = 0x100_u32;
+        let slots = text
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (SectionKind::Text, i, x))
+            .chain(data.iter().enumerate().map(|(i, x)| (SectionKind::Data, i, x)));
+        for (kind, index, section) in slots {
+            let aligned_size = section.size.align_next(32);
+            let end = offset.checked_add(aligned_size);
+            ensure!(
+                end.is_some(),
+                BuildProblem::InvalidRange("executable too large", Location::current())
+            );
+
+            let (offsets, addresses, sizes) = match kind {
+                SectionKind::Text => (
+                    &mut header.text_offset[..],
+                    &mut header.text_address[..],
+                    &mut header.text_size[..],
+                ),
+                _ => (
+                    &mut header.data_offset[..],
+                    &mut header.data_address[..],
+                    &mut header.data_size[..],
+                ),
+            };
+            offsets[index] = offset;
+            addresses[index] = section.address;
+            sizes[index] = aligned_size;
+
+            let mut data = section.data.clone();
+            data.resize(aligned_size as usize, 0);
+            sections.push(Section {
+                kind,
+                name: Section::guess_name(kind, index),
+                address: section.address,
+                size: section.size,
+                aligned_size,
+                data,
+                offset: Some(offset),
+            });
+            offset = end.unwrap_or(0);
+        }
+
+        if let Some(start) = bss.iter().map(|x| x.address).min() {
+            let end = bss
+                .iter()
+                .map(|x| x.address as u64 + x.size as u64)
+                .max()
+                .unwrap_or(0);
+            ensure!(
+                end - start as u64 <= u32::MAX as u64,
+                BuildProblem::InvalidRange("bss too large", Location::current())
+            );
+            header.bss_address = start;
+            header.bss_size = (end - start as u64) as u32;
+            sections.push(Section {
+                kind:         SectionKind::Bss,
+                name:         Section::guess_name(SectionKind::Bss, 0),
+                address:      header.bss_address,
+                size:         header.bss_size,
+                aligned_size: header.bss_size,
+                data:         vec![],
+                offset:       None,
+            });
+        }
+
+        Ok(Dol {
+            header,
+            rom_copy_info: None,
+            bss_init_info: None,
+            sections,
+        })
+    }
+
+    /// Write the [`Dol`] to `output`. The header is written as is, and the
+    /// data of each text and data section is written at the offset given by
+    /// the header, padded with zeros to the size given by the header.
+    ///
+    /// Returns [`BuildProblem::InvalidData`] if a section in the header has no
+    /// matching section or more data than the header size, and
+    /// [`BuildProblem::OverlappingSections`] if two sections overlap in the
+    /// file.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        let header = &self.header;
+        output.bu32_array(&header.text_offset)?;
+        output.bu32_array(&header.data_offset)?;
+        output.bu32_array(&header.text_address)?;
+        output.bu32_array(&header.data_address)?;
+        output.bu32_array(&header.text_size)?;
+        output.bu32_array(&header.data_size)?;
+        output.bu32(header.bss_address)?;
+        output.bu32(header.bss_size)?;
+        output.bu32(header.entry_point)?;
+        output.zeros(0x1C)?;
+
+        let text = header
+            .text_offset
+            .iter()
+            .zip(header.text_size.iter())
+            .map(|x| (SectionKind::Text, x));
+        let data = header
+            .data_offset
+            .iter()
+            .zip(header.data_size.iter())
+            .map(|x| (SectionKind::Data, x));
+        let mut slots = text
+            .chain(data)
+            .filter(|(_, (_, size))| **size > 0)
+            .map(|(kind, (offset, size))| {
+                let section = self
+                    .sections
+                    .iter()
+                    .find(|x| x.kind == kind && x.offset == Some(*offset))
+                    .ok_or(BuildProblem::InvalidData(
+                        "no section for header entry",
+                        Location::current(),
+                    ))?;
+                ensure!(
+                    section.data.len() <= *size as usize,
+                    BuildProblem::InvalidData(
+                        "section data larger than header size",
+                        Location::current()
+                    )
+                );
+                Ok((*offset, *size, section))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        slots.sort_by_key(|x| x.0);
+
+        let mut position = 0x100_u64;
+        let mut previous = "header";
+        for (offset, size, section) in slots {
+            ensure!(
+                offset as u64 >= position,
+                BuildProblem::OverlappingSections(previous, section.name, Location::current())
+            );
+            output.zeros((offset as u64 - position) as usize)?;
+            output.u8_array(&section.data)?;
+            output.zeros(size as usize - section.data.len())?;
+            position = offset as u64 + size as u64;
+            previous = section.name;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Dol {
+    /// Concise summary: entry point, section count and file size, e.g.,
+    /// `DOL entry 0x80003100, 3 sections, 0x00000520 bytes`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "DOL entry {:#010x}, {} sections, {:#010x} bytes",
+            self.entry_point(),
+            self.sections.len(),
+            self.data_end()
+        )
     }
 }
@@ -0,0 +1,278 @@
+//! Executable and Linkable Format ([ELF][`Elf`]).
+//!
+//! GameCube and Wii toolchains link to ELF files, which are later converted to
+//! [DOL][`crate::dol`] or [REL][`crate::rel`] files. This module only supports
+//! the subset used by these toolchains: 32-bit, big-endian, PowerPC
+//! (`EM_PPC`) files. The ELF header and the section header table are parsed,
+//! and the data of every section is read.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("main.elf")?;
+//!     let elf = picori::Elf::from_binary(&mut file)?;
+//!     if let Some(text) = elf.section_by_name(".text") {
+//!         println!(".text: {:#010x} ({:#x} bytes)", text.address, text.size);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, StringTable};
+use crate::{Ascii, Result};
+
+/// `e_machine` value for PowerPC.
+const EM_PPC: u16 = 20;
+
+/// `e_shstrndx` value for a file without section names.
+const SHN_UNDEF: u16 = 0;
+
+/// Size of a section header.
+const SECTION_HEADER_SIZE: u16 = 0x28;
+
+/// Section flag, the section is writable at runtime.
+const SHF_WRITE: u32 = 0x1;
+
+/// Section flag, the section occupies memory at runtime.
+const SHF_ALLOC: u32 = 0x2;
+
+/// Section flag, the section contains executable instructions.
+const SHF_EXECINSTR: u32 = 0x4;
+
+/// ELF header.
+#[derive(Debug, Clone)]
+pub struct Header {
+    /// Object file type (`e_type`), e.g., `2` for executables.
+    pub kind:                u16,
+    /// Processor-specific flags (`e_flags`).
+    pub flags:               u32,
+    /// Entry point address (`e_entry`).
+    pub entry_point:         u32,
+    /// Offset of the program header table (`e_phoff`).
+    pub program_offset:      u32,
+    /// Number of program headers (`e_phnum`).
+    pub program_count:       u16,
+    /// Offset of the section header table (`e_shoff`).
+    pub section_offset:      u32,
+    /// Number of section headers (`e_shnum`).
+    pub section_count:       u16,
+    /// Index of the section with the section names (`e_shstrndx`), zero if
+    /// the sections don't have names.
+    pub section_names_index: u16,
+}
+
+/// ELF section type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SectionKind {
+    /// Inactive section (`SHT_NULL`).
+    Null,
+    /// Program data (`SHT_PROGBITS`), e.g. `.text` or `.data`.
+    ProgBits,
+    /// Symbol table (`SHT_SYMTAB`).
+    SymTab,
+    /// String table (`SHT_STRTAB`).
+    StrTab,
+    /// Relocations with addends (`SHT_RELA`).
+    Rela,
+    /// Uninitialized data without any file data (`SHT_NOBITS`), e.g. `.bss`.
+    NoBits,
+    /// Relocations without addends (`SHT_REL`).
+    Rel,
+    /// Any other section type.
+    Other(u32),
+}
+
+/// ELF section.
+#[derive(Debug, Clone)]
+pub struct Section {
+    /// Section name, empty if the file has no section names.
+    pub name:       String,
+    /// Section type.
+    pub kind:       SectionKind,
+    /// Section flags (`sh_flags`).
+    pub flags:      u32,
+    /// Address of the section in memory.
+    pub address:    u32,
+    /// Offset of the section data in the file.
+    pub offset:     u32,
+    /// Size of the section in bytes.
+    pub size:       u32,
+    /// Section index link, the meaning depends on the section type.
+    pub link:       u32,
+    /// Extra information, the meaning depends on the section type.
+    pub info:       u32,
+    /// Alignment of the section address.
+    pub alignment:  u32,
+    /// Size of each entry for sections with fixed-size entries.
+    pub entry_size: u32,
+    /// Section data. For [`SectionKind::NoBits`] sections, this will be an
+    /// empty vector.
+    pub data:       Vec<u8>,
+}
+
+/// ELF file.
+#[derive(Debug, Clone)]
+pub struct Elf {
+    /// The ELF header.
+    pub header:   Header,
+    /// Sections in section header table order, including the null section at
+    /// index 0.
+    pub sections: Vec<Section>,
+}
+
+impl SectionKind {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::Null,
+            1 => Self::ProgBits,
+            2 => Self::SymTab,
+            3 => Self::StrTab,
+            4 => Self::Rela,
+            8 => Self::NoBits,
+            9 => Self::Rel,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl Section {
+    /// Returns `true` if the section occupies memory at runtime
+    /// (`SHF_ALLOC`).
+    pub fn is_alloc(&self) -> bool { self.flags & SHF_ALLOC != 0 }
+
+    /// Returns `true` if the section contains executable instructions
+    /// (`SHF_EXECINSTR`).
+    pub fn is_executable(&self) -> bool { self.flags & SHF_EXECINSTR != 0 }
+
+    /// Returns `true` if the section is writable at runtime (`SHF_WRITE`).
+    pub fn is_writable(&self) -> bool { self.flags & SHF_WRITE != 0 }
+}
+
+impl Elf {
+    /// Parse [`Elf`] from binary stream.
+    ///
+    /// Returns [`ParseProblem::InvalidHeader`] if the file isn't a 32-bit,
+    /// big-endian, PowerPC ELF file.
+    pub fn from_binary<D: Parser + Seeker>(reader: &mut D) -> Result<Self> {
+        let base = reader.position()?;
+        let ident = reader.u8_array::<16>()?;
+        ensure!(
+            ident[0..4] == *b"\x7FELF",
+            ParseProblem::InvalidMagic("invalid ELF magic", Location::current())
+        );
+        ensure!(
+            ident[4] == 1,
+            ParseProblem::InvalidHeader("not a 32-bit ELF (ELFCLASS32)", Location::current())
+        );
+        ensure!(
+            ident[5] == 2,
+            ParseProblem::InvalidHeader("not a big-endian ELF (ELFDATA2MSB)", Location::current())
+        );
+
+        let kind = reader.bu16()?;
+        let machine = reader.bu16()?;
+        let _version = reader.bu32()?;
+        let entry_point = reader.bu32()?;
+        let program_offset = reader.bu32()?;
+        let section_offset = reader.bu32()?;
+        let flags = reader.bu32()?;
+        let _header_size = reader.bu16()?;
+        let _program_entry_size = reader.bu16()?;
+        let program_count = reader.bu16()?;
+        let section_entry_size = reader.bu16()?;
+        let section_count = reader.bu16()?;
+        let section_names_index = reader.bu16()?;
+
+        ensure!(
+            machine == EM_PPC,
+            ParseProblem::InvalidHeader("not a PowerPC ELF (EM_PPC)", Location::current())
+        );
+        ensure!(
+            section_count == 0 || section_entry_size == SECTION_HEADER_SIZE,
+            ParseProblem::InvalidHeader("invalid section header size", Location::current())
+        );
+        ensure!(
+            section_count == 0 || section_names_index < section_count,
+            ParseProblem::InvalidHeader("section name index out of bounds", Location::current())
+        );
+
+        let header = Header {
+            kind,
+            flags,
+            entry_point,
+            program_offset,
+            program_count,
+            section_offset,
+            section_count,
+            section_names_index,
+        };
+
+        let mut sections = Vec::with_capacity(section_count as usize);
+        let mut name_offsets = Vec::with_capacity(section_count as usize);
+        for i in 0..section_count as u64 {
+            reader.goto(base + section_offset as u64 + i * SECTION_HEADER_SIZE as u64)?;
+            name_offsets.push(reader.bu32()?);
+            let kind = SectionKind::from_u32(reader.bu32()?);
+            let flags = reader.bu32()?;
+            let address = reader.bu32()?;
+            let offset = reader.bu32()?;
+            let size = reader.bu32()?;
+            let link = reader.bu32()?;
+            let info = reader.bu32()?;
+            let alignment = reader.bu32()?;
+            let entry_size = reader.bu32()?;
+
+            let data = match kind {
+                SectionKind::Null | SectionKind::NoBits => Vec::new(),
+                _ => {
+                    reader.goto(base + offset as u64)?;
+                    reader.read_as_vec_max(size as usize, 0x8000000)?
+                },
+            };
+
+            sections.push(Section {
+                name: String::new(),
+                kind,
+                flags,
+                address,
+                offset,
+                size,
+                link,
+                info,
+                alignment,
+                entry_size,
+                data,
+            });
+        }
+
+        let names = match section_names_index {
+            SHN_UNDEF => None,
+            index => sections.get(index as usize),
+        };
+        if let Some(names) = names {
+            let names = StringTable::new(names.data.clone());
+            for (section, offset) in sections.iter_mut().zip(name_offsets) {
+                section.name = names.get::<Ascii>(offset)?;
+            }
+        }
+
+        Ok(Self { header, sections })
+    }
+
+    /// Entry point address.
+    #[inline]
+    pub fn entry_point(&self) -> u32 { self.header.entry_point }
+
+    /// Returns an [`Some(&Section)`] if the [ELF][`Elf`] file contains a
+    /// section with the given name `name` or [`None`] otherwise.
+    #[inline]
+    pub fn section_by_name(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|x| x.name == name)
+    }
+}
@@ -0,0 +1,299 @@
+//! [`EucJp`] encoding.
+//!
+//! [EUC-JP][`EucJp`] (Extended Unix Code for Japanese) is a variable-width
+//! encoding for Japanese text. Unlike [Shift JIS][`crate::ShiftJis1997`], the
+//! first 128 characters are plain [ASCII][`crate::Ascii`]. Characters from
+//! `JIS X 0208` are encoded as two bytes, both in the range `[0xA1,0xFE]`,
+//! where the row and cell of the character are offset by `0xA0`. Half-width
+//! katakana from [JIS X 0201][`crate::JisX0201`] are encoded as the byte
+//! `0x8E` followed by the katakana byte in the range `[0xA1,0xDF]`.
+//!
+//! The `JIS X 0208` character set is shared with [Shift JIS
+//! 1997][`crate::ShiftJis1997`], and the same mapping table is used for both.
+//! The three-byte `JIS X 0212` sequences (introduced by `0x8F`) are not
+//! supported.
+//!
+//! # References
+//! - [Extended Unix Code](https://en.wikipedia.org/wiki/Extended_Unix_Code)
+//! - [JIS X 0208](https://en.wikipedia.org/wiki/JIS_X_0208)
+
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+
+use crate::error::DecodingProblem::*;
+use crate::error::EncodingProblem;
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
+use crate::{JisX0201, Result, ShiftJis1997};
+
+/// [`EucJp`] encoding.
+pub struct EucJp {}
+
+enum Next {
+    EndOfInput,
+    One(char),
+}
+
+/// A iterator decoder for the [`EucJp`] encoding.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:     <I as IntoIterator>::IntoIter,
+    position: usize,
+    _marker:  PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I) -> Decoder<'x, I> {
+        Decoder {
+            iter:     iter.into_iter(),
+            position: 0,
+            _marker:  PhantomData,
+        }
+    }
+
+    /// Get the next byte and its index in the input.
+    fn next_byte(&mut self) -> Option<(u8, usize)> {
+        let byte = *self.iter.next()?.borrow();
+        self.position += 1;
+        Some((byte, self.position - 1))
+    }
+
+    fn decode_next(&mut self) -> Result<Next> {
+        if let Some((byte, index)) = self.next_byte() {
+            match byte {
+                // ASCII character
+                0x00..=0x7F => Ok(Next::One(byte as char)),
+                // Single-shift to half-width katakana
+                0x8E => {
+                    let (next, next_index) = self
+                        .next_byte()
+                        .ok_or_else(|| UnexpectedEndOfData(Location::current()))?;
+                    match next {
+                        0xA1..=0xDF => Ok(Next::One(
+                            char::from_u32(0xFF61 + (next - 0xA1) as u32).unwrap(),
+                        )),
+                        _ => Err(InvalidByte(next, next_index, Location::current()).into()),
+                    }
+                },
+                // First byte of a double-byte JIS X 0208 character
+                0xA1..=0xFE => {
+                    let (next, next_index) = self
+                        .next_byte()
+                        .ok_or_else(|| UnexpectedEndOfData(Location::current()))?;
+                    match next {
+                        0xA1..=0xFE => {
+                            let (lead, trail) = EucJp::to_shift_jis(byte - 0xA0, next - 0xA0);
+                            let c = ShiftJis1997::decode_double(lead, trail)
+                                .ok_or_else(|| InvalidByte(next, next_index, Location::current()))?;
+                            Ok(Next::One(c))
+                        },
+                        _ => Err(InvalidByte(next, next_index, Location::current()).into()),
+                    }
+                },
+                // Invalid as first byte
+                _ => Err(InvalidByte(byte, index, Location::current()).into()),
+            }
+        } else {
+            Ok(Next::EndOfInput)
+        }
+    }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.decode_next() {
+            Ok(Next::EndOfInput) => None,
+            Ok(Next::One(c)) => Some(Ok(c)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl EucJp {
+    /// Convert a `JIS X 0208` row and cell (both `[1,94]`) to the equivalent
+    /// Shift JIS lead and trail byte.
+    fn to_shift_jis(row: u8, cell: u8) -> (u8, u8) {
+        let lead = row.div_ceil(2) + if row <= 62 { 0x80 } else { 0xC0 };
+        let trail = if row.is_multiple_of(2) {
+            cell + 0x9E
+        } else if cell >= 0x40 {
+            cell + 0x40
+        } else {
+            cell + 0x3F
+        };
+        (lead, trail)
+    }
+
+    /// Convert a Shift JIS lead and trail byte to the equivalent `JIS X 0208`
+    /// row and cell.
+    fn from_shift_jis(lead: u8, trail: u8) -> (u8, u8) {
+        let base = if lead <= 0x9F { 0x81 } else { 0xC1 };
+        let row = (lead - base) * 2 + 1;
+        if trail >= 0x9F {
+            (row + 1, trail - 0x9E)
+        } else if trail > 0x7F {
+            (row, trail - 0x40)
+        } else {
+            (row, trail - 0x3F)
+        }
+    }
+
+    /// Encode a single character. Returns the encoded bytes and the number of
+    /// bytes used, or `None` if the character can't be represented in
+    /// [EUC-JP][`EucJp`].
+    pub fn encode_char(c: char) -> Option<([u8; 2], usize)> {
+        if (c as u32) < 0x80 {
+            return Some(([c as u8, 0], 1));
+        }
+
+        if let Some(byte @ 0xA1..=0xDF) = JisX0201::encode_char(c) {
+            return Some(([0x8E, byte], 2));
+        }
+
+        match ShiftJis1997::encode_char(c)? {
+            ([lead, trail], 2) => {
+                let (row, cell) = Self::from_shift_jis(lead, trail);
+                Some(([row + 0xA0, cell + 0xA0], 2))
+            },
+            _ => None,
+        }
+    }
+
+    /// Create an iterator that decodes the given iterator of bytes into
+    /// characters.
+    pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Decoder::new(iter)
+    }
+
+    /// Decode all bytes into a string. Will continue passed NULL bytes and only
+    /// stop at the end of the iterator or if an decoding error occurs.
+    pub fn all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).collect()
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator.
+    pub fn first<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+}
+
+/// Extension trait for iterators of bytes and adds the helper function
+/// [`IteratorExt::euc_jp`] for decoding as [EUC-JP][`EucJp`] strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [EUC-JP][`EucJp`].
+    fn euc_jp<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+impl ParseStringEncoding for EucJp {
+    fn parse_str<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::first(iter)
+    }
+
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for c in data.chars() {
+            let Some((bytes, length)) = Self::encode_char(c) else {
+                return Err(EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into());
+            };
+            if i + length > buffer.len() {
+                return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+            }
+            buffer[i..i + length].copy_from_slice(&bytes[..length]);
+            i += length;
+        }
+        Ok(i)
+    }
+
+    fn from_binary(reader: &mut impl Parser) -> Result<String> {
+        Self::first(parser_bytes(reader))
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_str() {
+        let data = b"abc\xb0\xa1\0def";
+        assert_eq!(EucJp::parse_str(data).unwrap(), "abc亜");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 8];
+        let length = EucJp::write_str("a\u{ff61}亜", &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"a\x8e\xa1\xb0\xa1");
+        assert!(EucJp::write_str("\u{1f600}", &mut buffer).is_err());
+        assert!(EucJp::write_str("亜", &mut buffer[..1]).is_err());
+    }
+
+    #[test]
+    fn shift_jis_round_trip() {
+        for row in 1..=94 {
+            for cell in 1..=94 {
+                let (lead, trail) = EucJp::to_shift_jis(row, cell);
+                assert_eq!(EucJp::from_shift_jis(lead, trail), (row, cell));
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Detect the [format][`Format`] of a binary stream.
+//!
+//! [`detect_format`] probes the stream with the `is_*` sniffers of each
+//! module, e.g., [`yaz0::is_yaz0`][`crate::yaz0::is_yaz0`] or
+//! [`gcm::is_gcm`][`crate::gcm::is_gcm`]. Formats with a magic are checked
+//! first, followed by [DOL][`crate::dol`] and [REL][`crate::rel`] which are
+//! only detected by sanity-checking their headers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::Format;
+//!
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("unknown.bin")?;
+//!     match picori::detect_format(&mut file)? {
+//!         Format::Dol => println!("{:?}", picori::Dol::from_binary(&mut file)?.entry_point()),
+//!         format => println!("{format:?}"),
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::helper::{Parser, Seeker};
+use crate::{ciso, dol, gcm, rarc, rel, yay0, yaz0, Result};
+
+/// File format detected by [`detect_format`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Dolphin executable, see [`crate::dol`].
+    Dol,
+    /// Relocatable module, see [`crate::rel`].
+    Rel,
+    /// GameCube master disc, see [`crate::gcm`].
+    Gcm,
+    /// Compact ISO, see [`crate::ciso`].
+    Ciso,
+    /// RARC archive, see [`crate::rarc`].
+    Rarc,
+    /// Yaz0 compressed data, see [`crate::yaz0`].
+    Yaz0,
+    /// Yay0 compressed data, see [`crate::yay0`].
+    Yay0,
+    /// None of the above.
+    Unknown,
+}
+
+/// Detect the [`Format`] of the data at the current position of `input`. The
+/// stream position is restored before returning.
+pub fn detect_format<D: Parser + Seeker>(input: &mut D) -> Result<Format> {
+    let base = input.position()?;
+
+    let format = if yaz0::is_yaz0(input) {
+        Format::Yaz0
+    } else if yay0::is_yay0(input) {
+        Format::Yay0
+    } else if rarc::is_rarc(input) {
+        Format::Rarc
+    } else if ciso::is_ciso(input) {
+        Format::Ciso
+    } else if gcm::is_gcm(input) {
+        Format::Gcm
+    } else if dol::is_dol(input) {
+        Format::Dol
+    } else if rel::is_rel(input) {
+        Format::Rel
+    } else {
+        Format::Unknown
+    };
+
+    input.goto(base)?;
+    Ok(format)
+}
@@ -2,11 +2,14 @@
 //! apploader code, The apploader is a small program that loads the main
 //! executable and the [FST][`crate::gcm::fst`].
 
-use crate::helper::{Parser, Writer};
+use std::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{Parser, ProblemLocation, Writer};
 use crate::{Ascii, Result};
 
 /// [GCM][`crate::gcm`] Apploader (`apploader.img`) object.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Apploader {
     /// Date.
     pub date: String,
@@ -35,8 +38,10 @@ impl Apploader {
         let size = input.bu32()?;
         let trailer_size = input.bu32()?;
         let unknown = input.bu32()?;
-        let data_size = (size + trailer_size) as usize;
-        let data = input.read_as_vec(data_size)?;
+        let data_size = size.checked_add(trailer_size).ok_or_else(|| {
+            ParseProblem::InvalidRange("size + trailer size overflow", Location::current())
+        })?;
+        let data = input.read_as_vec_max(data_size as usize, 0x2000000)?;
 
         Ok(Self {
             date,
@@ -48,6 +53,20 @@ impl Apploader {
         })
     }
 
+    /// The apploader code, the first [`Apploader::size`] bytes of the data.
+    pub fn code(&self) -> &[u8] {
+        let end = (self.size as usize).min(self.data.len());
+        &self.data[..end]
+    }
+
+    /// The apploader trailer, the [`Apploader::trailer_size`] bytes following
+    /// the code.
+    pub fn trailer(&self) -> &[u8] {
+        let start = (self.size as usize).min(self.data.len());
+        let end = (start + self.trailer_size as usize).min(self.data.len());
+        &self.data[start..end]
+    }
+
     pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> { 
         output.str::<0x10, Ascii>(&self.date)?;
         output.bu32(self.entry_point)?;
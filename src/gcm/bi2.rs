@@ -8,11 +8,16 @@
 //! file is not well understood. Only the first 0x28 bytes are known to be
 //! used.
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::panic::Location;
 
-use crate::helper::{Parser, Writer};
+use crate::error::BuildProblem;
+use crate::helper::{ensure, Parser, ProblemLocation, Writer};
 use crate::Result;
 
+/// Number of options, i.e., the number of u32 values in the 0x2000 bytes.
+const OPTION_COUNT: usize = 0x2000 / 4;
+
 /// [`Bi2`] Options.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, PartialOrd, Ord)]
 pub enum Bi2Options {
@@ -68,6 +73,10 @@ impl Bi2Options {
             Unknown(index) => *index,
         }
     }
+
+    /// The option with the same index as `self`, with [`Bi2Options::Unknown`]
+    /// replaced by the named option if there is one.
+    fn normalize(self) -> Self { Self::from(self.index()) }
 }
 
 impl From<usize> for Bi2Options {
@@ -89,44 +98,119 @@ impl From<usize> for Bi2Options {
     }
 }
 
+/// Initial `PAD_SPEC_X` that the Pad library uses, the value of
+/// [`Bi2Options::PadSpec`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum PadSpec {
+    /// `PAD_SPEC_0`.
+    Spec0,
+    /// `PAD_SPEC_1`.
+    Spec1,
+    /// `PAD_SPEC_2`.
+    Spec2,
+    /// `PAD_SPEC_3`.
+    Spec3,
+    /// `PAD_SPEC_4`.
+    Spec4,
+    /// `PAD_SPEC_5`, used by retail games.
+    Spec5,
+    /// Unknown value.
+    Unknown(u32),
+}
+
+impl From<u32> for PadSpec {
+    fn from(value: u32) -> Self {
+        use PadSpec::*;
+        match value {
+            0 => Spec0,
+            1 => Spec1,
+            2 => Spec2,
+            3 => Spec3,
+            4 => Spec4,
+            5 => Spec5,
+            _ => Unknown(value),
+        }
+    }
+}
+
+impl From<PadSpec> for u32 {
+    fn from(pad_spec: PadSpec) -> Self {
+        use PadSpec::*;
+        match pad_spec {
+            Spec0 => 0,
+            Spec1 => 1,
+            Spec2 => 2,
+            Spec3 => 3,
+            Spec4 => 4,
+            Spec5 => 5,
+            Unknown(value) => value,
+        }
+    }
+}
+
 /// [GCM][`crate::gcm`] Boot information (`bi2.bin`) object.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Bi2 {
-    // TODO: HashMap or Array?
-    options: HashMap<Bi2Options, u32>,
+    options: BTreeMap<Bi2Options, u32>,
 }
 
 impl Bi2 {
-    /// Get options value.
-    pub fn get(&self, options: Bi2Options) -> Option<&u32> { self.options.get(&options) }
+    /// Get options value. [`Bi2Options::Unknown`] with the index of a named
+    /// option refers to the same value as the named option.
+    pub fn get(&self, options: Bi2Options) -> Option<&u32> {
+        self.options.get(&options.normalize())
+    }
 
     /// Set options value.
-    pub fn set(&mut self, options: Bi2Options, value: u32) { self.options.insert(options, value); }
+    pub fn set(&mut self, options: Bi2Options, value: u32) {
+        self.options.insert(options.normalize(), value);
+    }
 
     /// Clear options value.
-    pub fn clear(&mut self, options: Bi2Options) { self.options.remove(&options); }
+    pub fn clear(&mut self, options: Bi2Options) { self.options.remove(&options.normalize()); }
+
+    /// Get all options, ordered by option.
+    pub fn options(&self) -> &BTreeMap<Bi2Options, u32> { &self.options }
+
+    /// Get [`Bi2Options::PadSpec`] decoded as [`PadSpec`].
+    ///
+    /// Options with the value zero are not kept by [`Bi2::from_binary`], so
+    /// `PAD_SPEC_0` on disc is returned as [`None`].
+    pub fn pad_spec(&self) -> Option<PadSpec> {
+        self.get(Bi2Options::PadSpec).map(|x| PadSpec::from(*x))
+    }
 
-    /// Get all options.
-    pub fn options(&self) -> &HashMap<Bi2Options, u32> { &self.options }
+    /// Get [`Bi2Options::DebugFlag`] as a [`bool`], any non-zero value is
+    /// `true`.
+    pub fn debug_flag(&self) -> Option<bool> { self.get(Bi2Options::DebugFlag).map(|x| *x != 0) }
 }
 
 impl Bi2 {
     /// Parse GCM BI2.
     pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
         let options = input
-            .bu32_array::<{ 0x2000 / 4 }>()?
+            .bu32_array::<OPTION_COUNT>()?
             .iter()
             .enumerate()
             .map(|(i, data)| (Bi2Options::from(i), *data))
             .filter(|x| x.1 != 0)
-            .collect::<HashMap<_, _>>();
+            .collect::<BTreeMap<_, _>>();
 
         Ok(Self { options })
     }
 
-    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> { 
-        let mut data = [0u32; 0x2000 / 4];
+    /// Write GCM BI2, always 0x2000 bytes. Each option value is written at
+    /// [`Bi2Options::index`] and all other values are zero.
+    ///
+    /// Returns [`BuildProblem::InvalidRange`] if an option index is `0x800`
+    /// or larger.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        let mut data = [0u32; OPTION_COUNT];
         for (options, value) in self.options.iter() {
+            ensure!(
+                options.index() < OPTION_COUNT,
+                BuildProblem::InvalidRange("option index out of bounds", Location::current())
+            );
             data[options.index()] = *value;
         }
         output.bu32_array(&data)?;
@@ -1,7 +1,9 @@
 //! [GCM][`crate::gcm`] Boot Header (`boot.bin`). This is the first 0x440 bytes
 //! of the GCM image.
 
-use crate::error::ParseProblem;
+use std::panic::Location;
+
+use crate::error::{BuildProblem, ParseProblem};
 use crate::helper::{ensure, Parser, ProblemLocation, Writer};
 use crate::{Ascii, Result};
 
@@ -39,9 +41,17 @@ pub struct Boot {
     /// Stream buffer size.
     pub streaming_buffer_size: u8,
 
-    /// Game name.
+    /// Game name. Bytes that aren't ASCII are decoded as `U+FFFD`, see
+    /// [`Boot::raw_game_name`] for the original bytes.
     pub game_name: String,
 
+    /// Game name as stored in the header, including any bytes that aren't
+    /// ASCII and the padding after the NUL terminator. Written by
+    /// [`Boot::to_binary`] instead of [`Boot::game_name`] as long as it still
+    /// decodes to the same name, otherwise it's ignored, e.g., all zeros is
+    /// fine when constructing a [`Boot`] by hand.
+    pub raw_game_name: [u8; 0x3E0],
+
     /// Debug monitor offset (unknown purpose).
     pub debug_monitor_offset: u32,
 
@@ -71,7 +81,132 @@ pub struct Boot {
     pub unknown0: u32,
 }
 
+/// Builder for [`Boot`], created with [`Boot::builder`]. Fields without a
+/// setter are zero, the console is [`ConsoleType::GameCube`], and the magic
+/// and reserved regions are filled in by [`Boot::to_binary`].
+#[derive(Debug, Clone, Default)]
+pub struct BootBuilder {
+    game_code:              String,
+    country_code:           u8,
+    maker_code:             String,
+    disc_id:                u8,
+    version:                u8,
+    game_name:              String,
+    main_executable_offset: u32,
+    fst_offset:             u32,
+    fst_size:               u32,
+    fst_max_size:           u32,
+}
+
+impl BootBuilder {
+    /// Set the two character game code, e.g., `"ZL"`.
+    pub fn game_code(&mut self, game_code: &str) -> &mut Self {
+        self.game_code = game_code.to_string();
+        self
+    }
+
+    /// Set the region code, e.g., `b'E'`.
+    pub fn country_code(&mut self, country_code: u8) -> &mut Self {
+        self.country_code = country_code;
+        self
+    }
+
+    /// Set the two character maker code, e.g., `"01"`.
+    pub fn maker_code(&mut self, maker_code: &str) -> &mut Self {
+        self.maker_code = maker_code.to_string();
+        self
+    }
+
+    /// Set the disc number.
+    pub fn disc_id(&mut self, disc_id: u8) -> &mut Self {
+        self.disc_id = disc_id;
+        self
+    }
+
+    /// Set the version.
+    pub fn version(&mut self, version: u8) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// Set the game name.
+    pub fn game_name(&mut self, game_name: &str) -> &mut Self {
+        self.game_name = game_name.to_string();
+        self
+    }
+
+    /// Set the main executable offset.
+    pub fn main_executable_offset(&mut self, offset: u32) -> &mut Self {
+        self.main_executable_offset = offset;
+        self
+    }
+
+    /// Set the FST offset and size. The FST max size is set to `size` as
+    /// well, use [`BootBuilder::fst_max_size`] for multi-disc games.
+    pub fn fst(&mut self, offset: u32, size: u32) -> &mut Self {
+        self.fst_offset = offset;
+        self.fst_size = size;
+        self.fst_max_size = self.fst_max_size.max(size);
+        self
+    }
+
+    /// Set the FST max size.
+    pub fn fst_max_size(&mut self, size: u32) -> &mut Self {
+        self.fst_max_size = size;
+        self
+    }
+
+    /// Build the [`Boot`].
+    ///
+    /// Returns [`BuildProblem::InvalidData`] if the game code or maker code
+    /// aren't two printable ASCII characters, or if the game name isn't ASCII
+    /// or doesn't fit in 0x3E0 bytes (including the NUL terminator).
+    pub fn build(&self) -> Result<Boot> {
+        let is_code = |x: &str| x.len() == 2 && x.bytes().all(|x| x.is_ascii_graphic());
+        ensure!(
+            is_code(&self.game_code),
+            BuildProblem::InvalidData("invalid game code", Location::current())
+        );
+        ensure!(
+            is_code(&self.maker_code),
+            BuildProblem::InvalidData("invalid maker code", Location::current())
+        );
+        ensure!(
+            self.game_name.is_ascii() && self.game_name.len() < 0x3E0,
+            BuildProblem::InvalidData("invalid game name", Location::current())
+        );
+
+        let code = |x: &str| [x.as_bytes()[0], x.as_bytes()[1]];
+        let mut raw_game_name = [0u8; 0x3E0];
+        raw_game_name[..self.game_name.len()].copy_from_slice(self.game_name.as_bytes());
+        Ok(Boot {
+            console:                ConsoleType::GameCube,
+            game_code:              code(&self.game_code),
+            country_code:           self.country_code,
+            maker_code:             code(&self.maker_code),
+            disc_id:                self.disc_id,
+            version:                self.version,
+            audio_streaming:        0,
+            streaming_buffer_size:  0,
+            game_name:              self.game_name.clone(),
+            raw_game_name,
+            debug_monitor_offset:   0,
+            debug_monitor_address:  0,
+            main_executable_offset: self.main_executable_offset,
+            fst_offset:             self.fst_offset,
+            fst_size:               self.fst_size,
+            fst_max_size:           self.fst_max_size,
+            user_position:          0,
+            user_length:            0,
+            unknown0:               0,
+        })
+    }
+}
+
 impl Boot {
+    /// Create a [`BootBuilder`] to construct a [`Boot`] from scratch.
+    pub fn builder() -> BootBuilder { BootBuilder::default() }
+
     /// Parse GCM Boot.
     pub fn from_binary<D: Parser>(input: &mut D) -> Result<Self> {
         let console = input.u8()?;
@@ -84,7 +219,9 @@ impl Boot {
         let streaming_buffer_size = input.u8()?;
         let _reserved0 = input.u8_array::<0x12>()?;
         let magic = input.bu32()?;
-        let game_name = input.str_fixed::<0x3E0, Ascii>()?;
+        // lossy, some discs have stray non-ASCII bytes in the game name
+        let raw_game_name = input.u8_array::<0x3E0>()?;
+        let game_name = Ascii::first_lossy(raw_game_name);
         let debug_monitor_offset = input.bu32()?;
         let debug_monitor_address = input.bu32()?;
         let _reserved1 = input.u8_array::<0x18>()?;
@@ -99,14 +236,14 @@ impl Boot {
 
         ensure!(
             magic == 0xC2339F3D,
-            ParseProblem::InvalidHeader("invalid magic", std::panic::Location::current())
+            ParseProblem::InvalidHeader("invalid magic", Location::current())
         );
 
         let console = match console {
             0x47 => ConsoleType::GameCube,
             _ => Err(ParseProblem::InvalidHeader(
                 "invalid console type",
-                std::panic::Location::current(),
+                Location::current(),
             ))?,
         };
 
@@ -120,6 +257,7 @@ impl Boot {
             audio_streaming,
             streaming_buffer_size,
             game_name,
+            raw_game_name,
             debug_monitor_offset,
             debug_monitor_address,
             main_executable_offset,
@@ -145,12 +283,16 @@ impl Boot {
         output.u8(self.version)?;
         output.u8(self.audio_streaming)?;
         output.u8(self.streaming_buffer_size)?;
-        output.u8_array(&[0; 0x12])?;
+        output.zeros(0x12)?;
         output.bu32(0xC2339F3D)?;
-        output.str::<0x3E0, Ascii>(&self.game_name)?;
+        if Ascii::first_lossy(self.raw_game_name) == self.game_name {
+            output.u8_array(&self.raw_game_name)?;
+        } else {
+            output.str::<0x3E0, Ascii>(&self.game_name)?;
+        }
         output.bu32(self.debug_monitor_offset)?;
         output.bu32(self.debug_monitor_address)?;
-        output.u8_array(&[0; 0x18])?;
+        output.zeros(0x18)?;
         output.bu32(self.main_executable_offset)?;
         output.bu32(self.fst_offset)?;
         output.bu32(self.fst_size)?;
@@ -158,7 +300,7 @@ impl Boot {
         output.bu32(self.user_position)?;
         output.bu32(self.user_length)?;
         output.bu32(self.unknown0)?;
-        output.u8_array(&[0; 0x4])?;
+        output.zeros(0x4)?;
 
         Ok(())
     }
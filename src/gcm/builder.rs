@@ -0,0 +1,232 @@
+//! Build GameCube master disc ([GCM][`crate::gcm`]) images.
+
+use std::collections::BTreeMap;
+use std::panic::Location;
+
+use super::{Apploader, Bi2, Boot, MAX_IMAGE_SIZE};
+use crate::error::BuildProblem;
+use crate::helper::alignment::AlignPowerOfTwo;
+use crate::helper::{ensure, ProblemLocation, StringTable, Writer};
+use crate::{Ascii, Result};
+
+/// Offset of the apploader in the image.
+const APPLOADER_OFFSET: u32 = 0x2440;
+
+/// Alignment of the main executable and the [FST][`crate::gcm::fst`].
+const SECTION_ALIGNMENT: u32 = 0x100;
+
+/// Alignment of the file data.
+const FILE_ALIGNMENT: u32 = 0x20;
+
+/// Directory tree, children are sorted case-insensitively by name like on
+/// retail discs.
+#[derive(Default)]
+struct Directory {
+    children: BTreeMap<(String, String), Node>,
+}
+
+enum Node {
+    File(usize),
+    Directory(Directory),
+}
+
+enum Entry<'a> {
+    File {
+        name: &'a str,
+        file: usize,
+    },
+    Directory {
+        name:   &'a str,
+        parent: usize,
+        end:    usize,
+    },
+}
+
+impl Directory {
+    #[track_caller]
+    fn insert(&mut self, path: &str, file: usize) -> Result<()> {
+        let (name, rest) = match path.split_once('/') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+        ensure!(
+            !name.is_empty(),
+            BuildProblem::InvalidData("empty path component", Location::caller())
+        );
+
+        let key = (name.to_ascii_lowercase(), name.to_string());
+        match rest {
+            Some(rest) => {
+                let node = self
+                    .children
+                    .entry(key)
+                    .or_insert_with(|| Node::Directory(Directory::default()));
+                match node {
+                    Node::Directory(directory) => directory.insert(rest, file),
+                    Node::File(_) => Err(BuildProblem::InvalidData(
+                        "path is both a file and a directory",
+                        Location::caller(),
+                    )
+                    .into()),
+                }
+            },
+            None => {
+                ensure!(
+                    !self.children.contains_key(&key),
+                    BuildProblem::InvalidData("duplicate path", Location::caller())
+                );
+                self.children.insert(key, Node::File(file));
+                Ok(())
+            },
+        }
+    }
+
+    fn flatten<'a>(&'a self, parent: usize, entries: &mut Vec<Entry<'a>>) {
+        for ((_, name), node) in self.children.iter() {
+            match node {
+                Node::File(file) => entries.push(Entry::File { name, file: *file }),
+                Node::Directory(directory) => {
+                    let index = entries.len() + 1;
+                    entries.push(Entry::Directory {
+                        name,
+                        parent,
+                        end: 0,
+                    });
+                    directory.flatten(index, entries);
+                    let end = entries.len() + 1;
+                    if let Some(Entry::Directory { end: x, .. }) = entries.get_mut(index - 1) {
+                        *x = end;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Builder for `.gcm` disc images.
+///
+/// The image is laid out as follows: [`Boot`] at `0x0`, [`Bi2`] at `0x440`,
+/// [`Apploader`] at `0x2440`, followed by the main executable and the
+/// [FST][`crate::gcm::fst`] (both aligned to `0x100` bytes), and then the
+/// file data (each file aligned to `0x20` bytes). The executable offset and
+/// the FST offset and size in [`Boot`] are recomputed to match the layout.
+pub struct GcmBuilder {
+    boot:       Boot,
+    bi2:        Bi2,
+    apploader:  Apploader,
+    executable: Vec<u8>,
+    files:      Vec<(String, Vec<u8>)>,
+}
+
+impl GcmBuilder {
+    /// Create a new [`GcmBuilder`] without any files. `executable` is the
+    /// main executable ([DOL][`crate::dol`]) data.
+    pub fn new(boot: Boot, bi2: Bi2, apploader: Apploader, executable: Vec<u8>) -> Self {
+        Self {
+            boot,
+            bi2,
+            apploader,
+            executable,
+            files: Vec::new(),
+        }
+    }
+
+    /// Add a file at the slash-separated `path`, e.g. `"audio/bgm.arc"`.
+    /// Directories are created implicitly.
+    pub fn add_file(&mut self, path: impl Into<String>, data: Vec<u8>) -> &mut Self {
+        self.files.push((path.into(), data));
+        self
+    }
+
+    /// Write the disc image to `output`.
+    pub fn build<W: Writer>(&self, output: &mut W) -> Result<()> {
+        let mut root = Directory::default();
+        for (index, (path, _)) in self.files.iter().enumerate() {
+            root.insert(path.trim_start_matches('/'), index)?;
+        }
+
+        let mut entries = Vec::new();
+        root.flatten(0, &mut entries);
+
+        let mut strings = StringTable::default();
+        let mut names = Vec::with_capacity(entries.len());
+        for entry in entries.iter() {
+            let name = match entry {
+                Entry::File { name, .. } | Entry::Directory { name, .. } => name,
+            };
+            names.push(strings.push::<Ascii>(name)?);
+        }
+
+        let apploader_size = 0x20 + self.apploader.data.len() as u64;
+        let executable_offset =
+            (APPLOADER_OFFSET as u64 + apploader_size).align_next(SECTION_ALIGNMENT);
+        let fst_offset =
+            (executable_offset + self.executable.len() as u64).align_next(SECTION_ALIGNMENT);
+        let fst_size = (entries.len() as u64 + 1) * 12 + strings.len() as u64;
+
+        let mut offset = (fst_offset + fst_size).align_next(FILE_ALIGNMENT);
+        let mut file_offsets = vec![0; self.files.len()];
+        for entry in entries.iter() {
+            if let Entry::File { file, .. } = entry {
+                file_offsets[*file] = offset;
+                offset = (offset + self.files[*file].1.len() as u64).align_next(FILE_ALIGNMENT);
+            }
+        }
+
+        ensure!(
+            offset <= MAX_IMAGE_SIZE,
+            BuildProblem::InvalidRange("image too large", Location::current())
+        );
+
+        let mut boot = self.boot.clone();
+        boot.main_executable_offset = executable_offset as u32;
+        boot.fst_offset = fst_offset as u32;
+        boot.fst_size = fst_size as u32;
+        boot.fst_max_size = fst_size as u32;
+
+        let mut fst = Vec::with_capacity(fst_size as usize);
+        fst.bu32(0x0100_0000)?;
+        fst.bu32(0)?;
+        fst.bu32(entries.len() as u32 + 1)?;
+        for (entry, name) in entries.iter().zip(names.iter()) {
+            match entry {
+                Entry::File { file, .. } => {
+                    fst.bu32(*name)?;
+                    fst.bu32(file_offsets[*file] as u32)?;
+                    fst.bu32(self.files[*file].1.len() as u32)?;
+                },
+                Entry::Directory { parent, end, .. } => {
+                    fst.bu32(0x0100_0000 | *name)?;
+                    fst.bu32(*parent as u32)?;
+                    fst.bu32(*end as u32)?;
+                },
+            }
+        }
+        fst.u8_array(strings.as_bytes())?;
+
+        let mut position = 0_u64;
+        let mut write = |output: &mut W, offset: u64, data: &[u8]| -> Result<()> {
+            output.zeros((offset - position) as usize)?;
+            output.u8_array(data)?;
+            position = offset + data.len() as u64;
+            Ok(())
+        };
+
+        let mut header = Vec::with_capacity(APPLOADER_OFFSET as usize + apploader_size as usize);
+        boot.to_binary(&mut header)?;
+        self.bi2.to_binary(&mut header)?;
+        self.apploader.to_binary(&mut header)?;
+
+        write(output, 0, &header)?;
+        write(output, executable_offset, &self.executable)?;
+        write(output, fst_offset, &fst)?;
+        for entry in entries.iter() {
+            if let Entry::File { file, .. } = entry {
+                write(output, file_offsets[*file], &self.files[*file].1)?;
+            }
+        }
+        write(output, offset, &[])?;
+
+        Ok(())
+    }
+}
@@ -1,14 +1,19 @@
 //! [GCM][`crate::gcm`] executable file. This is the [DOL][`crate::dol`] file
 //! that contains the actual game code.
 
+use std::ops::Range;
+
+use super::MAX_DOL_SIZE;
 use crate::error::ParseProblem;
 use crate::helper::{Parser, ProblemLocation, Seeker, Writer};
 use crate::Result;
 
 /// [GCM][`crate::gcm`] executable file object.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Executable {
-    data: Vec<u8>,
+    offset: u64,
+    size:   u32,
+    data:   Vec<u8>,
 }
 
 impl Executable {
@@ -25,30 +30,57 @@ impl Executable {
         let text_iter = text_offsets
             .iter()
             .zip(text_sizes.iter())
-            .map(|(offset, size)| offset + size);
+            .map(|(offset, size)| offset.checked_add(*size));
 
         let data_iter = data_offsets
             .iter()
             .zip(data_sizes.iter())
-            .map(|(offset, size)| offset + size);
+            .map(|(offset, size)| offset.checked_add(*size));
 
-        let total_size = text_iter.chain(data_iter).max().ok_or_else(|| {
-            ParseProblem::InvalidHeader(
-                "unable to determine executable size",
-                std::panic::Location::current(),
-            )
-        })?;
+        let total_size = text_iter
+            .chain(data_iter)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                ParseProblem::InvalidRange(
+                    "executable section out of range",
+                    std::panic::Location::current(),
+                )
+            })?
+            .into_iter()
+            .max()
+            .ok_or_else(|| {
+                ParseProblem::InvalidHeader(
+                    "unable to determine executable size",
+                    std::panic::Location::current(),
+                )
+            })?;
 
         input.goto(base)?;
-        let data = input.read_as_vec(total_size as usize)?;
-        Ok(Self { data })
+        let data = input.read_as_vec_max(total_size as usize, MAX_DOL_SIZE)?;
+        Ok(Self {
+            offset: base,
+            size: total_size,
+            data,
+        })
     }
 
-    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> { 
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
         output.u8_array(&self.data)?;
         Ok(())
     }
 
     /// Get the executable data.
     pub fn data(&self) -> &[u8] { &self.data }
+
+    /// Size of the executable in bytes, i.e., the end of the last text or
+    /// data section as given by the [DOL][`crate::dol`] header.
+    pub fn size(&self) -> u32 { self.size }
+
+    /// Offset of the executable in the stream it was parsed from, i.e.,
+    /// [`Boot::main_executable_offset`][`crate::gcm::Boot::main_executable_offset`]
+    /// for a disc image.
+    pub fn offset(&self) -> u64 { self.offset }
+
+    /// Range of the executable in the stream it was parsed from.
+    pub fn range(&self) -> Range<u64> { self.offset..self.offset + self.size as u64 }
 }
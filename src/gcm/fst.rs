@@ -2,10 +2,11 @@
 //! information about the file structure of the GameCube disc, i.e. the file
 //! names and their locations.
 
+use std::panic::Location;
 use std::path::PathBuf;
 
-use crate::error::ParseProblem;
-use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, StringTable, Writer};
 use crate::{Ascii, Result};
 
 /// Enum varient of a single [`Fst`] entry.
@@ -23,6 +24,10 @@ pub enum Entry {
         offset: u32,
         /// File size.
         size:   u32,
+        /// The file data is on another disc of a multi-disc game, i.e.,
+        /// `offset` and `size` don't refer to this image. Only set by
+        /// [`Fst::from_binary_shared`].
+        on_other_disc: bool,
     },
     /// Directory.
     Directory {
@@ -52,11 +57,10 @@ enum RawEntry {
 
 impl RawEntry {
     pub fn new<D: Parser + Seeker>(input: &mut D) -> Result<Self> {
-        let flag_or_name_offset = input.bu32()?;
+        let flag = input.u8()?;
+        let name_offset = input.bu24()?;
         let data_offset_or_parent = input.bu32()?;
         let data_length_or_end = input.bu32()?;
-        let flag = flag_or_name_offset >> 24;
-        let name_offset = flag_or_name_offset & 0x00ffffff;
 
         if (flag & 1) == 0 {
             Ok(Self::File {
@@ -74,9 +78,19 @@ impl RawEntry {
     }
 }
 
+/// Default maximum number of entries accepted by [`Fst::from_binary`] and
+/// [`Fst::from_binary_shared`], including the root. The entry count is read
+/// from the root entry before anything else is validated, so the limit
+/// prevents a corrupt FST from causing a huge allocation. Commercial discs
+/// stay well below `0x10000` entries (about 768 KiB of entries).
+pub const DEFAULT_MAX_ENTRIES: usize = 0x10000;
+
 /// [GCM][`crate::gcm`] File String Table (`fst.bin`) object.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Fst {
-    entries: Vec<Entry>,
+    entries:  Vec<Entry>,
+    size:     u32,
+    max_size: u32,
 }
 
 impl Fst {
@@ -85,28 +99,88 @@ impl Fst {
     /// To read the full string table, this function needs the size of the
     /// [`Fst`]. This is available in the [`crate::gcm::Boot`] struct.
     pub fn from_binary<D: Parser + Seeker>(reader: &mut D, fst_size: usize) -> Result<Fst> {
+        Self::parse(reader, fst_size, None, DEFAULT_MAX_ENTRIES, 0)
+    }
+
+    /// Parse GCM FST as [`Fst::from_binary`], but with a custom limit for the
+    /// number of entries instead of [`DEFAULT_MAX_ENTRIES`]. If the FST has
+    /// more entries, [`ParseProblem::LimitExceeded`] is returned.
+    pub fn from_binary_with_limit<D: Parser + Seeker>(
+        reader: &mut D,
+        fst_size: usize,
+        max_entries: usize,
+    ) -> Result<Fst> {
+        Self::parse(reader, fst_size, None, max_entries, 0)
+    }
+
+    /// Parse GCM FST of a multi-disc game, where `fst_max_size` is the
+    /// combined size of the FST of all discs (see
+    /// [`crate::gcm::Boot::fst_max_size`]). File entries with data past the
+    /// end of `reader` are marked as [`Entry::File::on_other_disc`] instead of
+    /// referring to data that doesn't exist in this image. If `fst_max_size`
+    /// is the same as `fst_size`, the game has a single disc and such entries
+    /// fail with [`ParseProblem::InvalidRange`] instead.
+    pub fn from_binary_shared<D: Parser + Seeker>(
+        reader: &mut D,
+        fst_size: usize,
+        fst_max_size: usize,
+    ) -> Result<Fst> {
+        Self::parse(reader, fst_size, Some(fst_max_size), DEFAULT_MAX_ENTRIES, 0)
+    }
+
+    /// Parse GCM FST as [`Fst::from_binary_shared`] for an image starting at
+    /// the stream position `image_base` instead of the start of `reader`.
+    pub(crate) fn from_binary_in_image<D: Parser + Seeker>(
+        reader: &mut D,
+        fst_size: usize,
+        fst_max_size: usize,
+        image_base: u64,
+    ) -> Result<Fst> {
+        Self::parse(reader, fst_size, Some(fst_max_size), DEFAULT_MAX_ENTRIES, image_base)
+    }
+
+    fn parse<D: Parser + Seeker>(
+        reader: &mut D,
+        fst_size: usize,
+        fst_max_size: Option<usize>,
+        max_entries: usize,
+        image_base: u64,
+    ) -> Result<Fst> {
         let base = reader.position()?;
+        let image_end = match fst_max_size {
+            Some(_) => reader.seek(std::io::SeekFrom::End(0))?.saturating_sub(image_base),
+            None => u64::MAX,
+        };
+        let multidisc = fst_max_size.is_some_and(|x| x != fst_size);
+        reader.goto(base)?;
 
         let _ = reader.bu32()?;
         let _ = reader.bu32()?;
         let root_count = reader.bu32()?;
         let entry_count = root_count as usize;
         ensure!(
-            entry_count <= 0x4000,
-            ParseProblem::InvalidRange(
-                "entry count limit (max 16384)",
+            entry_count <= max_entries,
+            ParseProblem::LimitExceeded(
+                "fst entry count",
+                entry_count,
+                max_entries,
                 std::panic::Location::current()
             )
         );
 
+        let entry_size = 0x0C * entry_count;
+        ensure!(
+            entry_size <= fst_size,
+            ParseProblem::InvalidRange("entries exceed fst size", std::panic::Location::current())
+        );
+
         reader.goto(base)?;
         let temp_entries = (0..entry_count)
             .map(|_| RawEntry::new(reader))
             .collect::<Result<Vec<_>>>()?;
 
-        let entry_size = 0x0C * entry_count;
         let name_table_size = fst_size - entry_size;
-        let string_table = reader.read_as_vec(name_table_size)?;
+        let string_table = StringTable::new(reader.read_as_vec(name_table_size)?);
 
         let mut entries = Vec::with_capacity(entry_count);
         for (i, entry) in temp_entries.iter().enumerate() {
@@ -116,28 +190,150 @@ impl Fst {
             }
 
             let entry = match entry {
-                RawEntry::File { name, offset, size } => Entry::File {
-                    name:   Ascii::first(&string_table[*name as usize..])?,
-                    index:  i as u32,
-                    offset: *offset,
-                    size:   *size,
+                RawEntry::File { name, offset, size } => {
+                    let outside = *offset as u64 + *size as u64 > image_end;
+                    ensure!(
+                        !outside || multidisc,
+                        ParseProblem::InvalidRange(
+                            "file data outside of image",
+                            std::panic::Location::current()
+                        )
+                    );
+                    Entry::File {
+                        name:          string_table.get::<Ascii>(*name)?,
+                        index:         i as u32,
+                        offset:        *offset,
+                        size:          *size,
+                        on_other_disc: outside,
+                    }
                 },
-                RawEntry::Directory { name, parent, end } => Entry::Directory {
-                    name:   Ascii::first(&string_table[*name as usize..])?,
-                    parent: *parent,
-                    begin:  (i + 1) as u32,
-                    end:    *end,
+                RawEntry::Directory { name, parent, end } => {
+                    ensure!(
+                        i < *end as usize && *end as usize <= entry_count,
+                        ParseProblem::InvalidData(
+                            "directory end out of bounds",
+                            std::panic::Location::current()
+                        )
+                    );
+                    ensure!(
+                        (*parent as usize) < i,
+                        ParseProblem::InvalidData(
+                            "directory parent out of bounds",
+                            std::panic::Location::current()
+                        )
+                    );
+                    Entry::Directory {
+                        name:   string_table.get::<Ascii>(*name)?,
+                        parent: *parent,
+                        begin:  (i + 1) as u32,
+                        end:    *end,
+                    }
                 },
             };
 
             entries.push(entry);
         }
 
-        Ok(Fst { entries })
+        Ok(Fst {
+            entries,
+            size: fst_size as u32,
+            max_size: fst_max_size.unwrap_or(fst_size) as u32,
+        })
+    }
+
+    /// Serialize the [`Fst`], the entries followed by the string table.
+    /// Names are deduplicated in the string table. Returns the size of the
+    /// written [`Fst`] and the max size to store in
+    /// [`crate::gcm::Boot::fst_max_size`], which is the same as the size
+    /// unless this is the FST of a multi-disc game.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<(u32, u32)> {
+        let mut strings = StringTable::default();
+        let mut raw = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.iter() {
+            let (flag, name, offset_or_parent, size_or_end) = match entry {
+                Entry::Root => (0x0100_0000, None, 0, self.entries.len() as u32),
+                Entry::File {
+                    name, offset, size, ..
+                } => (0, Some(name), *offset, *size),
+                Entry::Directory {
+                    name, parent, end, ..
+                } => (0x0100_0000, Some(name), *parent, *end),
+            };
+
+            let name = match name {
+                Some(name) => strings.push::<Ascii>(name)?,
+                None => 0,
+            };
+            ensure!(
+                name <= 0x00FF_FFFF,
+                BuildProblem::InvalidRange("string table too large", Location::current())
+            );
+            raw.push((flag | name, offset_or_parent, size_or_end));
+        }
+
+        let size = raw.len() * 0x0C + strings.len();
+        ensure!(
+            size <= u32::MAX as usize,
+            BuildProblem::InvalidRange("fst too large", Location::current())
+        );
+
+        for (name, offset_or_parent, size_or_end) in raw {
+            output.bu32(name)?;
+            output.bu32(offset_or_parent)?;
+            output.bu32(size_or_end)?;
+        }
+        output.u8_array(strings.as_bytes())?;
+
+        let size = size as u32;
+        let max_size = if self.is_multidisc() {
+            self.max_size.max(size)
+        } else {
+            size
+        };
+        Ok((size, max_size))
+    }
+
+    /// Returns `true` if this is the FST of a multi-disc game, i.e., the FST
+    /// size of this disc differs from the combined FST size of all discs.
+    pub fn is_multidisc(&self) -> bool { self.size != self.max_size }
+
+    /// Find the [`Entry`] at the slash-separated `path`, relative to the
+    /// root directory, e.g. `"a/b/c.txt"`. Names are matched
+    /// case-sensitively and empty path components are ignored, i.e., `""`
+    /// and `"/"` return [`Entry::Root`].
+    pub fn find(&self, path: &str) -> Option<&Entry> {
+        let mut entry = self.entries.first()?;
+        for component in path.split('/').filter(|x| !x.is_empty()) {
+            entry = self.children(entry).find(|x| match x {
+                Entry::File { name, .. } | Entry::Directory { name, .. } => name == component,
+                Entry::Root => false,
+            })?;
+        }
+
+        Some(entry)
+    }
+
+    /// Get an iterator over the immediate children of the directory `dir`,
+    /// i.e., the contents of nested directories are skipped. Files have no
+    /// children.
+    pub fn children(&self, dir: &Entry) -> ChildIterator<'_> {
+        let (index, end) = match dir {
+            Entry::Root => (1, self.entries.len()),
+            Entry::Directory { begin, end, .. } => {
+                (*begin as usize, (*end as usize).min(self.entries.len()))
+            },
+            Entry::File { .. } => (0, 0),
+        };
+
+        ChildIterator {
+            fst: self,
+            index,
+            end,
+        }
     }
 
     /// Get an iterator over all [`Entry`]s.
-    pub fn files(&self) -> FileIterator {
+    pub fn files(&self) -> FileIterator<'_> {
         FileIterator {
             fst: self,
             index: 0,
@@ -188,3 +384,27 @@ impl<'fst> Iterator for FileIterator<'fst> {
         }
     }
 }
+
+/// Iterator over the immediate children of a directory in [`Fst`].
+pub struct ChildIterator<'fst> {
+    fst:   &'fst Fst,
+    index: usize,
+    end:   usize,
+}
+
+impl<'fst> Iterator for ChildIterator<'fst> {
+    type Item = &'fst Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+
+        let entry = self.fst.entries.get(self.index)?;
+        self.index = match entry {
+            Entry::Directory { end, .. } => (*end as usize).max(self.index + 1),
+            _ => self.index + 1,
+        };
+        Some(entry)
+    }
+}
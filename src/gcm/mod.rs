@@ -22,10 +22,28 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Read files
+//!
+//! [`Gcm`] doesn't keep the binary stream, use [`GcmReader`] to parse the
+//! disc and read file data on demand.
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let file = File::open("game.iso")?;
+//!     let mut reader = picori::GcmReader::new(file)?;
+//!     let data = reader.open("audio/bgm.arc")?;
+//!     println!("bgm.arc: {} bytes", data.len());
+//!     Ok(())
+//! }
+//! ```
 
 pub mod apploader;
 pub mod bi2;
 pub mod boot;
+pub mod builder;
 pub mod executable;
 pub mod fst;
 
@@ -36,22 +54,44 @@ pub use bi2::*;
 #[doc(inline)]
 pub use boot::*;
 #[doc(inline)]
+pub use builder::GcmBuilder;
+#[doc(inline)]
 pub use executable::*;
 #[doc(inline)]
 pub use fst::Fst;
 
-use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, Seeker};
-use crate::Result;
+use std::io::{Cursor, SeekFrom};
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+
+use crate::helper::{ensure, ParseProblem, Parser, ProblemLocation, ResultExt, Seeker, Writer};
+use crate::{bnr, Bnr, Dol, Result};
+
+/// Maximum size of a GameCube disc.
+pub(crate) const MAX_IMAGE_SIZE: u64 = 0x57058000;
+
+/// Maximum size of a [DOL][`crate::dol`] read from the disc, larger than the
+/// main memory of the console.
+pub(crate) const MAX_DOL_SIZE: usize = 0x2000000;
+
+/// Address the apploader code is loaded to.
+const APPLOADER_ADDRESS: u32 = 0x81200000;
+
+/// Chunk size used by [`GcmReader::write_file`].
+const WRITE_CHUNK_SIZE: usize = 0x10000;
 
 /// `.gcm` file object.
 ///
 /// Because `.gcm` files take up a lot of space, the [`Gcm`]
 /// only contains information about the boot, bi2, apploader, executable, and
 /// file string table. File specific data is not included. To get the data for a
-/// specific file, use [`Gcm::fst`] to find the file entry. Then use
-/// [`fst::Entry::File::offset`] and [`fst::Entry::File::size`] to read the file
-/// data yourself.
+/// specific file, use [`Gcm::open_file`] with the file path, or use
+/// [`Gcm::fst`] to find the file entry. Then use [`fst::Entry::File::offset`]
+/// and [`fst::Entry::File::size`] to read the file data yourself. Offsets are
+/// relative to [`Gcm::base`], the stream position the [`Gcm`] was parsed at.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Gcm {
+    base:       u64,
     boot:       Boot,
     bi2:        Bi2,
     apploader:  Apploader,
@@ -64,31 +104,38 @@ impl Gcm {
     pub fn from_binary<D: Parser + Seeker>(reader: &mut D) -> Result<Gcm> {
         let position = reader.position()?;
 
-        let boot = Boot::from_binary(reader)?;
+        let boot = Boot::from_binary(reader).context("boot")?;
         ensure!(
             position + 0x440 == reader.position()?,
             ParseProblem::InvalidData("invalid boot", std::panic::Location::current())
         );
 
-        let bi2 = Bi2::from_binary(reader)?;
+        let bi2 = Bi2::from_binary(reader).context("bi2")?;
         ensure!(
             position + 0x2440 == reader.position()?,
             ParseProblem::InvalidData("invalid bi2", std::panic::Location::current())
         );
 
-        let apploader = Apploader::from_binary(reader)?;
+        let apploader = Apploader::from_binary(reader).context("apploader")?;
         ensure!(
             position + 0x2460 + (apploader.data.len() as u64) == reader.position()?,
             ParseProblem::InvalidData("invalid apploader", std::panic::Location::current())
         );
 
         reader.goto(position + boot.main_executable_offset as u64)?;
-        let executable = Executable::from_binary(reader)?;
+        let executable = Executable::from_binary(reader).context("executable")?;
 
         reader.goto(position + boot.fst_offset as u64)?;
-        let fst = Fst::from_binary(reader, boot.fst_size as usize)?;
+        let fst = Fst::from_binary_in_image(
+            reader,
+            boot.fst_size as usize,
+            boot.fst_max_size as usize,
+            position,
+        )
+        .context("fst")?;
 
         Ok(Gcm {
+            base: position,
             boot,
             bi2,
             apploader,
@@ -97,6 +144,69 @@ impl Gcm {
         })
     }
 
+    /// Check that the parsed headers are consistent with each other. This is
+    /// stricter than [`Gcm::from_binary`], which only checks what is needed
+    /// to parse the disc. Checks that
+    /// - the console is a GameCube,
+    /// - the main executable and the [`Fst`] are within the disc,
+    /// - the apploader data is [`Apploader::size`] plus
+    ///   [`Apploader::trailer_size`] bytes and its entry point is within the
+    ///   apploader code,
+    /// - the data of every file is within the disc.
+    ///
+    /// Returns the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.boot.console == ConsoleType::GameCube,
+            ParseProblem::InvalidHeader("invalid console type", Location::current())
+        );
+        ensure!(
+            (self.boot.main_executable_offset as u64) < MAX_IMAGE_SIZE,
+            ParseProblem::InvalidRange("main executable outside of disc", Location::current())
+        );
+        ensure!(
+            self.boot.fst_offset as u64 + self.boot.fst_size as u64 <= MAX_IMAGE_SIZE,
+            ParseProblem::InvalidRange("fst outside of disc", Location::current())
+        );
+
+        let apploader = &self.apploader;
+        ensure!(
+            apploader.size as u64 + apploader.trailer_size as u64 == apploader.data.len() as u64,
+            ParseProblem::InvalidData("apploader data size mismatch", Location::current())
+        );
+        ensure!(
+            apploader.entry_point >= APPLOADER_ADDRESS
+                && apploader.entry_point - APPLOADER_ADDRESS < apploader.size,
+            ParseProblem::InvalidData("apploader entry point outside of code", Location::current())
+        );
+
+        for (_, entry) in self.fst.files() {
+            if let fst::Entry::File {
+                offset,
+                size,
+                on_other_disc,
+                ..
+            } = entry
+            {
+                ensure!(
+                    !on_other_disc || self.fst.is_multidisc(),
+                    ParseProblem::InvalidRange("file data outside of image", Location::current())
+                );
+                ensure!(
+                    on_other_disc || offset as u64 + size as u64 <= MAX_IMAGE_SIZE,
+                    ParseProblem::InvalidRange("file data outside of disc", Location::current())
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stream position of the start of the disc, i.e., the position of the
+    /// stream passed to [`Gcm::from_binary`]. Offsets in the headers are
+    /// relative to it.
+    pub fn base(&self) -> u64 { self.base }
+
     /// Get reference to [`Boot`] struct.
     pub fn boot(&self) -> &Boot { &self.boot }
 
@@ -111,4 +221,287 @@ impl Gcm {
 
     /// Get reference to [`Fst`] struct.
     pub fn fst(&self) -> &Fst { &self.fst }
+
+    /// Disc number of this image, starting at 0.
+    pub fn disc_id(&self) -> u8 { self.boot.disc_id }
+
+    /// Number of discs of the game. The header doesn't store the count, so
+    /// this is `1` for single-disc games and at least `2` for multi-disc games
+    /// (see [`Fst::is_multidisc`]), which is the most any GameCube game uses.
+    pub fn disc_count(&self) -> u8 {
+        if self.fst.is_multidisc() || self.boot.disc_id > 0 {
+            self.boot.disc_id.saturating_add(1).max(2)
+        } else {
+            1
+        }
+    }
+
+    /// Parse the [DOL][`crate::dol`] file referenced by `entry`. Useful for
+    /// games that ship more than one executable in the file system. The file
+    /// data is read into memory before parsing, so the [`Dol`] can't read
+    /// past the end of the file.
+    pub fn open_dol<D: Parser + Seeker>(&self, reader: &mut D, entry: &fst::Entry) -> Result<Dol> {
+        let fst::Entry::File { offset, size, .. } = entry else {
+            return Err(ParseProblem::InvalidData(
+                "entry is not a file",
+                std::panic::Location::current(),
+            )
+            .into());
+        };
+
+        reader.goto(self.base + *offset as u64)?;
+        let data = reader.read_as_vec_max(*size as usize, MAX_DOL_SIZE)?;
+        Dol::from_binary(&mut Cursor::new(data))
+    }
+
+    /// Parse the main executable (`main.dol`) as a [`Dol`]. Reads the
+    /// [`Executable::size`] sized region at [`Boot::main_executable_offset`]
+    /// from `reader`. Fails with [`ParseProblem::InvalidRange`] if the size is
+    /// larger than 32 MiB.
+    pub fn executable_as_dol<D: Parser + Seeker>(&self, reader: &mut D) -> Result<Dol> {
+        reader.goto(self.base + self.boot.main_executable_offset as u64)?;
+        let data = reader.read_as_vec_max(self.executable.size() as usize, MAX_DOL_SIZE)?;
+        Dol::from_binary(&mut Cursor::new(data))
+    }
+
+    /// Read and parse the disc banner, `opening.bnr` in the root directory.
+    /// Returns [`None`] if the disc has no banner, and
+    /// [`ParseProblem::InvalidRange`] if the file is larger than a `BNR2`
+    /// banner.
+    pub fn banner<D: Parser + Seeker>(&self, reader: &mut D) -> Result<Option<Bnr>> {
+        let Some(fst::Entry::File { offset, size, .. }) = self.fst.find("opening.bnr") else {
+            return Ok(None);
+        };
+
+        reader.goto(self.base + *offset as u64)?;
+        let data = reader.read_as_vec_max(*size as usize, bnr::BNR2_SIZE)?;
+        Bnr::from_binary(&mut Cursor::new(data)).map(Some)
+    }
+
+    /// Read the user region given by [`Boot::user_position`] and
+    /// [`Boot::user_length`]. Returns [`None`] if the user length is zero,
+    /// and [`ParseProblem::InvalidRange`] if the region isn't within the
+    /// image, i.e., past the end of `reader`.
+    pub fn user_data<D: Parser + Seeker>(&self, reader: &mut D) -> Result<Option<Vec<u8>>> {
+        if self.boot.user_length == 0 {
+            return Ok(None);
+        }
+
+        let start = self.boot.user_position as u64;
+        let image_size = reader.seek(SeekFrom::End(0))?.saturating_sub(self.base);
+        ensure!(
+            start + self.boot.user_length as u64 <= image_size,
+            ParseProblem::InvalidRange("user data outside of image", Location::current())
+        );
+        reader.goto(self.base + start)?;
+        reader.read_as_vec(self.boot.user_length as usize).map(Some)
+    }
+
+    /// Read the data of the file at the slash-separated `path`, e.g.
+    /// `"audio/bgm.arc"`. The path is resolved using [`Fst::find`]. Fails with
+    /// [`ParseProblem::InvalidData`] if the file is stored on another disc,
+    /// see [`fst::Entry::File::on_other_disc`].
+    pub fn open_file<D: Parser + Seeker>(&self, reader: &mut D, path: &str) -> Result<Vec<u8>> {
+        let Some(entry @ fst::Entry::File { .. }) = self.fst.find(path) else {
+            return Err(ParseProblem::InvalidData(
+                "path does not resolve to a file",
+                std::panic::Location::current(),
+            )
+            .into());
+        };
+
+        let (offset, size) = file_location(entry)?;
+        reader.goto(self.base + offset)?;
+        reader.read_as_vec(size)
+    }
+
+    /// Read every file in the [`Fst`] and call `f` with the path of the file,
+    /// relative to the root directory, and its data. Files are visited in
+    /// [`Fst::files`] order, so the parent directories of a file have always
+    /// been visited before the file itself. Files stored on another disc of a
+    /// multi-disc game are skipped. Fails with [`ParseProblem::InvalidRange`]
+    /// if the data of a file isn't within the disc. Stops at the first error
+    /// returned by `f`.
+    pub fn extract_all<D, F>(&self, reader: &mut D, mut f: F) -> Result<()>
+    where
+        D: Parser + Seeker,
+        F: FnMut(&Path, &[u8]) -> Result<()>,
+    {
+        for (path, entry) in self.fst.files() {
+            if let fst::Entry::File {
+                on_other_disc: false,
+                ..
+            } = entry
+            {
+                let (offset, size) = file_location(&entry)?;
+                reader.goto(self.base + offset)?;
+                let data = reader.read_as_vec(size)?;
+                f(&path, &data)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reader for `.gcm` files. Owns the binary stream together with the parsed
+/// [`Gcm`], so that file data can be read on demand without parsing the disc
+/// again. Only the requested file is read, the rest of the disc is never
+/// loaded into memory.
+pub struct GcmReader<D: Parser + Seeker> {
+    reader: D,
+    gcm:    Gcm,
+}
+
+impl<D: Parser + Seeker> GcmReader<D> {
+    /// Create a new [`GcmReader`] by parsing the [`Gcm`] from `reader`.
+    pub fn new(mut reader: D) -> Result<Self> {
+        let gcm = Gcm::from_binary(&mut reader)?;
+        Ok(Self { reader, gcm })
+    }
+
+    /// Get reference to the parsed [`Gcm`].
+    pub fn gcm(&self) -> &Gcm { &self.gcm }
+
+    /// Get reference to [`Boot`] struct.
+    pub fn boot(&self) -> &Boot { self.gcm.boot() }
+
+    /// Get reference to [`Bi2`] struct.
+    pub fn bi2(&self) -> &Bi2 { self.gcm.bi2() }
+
+    /// Get reference to [`Apploader`] struct.
+    pub fn apploader(&self) -> &Apploader { self.gcm.apploader() }
+
+    /// Get reference to [`Executable`] struct.
+    pub fn executable(&self) -> &Executable { self.gcm.executable() }
+
+    /// Get reference to [`Fst`] struct.
+    pub fn fst(&self) -> &Fst { self.gcm.fst() }
+
+    /// Read the data of the file `entry`. Fails with
+    /// [`ParseProblem::InvalidRange`] if the data isn't within the disc.
+    pub fn file_data(&mut self, entry: &fst::Entry) -> Result<Vec<u8>> {
+        let (offset, size) = file_location(entry)?;
+        self.reader.goto(self.gcm.base + offset)?;
+        self.reader.read_as_vec(size)
+    }
+
+    /// Copy the data of the file `entry` to `output`. Unlike
+    /// [`GcmReader::file_data`], the file is never loaded into memory as a
+    /// whole, it's copied in chunks of at most 64 KiB.
+    pub fn write_file<W: Writer>(&mut self, entry: &fst::Entry, output: &mut W) -> Result<()> {
+        let (offset, size) = file_location(entry)?;
+        self.reader.goto(self.gcm.base + offset)?;
+
+        let mut buffer = vec![0u8; size.min(WRITE_CHUNK_SIZE)];
+        let mut remaining = size;
+        while remaining > 0 {
+            let chunk = &mut buffer[..remaining.min(WRITE_CHUNK_SIZE)];
+            self.reader.read_exact_into(chunk)?;
+            output.u8_array(chunk)?;
+            remaining -= chunk.len();
+        }
+        Ok(())
+    }
+
+    /// Read the user region of the disc. See [`Gcm::user_data`].
+    pub fn user_data(&mut self) -> Result<Option<Vec<u8>>> { self.gcm.user_data(&mut self.reader) }
+
+    /// Read the data of the file at the slash-separated `path`. See
+    /// [`Gcm::open_file`].
+    pub fn open(&mut self, path: &str) -> Result<Vec<u8>> {
+        self.gcm.open_file(&mut self.reader, path)
+    }
+
+    /// Get an iterator over all files and their data. The data of each file
+    /// is read when the iterator reaches it. Directories and files stored on
+    /// another disc of a multi-disc game are skipped, and paths are relative
+    /// to the root directory. Files of a single-disc game are never skipped,
+    /// [`GcmReader::new`] already fails if their data isn't within the image.
+    pub fn files(&mut self) -> FileDataIterator<'_, D> {
+        FileDataIterator {
+            reader: &mut self.reader,
+            base:   self.gcm.base,
+            files:  self.gcm.fst.files(),
+        }
+    }
+
+    /// Consume the [`GcmReader`] and return the binary stream.
+    pub fn into_inner(self) -> D { self.reader }
+}
+
+/// Check if the given data is a [GCM][`crate::gcm`] image by looking for the
+/// disc magic `0xC2339F3D` at offset `0x1C`. The stream position is restored.
+pub fn is_gcm<D: Parser + Seeker>(input: &mut D) -> bool {
+    let Ok(base) = input.position() else {
+        return false;
+    };
+
+    let mut check = || -> Result<bool> {
+        input.goto(base + 0x1C)?;
+        Ok(input.bu32()? == 0xC2339F3D)
+    };
+
+    let is_gcm = check().unwrap_or(false);
+    input.goto(base).is_ok() && is_gcm
+}
+
+/// Offset and size of the file `entry`. Fails if `entry` isn't a file, if
+/// the file is stored on another disc or if the data isn't within the disc,
+/// so that a corrupt size can't cause a huge allocation.
+fn file_location(entry: &fst::Entry) -> Result<(u64, usize)> {
+    match entry {
+        fst::Entry::File {
+            on_other_disc: true,
+            ..
+        } => Err(ParseProblem::InvalidData(
+            "file is stored on another disc",
+            std::panic::Location::current(),
+        )
+        .into()),
+        fst::Entry::File { offset, size, .. }
+            if *offset as u64 + *size as u64 > MAX_IMAGE_SIZE =>
+        {
+            Err(ParseProblem::InvalidRange(
+                "file data outside of disc",
+                std::panic::Location::current(),
+            )
+            .into())
+        },
+        fst::Entry::File { offset, size, .. } => Ok((*offset as u64, *size as usize)),
+        _ => Err(ParseProblem::InvalidData(
+            "entry is not a file",
+            std::panic::Location::current(),
+        )
+        .into()),
+    }
+}
+
+/// Iterator over all files of a [`GcmReader`] and their data, see
+/// [`GcmReader::files`].
+pub struct FileDataIterator<'gcm, D: Parser + Seeker> {
+    reader: &'gcm mut D,
+    base:   u64,
+    files:  fst::FileIterator<'gcm>,
+}
+
+impl<D: Parser + Seeker> Iterator for FileDataIterator<'_, D> {
+    type Item = Result<(PathBuf, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, entry) = self.files.next()?;
+            if let fst::Entry::File {
+                on_other_disc: false,
+                ..
+            } = entry
+            {
+                let data = file_location(&entry).and_then(|(offset, size)| {
+                    self.reader.goto(self.base + offset)?;
+                    self.reader.read_as_vec(size)
+                });
+                return Some(data.map(|data| (path, data)));
+            }
+        }
+    }
 }
@@ -2,15 +2,33 @@ pub trait AlignPowerOfTwo {
     fn align_next(self, alignment: u32) -> Self;
 }
 
-impl AlignPowerOfTwo for u32 {
-    fn align_next(self, alignment: u32) -> Self {
-        assert!(
-            alignment.is_power_of_two(),
-            "alignment must be a power of two"
-        );
-        let alignment = alignment - 1;
-        self.wrapping_add(alignment) & !alignment
-    }
+macro_rules! impl_align_power_of_two {
+    ($($ty:ty),*) => {
+        $(
+            impl AlignPowerOfTwo for $ty {
+                fn align_next(self, alignment: u32) -> Self {
+                    assert!(
+                        alignment.is_power_of_two(),
+                        "alignment must be a power of two"
+                    );
+                    let alignment = alignment as $ty - 1;
+                    self.wrapping_add(alignment) & !alignment
+                }
+            }
+        )*
+    };
+}
+
+impl_align_power_of_two!(u32, u64, usize);
+
+/// Number of bytes needed to get from `n` to the next multiple of
+/// `alignment`, i.e., the amount of padding to write.
+pub fn padding(n: u64, alignment: u64) -> u64 {
+    assert!(
+        alignment.is_power_of_two(),
+        "alignment must be a power of two"
+    );
+    n.wrapping_neg() & (alignment - 1)
 }
 
 // -------------------------------------------------------------------------------
@@ -28,6 +46,21 @@ mod tests {
         assert_eq!(32u32.align_next(32), 32);
     }
 
+    #[test]
+    fn align_64() {
+        assert_eq!(0u64.align_next(32), 0);
+        assert_eq!(0x1_0000_0001u64.align_next(32), 0x1_0000_0020);
+        assert_eq!(31usize.align_next(32), 32);
+    }
+
+    #[test]
+    fn padding_32() {
+        assert_eq!(padding(0, 32), 0);
+        assert_eq!(padding(1, 32), 31);
+        assert_eq!(padding(32, 32), 0);
+        assert_eq!(padding(0x1_0000_0010, 32), 0x10);
+    }
+
     #[test]
     #[should_panic]
     fn align_0() { 0u32.align_next(0); }
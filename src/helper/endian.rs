@@ -1,19 +1,48 @@
+/// Little endian byte order, used as a type parameter.
 pub enum LittleEndian {}
 
+/// Big endian byte order, used as a type parameter.
 pub enum BigEndian {}
 
+/// Byte order of the target.
 #[cfg(target_endian = "little")]
 pub type NativeEndian = LittleEndian;
 
+/// Byte order of the target.
 #[cfg(target_endian = "big")]
 pub type NativeEndian = BigEndian;
 
+/// Byte order as a runtime value, for formats where the byte order is given
+/// by the data, e.g., a byte order mark in the header. See
+/// [`Parser::u32_endian`][`crate::Parser::u32_endian`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Little endian, see [`LittleEndian`].
+    Little,
+    /// Big endian, see [`BigEndian`].
+    Big,
+}
+
+impl Endian {
+    /// Byte order of the target.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endian = Endian::Little;
+
+    /// Byte order of the target.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endian = Endian::Big;
+}
+
+/// Conversion between a byte order and native values, implemented by
+/// [`BigEndian`] and [`LittleEndian`].
 pub trait EndianAgnostic {
     fn u16_from_bytes(bytes: &[u8]) -> u16;
     fn u32_from_bytes(bytes: &[u8]) -> u32;
+    fn u64_from_bytes(bytes: &[u8]) -> u64;
 
     fn u16_from_native(n: u16) -> u16;
     fn u32_from_native(n: u32) -> u32;
+    fn u64_from_native(n: u64) -> u64;
 }
 
 impl EndianAgnostic for LittleEndian {
@@ -29,11 +58,20 @@ impl EndianAgnostic for LittleEndian {
         u32::from_le_bytes(bytes.try_into().unwrap())
     }
 
+    #[inline]
+    fn u64_from_bytes(bytes: &[u8]) -> u64 {
+        debug_assert!(bytes.len() == 8);
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+
     #[inline]
     fn u16_from_native(n: u16) -> u16 { n.to_le() }
 
     #[inline]
     fn u32_from_native(n: u32) -> u32 { n.to_le() }
+
+    #[inline]
+    fn u64_from_native(n: u64) -> u64 { n.to_le() }
 }
 
 impl EndianAgnostic for BigEndian {
@@ -49,11 +87,20 @@ impl EndianAgnostic for BigEndian {
         u32::from_be_bytes(bytes.try_into().unwrap())
     }
 
+    #[inline]
+    fn u64_from_bytes(bytes: &[u8]) -> u64 {
+        debug_assert!(bytes.len() == 8);
+        u64::from_be_bytes(bytes.try_into().unwrap())
+    }
+
     #[inline]
     fn u16_from_native(n: u16) -> u16 { n.to_be() }
 
     #[inline]
     fn u32_from_native(n: u32) -> u32 { n.to_be() }
+
+    #[inline]
+    fn u64_from_native(n: u64) -> u64 { n.to_be() }
 }
 
 // -------------------------------------------------------------------------------
@@ -71,6 +118,10 @@ mod tests {
             BigEndian::u32_from_bytes(&[0x01, 0x02, 0x03, 0x04]),
             0x01020304
         );
+        assert_eq!(
+            BigEndian::u64_from_bytes(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]),
+            0x0102030405060708
+        );
     }
 
     #[test]
@@ -1,4 +1,23 @@
+use alloc::vec::Vec;
+use core::panic::Location;
+
 /// Enum for possible build problems that can occur.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
-pub enum BuildProblem {}
+pub enum BuildProblem {
+    /// Invalid value range found while building.
+    #[error("invalid range: {0}")]
+    InvalidRange(&'static str, &'static Location<'static>),
+
+    /// Unable to build from the given data.
+    #[error("invalid data: {0}")]
+    InvalidData(&'static str, &'static Location<'static>),
+
+    /// Two sections cover the same memory.
+    #[error("overlapping sections: {0} and {1} at {2}")]
+    OverlappingSections(&'static str, &'static str, &'static Location<'static>),
+
+    /// Relocations reference modules that could not be resolved.
+    #[error("unresolved modules: {0:?} at {1}")]
+    UnresolvedModules(Vec<u32>, &'static Location<'static>),
+}
@@ -1,4 +1,10 @@
+use core::panic::Location;
+
 /// Enum for possible compression problems that can occur.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
-pub enum CompressionProblem {}
+pub enum CompressionProblem {
+    /// The data to compress is larger than the format can represent.
+    #[error("input too large: {0} bytes at {1}")]
+    InputTooLarge(usize, &'static Location<'static>),
+}
@@ -1,16 +1,18 @@
-use std::panic::Location;
+use core::panic::Location;
 
 /// Enum for possible decoding problems that can occur.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
 pub enum DecodingProblem {
-    /// Decoding try to create a character that is not valid in UTF-8.
-    #[error("invalid code point: {0} at {1}")]
-    InvalidCodePoint(usize, &'static Location<'static>),
+    /// Decoding try to create a character that is not valid in UTF-8. The
+    /// second value is the index of the code point in the input.
+    #[error("invalid code point: {0} at index {1} at {2}")]
+    InvalidCodePoint(usize, usize, &'static Location<'static>),
 
-    /// Invalid byte sequence.
-    #[error("invalid byte: {0} at {1}")]
-    InvalidByte(u8, &'static Location<'static>),
+    /// Invalid byte sequence. The second value is the index of the byte in
+    /// the input.
+    #[error("invalid byte: {0} at index {1} at {2}")]
+    InvalidByte(u8, usize, &'static Location<'static>),
 
     /// Decoding failed due to unexpected end of data, .i.e, it requires more
     /// data to fully decode.
@@ -1,4 +1,4 @@
-use std::panic::Location;
+use core::panic::Location;
 
 /// Enum for possible encoding problems that can occur.
 #[derive(thiserror::Error, Debug)]
@@ -5,7 +5,8 @@ pub mod decompression;
 pub mod encoding;
 pub mod parse;
 
-use std::panic::Location;
+use alloc::boxed::Box;
+use core::panic::Location;
 
 use super::{
     BuildProblem, CompressionProblem, DecodingProblem, DecompressionProblem, EncodingProblem,
@@ -42,25 +43,29 @@ pub enum Error {
     Decoding(#[from] DecodingProblem),
 
     /// Reading failed.
+    #[cfg(feature = "std")]
     #[error("read failed: {0} bytes ({1}) at {2}")]
     ReadFailed(usize, #[source] std::io::Error, &'static Location<'static>),
 
     /// Seeking failed.
+    #[cfg(feature = "std")]
     #[error("seek failed: {0} at {1}")]
     SeekFailed(#[source] std::io::Error, &'static Location<'static>),
 
     /// Writing failed.
+    #[cfg(feature = "std")]
     #[error("write failed: {0} bytes ({1}) at {2}")]
     WriteFailed(usize, #[source] std::io::Error, &'static Location<'static>),
 
     /// Unknown IO error.
+    #[cfg(feature = "std")]
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 }
 
 /// A specialized [`Result`] type for Picori. This type is broadly used across
 /// internal and public APIs. The Err variant is [`Error`].
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 macro_rules! ensure {
     ($cond:expr, $err:expr) => {
@@ -72,9 +77,68 @@ macro_rules! ensure {
 
 pub(crate) use ensure;
 
+/// Extension trait for [`Result`] to annotate parse problems.
+pub trait ResultExt {
+    /// Wrap a [`ParseProblem`] in [`ParseProblem::Context`] with the name of
+    /// what was being parsed, e.g., `"fst"`. Other errors are returned
+    /// unchanged.
+    ///
+    /// ```
+    /// # use picori::error::ParseProblem;
+    /// use picori::{Error, ResultExt};
+    ///
+    /// let result: picori::Result<()> =
+    ///     Err(ParseProblem::InvalidData("bad entry", std::panic::Location::caller()).into());
+    /// let error = result.context("fst").unwrap_err();
+    /// assert_eq!(error.to_string(), "parse: fst: invalid data: bad entry");
+    /// ```
+    fn context(self, context: &'static str) -> Self;
+}
+
+impl<T> ResultExt for Result<T> {
+    #[track_caller]
+    fn context(self, context: &'static str) -> Self {
+        match self {
+            Err(Error::Parse(problem)) => Err(Error::Parse(ParseProblem::Context(
+                context,
+                Box::new(problem),
+                Location::caller(),
+            ))),
+            result => result,
+        }
+    }
+}
+
 pub trait ProblemLocation {
     #[track_caller]
-    fn current() -> &'static std::panic::Location<'static> { std::panic::Location::caller() }
+    fn current() -> &'static core::panic::Location<'static> { core::panic::Location::caller() }
 }
 
 impl ProblemLocation for Location<'_> {}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::error::Error as _;
+    use std::io::ErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn io_source() {
+        let error = Error::from(std::io::Error::new(ErrorKind::NotFound, "missing file"));
+        assert_eq!(error.to_string(), "io error: missing file");
+        assert_eq!(error.source().unwrap().to_string(), "missing file");
+    }
+
+    #[test]
+    fn seek_source() {
+        let io = std::io::Error::new(ErrorKind::InvalidInput, "bad seek");
+        let error = Error::SeekFailed(io, Location::caller());
+        assert!(error.to_string().starts_with("seek failed: bad seek at "));
+        assert_eq!(error.source().unwrap().to_string(), "bad seek");
+    }
+}
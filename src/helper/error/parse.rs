@@ -1,4 +1,5 @@
-use std::panic::Location;
+use alloc::boxed::Box;
+use core::panic::Location;
 
 /// Enum for possible parse problems that can occur.
 #[derive(thiserror::Error, Debug)]
@@ -20,6 +21,19 @@ pub enum ParseProblem {
     #[error("invalid data: {0}")]
     InvalidData(&'static str, &'static Location<'static>),
 
+    /// A count read while parsing exceeds the limit, e.g., the number of
+    /// entries in a table. Contains what was counted, the count and the
+    /// limit.
+    #[error("{0} limit exceeded: {1} > {2} at {3}")]
+    LimitExceeded(&'static str, usize, usize, &'static Location<'static>),
+
+    /// A problem that occurred while parsing a part of a larger structure,
+    /// e.g., the [FST][`crate::gcm::Fst`] of a disc. Contains the name of
+    /// the part and the original problem, see
+    /// [`ResultExt::context`][`crate::ResultExt::context`].
+    #[error("{0}: {1}")]
+    Context(&'static str, #[source] Box<ParseProblem>, &'static Location<'static>),
+
     /// Unsupported version.
     #[error("unsupported version: {0} at {1}")]
     UnsupportedVersion(usize, &'static Location<'static>),
@@ -0,0 +1,109 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::panic::Location;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::{DecodingProblem, Parser, Reader, Seeker, SliceParser};
+use crate::Result;
+
+/// A [`Parser`] and [`Seeker`] over a memory-mapped file. Requires the `mmap`
+/// feature.
+///
+/// Reads copy directly from the mapping, without the system calls of a
+/// [`File`] and without buffering, which makes seeking around large disc
+/// images cheap. Use [`MmapReader::data`] or [`MmapReader::read_slice`] to
+/// access the data without copying. Errors are reported like
+/// [`SliceParser`].
+///
+/// The file must not be modified or truncated while it is mapped, e.g., by
+/// another process. Doing so is undefined behavior, see [`Mmap`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use picori::Result;
+/// use picori::{Gcm, MmapReader};
+///
+/// fn main() -> Result<()> {
+///     let gcm = Gcm::from_binary(&mut MmapReader::open("disc.iso")?)?;
+///     Ok(())
+/// }
+/// ```
+pub struct MmapReader {
+    mmap:     Mmap,
+    position: usize,
+}
+
+impl MmapReader {
+    /// Open and map the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> { Self::from_file(&File::open(path)?) }
+
+    /// Map `file`. The mapping stays valid after `file` is closed.
+    pub fn from_file(file: &File) -> Result<Self> {
+        // SAFETY: see the type documentation, the file must not be modified
+        // while it is mapped.
+        let mmap = unsafe { Mmap::map(file)? };
+        Ok(Self { mmap, position: 0 })
+    }
+
+    /// The whole mapped file.
+    pub fn data(&self) -> &[u8] { &self.mmap }
+
+    /// The data from the current position to the end of the file.
+    pub fn remaining(&self) -> &[u8] { &self.mmap[self.position..] }
+
+    /// Read `size` bytes without copying them.
+    #[track_caller]
+    pub fn read_slice(&mut self, size: usize) -> Result<&[u8]> {
+        let start = self.position;
+        match self.mmap.get(start..start.saturating_add(size)) {
+            Some(data) => {
+                self.position += size;
+                Ok(data)
+            },
+            None => Err(DecodingProblem::UnexpectedEndOfData(Location::caller()).into()),
+        }
+    }
+
+    /// Run `f` on a [`SliceParser`] at the current position and keep the
+    /// position it ends at.
+    fn view<T>(&mut self, f: impl FnOnce(&mut SliceParser<'_>) -> T) -> T {
+        let mut view = SliceParser::new(&self.mmap);
+        // the position never exceeds the length of the mapping
+        let _ = view.goto(self.position as u64);
+        let result = f(&mut view);
+        self.position = self.mmap.len() - view.remaining().len();
+        result
+    }
+}
+
+impl Read for MmapReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> { self.view(|x| x.read(buf)) }
+}
+
+impl Seek for MmapReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> { self.view(|x| x.seek(pos)) }
+}
+
+impl Reader for MmapReader {
+    #[inline]
+    fn read_into_tracked(&mut self, buffer: &mut [u8], caller: &'static Location) -> Result<()> {
+        self.view(|x| x.read_into_tracked(buffer, caller))
+    }
+}
+
+impl Parser for MmapReader {}
+
+impl Seeker for MmapReader {
+    #[inline]
+    fn goto_tracked(&mut self, pos: u64, caller: &'static Location) -> Result<u64> {
+        self.view(|x| x.goto_tracked(pos, caller))
+    }
+
+    #[inline]
+    fn position_tracked(&mut self, _caller: &'static Location) -> Result<u64> {
+        Ok(self.position as u64)
+    }
+}
@@ -1,10 +1,18 @@
+#[cfg(feature = "std")]
 pub mod alignment;
 mod endian;
 mod error;
+#[cfg(feature = "mmap")]
+mod mmap_reader;
 
 mod parser;
+mod pod;
 mod reader;
 mod seeker;
+mod slice_parser;
+#[cfg(feature = "std")]
+mod string_table;
+#[cfg(feature = "std")]
 mod writer;
 
 pub use error::build::BuildProblem;
@@ -14,10 +22,18 @@ pub use error::decompression::DecompressionProblem;
 pub use error::encoding::EncodingProblem;
 pub use error::parse::ParseProblem;
 pub(crate) use error::{ensure, ProblemLocation};
-pub use error::{Error, Result};
+pub use error::{Error, Result, ResultExt};
 pub(crate) use parser::*;
+pub use endian::{BigEndian, Endian, EndianAgnostic, LittleEndian, NativeEndian};
+pub use pod::Pod;
 
+#[cfg(feature = "mmap")]
+pub use mmap_reader::MmapReader;
 pub use seeker::Seeker;
+pub use slice_parser::SliceParser;
+#[cfg(feature = "std")]
+pub use string_table::StringTable;
 pub use reader::Reader;
+#[cfg(feature = "std")]
 pub use writer::Writer;
 pub use parser::Parser;
\ No newline at end of file
@@ -1,8 +1,10 @@
-use std::borrow::Borrow;
-use std::panic::Location;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::panic::Location;
 
-use super::endian::{BigEndian, EndianAgnostic, LittleEndian, NativeEndian};
-use super::{Reader};
+use super::endian::{BigEndian, Endian, EndianAgnostic, LittleEndian, NativeEndian};
+use super::{Pod, Reader};
 use crate::Result;
 
 /// A helper trait for types that can interpret bytes.
@@ -30,6 +32,13 @@ pub trait Parser: Reader {
         Ok(E::u32_from_bytes(&buffer))
     }
 
+    /// Read a single endian agnostic u64.
+    fn eu64<E: EndianAgnostic>(&mut self, caller: &'static Location) -> Result<u64> {
+        let mut buffer = [0u8; 8];
+        self.read_into_tracked(&mut buffer, caller)?;
+        Ok(E::u64_from_bytes(&buffer))
+    }
+
     /// Read a single u16 in native endian.
     #[track_caller]
     #[inline]
@@ -51,6 +60,16 @@ pub trait Parser: Reader {
         self.eu16::<BigEndian>(Location::caller())
     }
 
+    /// Read a single 24-bit unsigned integer in big endian. Reads exactly
+    /// three bytes.
+    #[track_caller]
+    #[inline]
+    fn bu24(&mut self) -> Result<u32> {
+        let mut buffer = [0u8; 4];
+        self.read_into_tracked(&mut buffer[1..], Location::caller())?;
+        Ok(u32::from_be_bytes(buffer))
+    }
+
     /// Read a single u32 in big endian.
     #[track_caller]
     #[inline]
@@ -58,6 +77,13 @@ pub trait Parser: Reader {
         self.eu32::<BigEndian>(Location::caller())
     }
 
+    /// Read a single u64 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bu64(&mut self) -> Result<u64> {
+        self.eu64::<BigEndian>(Location::caller())
+    }
+
     /// Read a single u16 in little endian.
     #[track_caller]
     #[inline]
@@ -65,6 +91,16 @@ pub trait Parser: Reader {
         self.eu16::<LittleEndian>(Location::caller())
     }
 
+    /// Read a single 24-bit unsigned integer in little endian. Reads exactly
+    /// three bytes.
+    #[track_caller]
+    #[inline]
+    fn lu24(&mut self) -> Result<u32> {
+        let mut buffer = [0u8; 4];
+        self.read_into_tracked(&mut buffer[..3], Location::caller())?;
+        Ok(u32::from_le_bytes(buffer))
+    }
+
     /// Read a single u32 in little endian.
     #[track_caller]
     #[inline]
@@ -72,6 +108,165 @@ pub trait Parser: Reader {
         self.eu32::<LittleEndian>(Location::caller())
     }
 
+    /// Read a single u64 in little endian.
+    #[track_caller]
+    #[inline]
+    fn lu64(&mut self) -> Result<u64> {
+        self.eu64::<LittleEndian>(Location::caller())
+    }
+
+    /// Read a single u16 in the byte order given by `endian`.
+    #[track_caller]
+    #[inline]
+    fn u16_endian(&mut self, endian: Endian) -> Result<u16> {
+        match endian {
+            Endian::Big => self.eu16::<BigEndian>(Location::caller()),
+            Endian::Little => self.eu16::<LittleEndian>(Location::caller()),
+        }
+    }
+
+    /// Read a single u32 in the byte order given by `endian`.
+    #[track_caller]
+    #[inline]
+    fn u32_endian(&mut self, endian: Endian) -> Result<u32> {
+        match endian {
+            Endian::Big => self.eu32::<BigEndian>(Location::caller()),
+            Endian::Little => self.eu32::<LittleEndian>(Location::caller()),
+        }
+    }
+
+    /// Read a single u64 in the byte order given by `endian`.
+    #[track_caller]
+    #[inline]
+    fn u64_endian(&mut self, endian: Endian) -> Result<u64> {
+        match endian {
+            Endian::Big => self.eu64::<BigEndian>(Location::caller()),
+            Endian::Little => self.eu64::<LittleEndian>(Location::caller()),
+        }
+    }
+
+    /// Read a single i8.
+    #[track_caller]
+    #[inline]
+    fn i8(&mut self) -> Result<i8> {
+        let mut buffer = [0u8; 1];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i8::from_ne_bytes(buffer))
+    }
+
+    /// Read a single i16 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bi16(&mut self) -> Result<i16> {
+        let mut buffer = [0u8; 2];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i16::from_be_bytes(buffer))
+    }
+
+    /// Read a single i16 in little endian.
+    #[track_caller]
+    #[inline]
+    fn li16(&mut self) -> Result<i16> {
+        let mut buffer = [0u8; 2];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i16::from_le_bytes(buffer))
+    }
+
+    /// Read a single i32 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bi32(&mut self) -> Result<i32> {
+        let mut buffer = [0u8; 4];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i32::from_be_bytes(buffer))
+    }
+
+    /// Read a single i32 in little endian.
+    #[track_caller]
+    #[inline]
+    fn li32(&mut self) -> Result<i32> {
+        let mut buffer = [0u8; 4];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i32::from_le_bytes(buffer))
+    }
+
+    /// Read a single i64 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bi64(&mut self) -> Result<i64> {
+        let mut buffer = [0u8; 8];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i64::from_be_bytes(buffer))
+    }
+
+    /// Read a single i64 in little endian.
+    #[track_caller]
+    #[inline]
+    fn li64(&mut self) -> Result<i64> {
+        let mut buffer = [0u8; 8];
+        self.read_into_tracked(&mut buffer, Location::caller())?;
+        Ok(i64::from_le_bytes(buffer))
+    }
+
+    /// Read a single endian agnostic f32.
+    #[inline]
+    fn ef32<E: EndianAgnostic>(&mut self, caller: &'static Location) -> Result<f32> {
+        Ok(f32::from_bits(self.eu32::<E>(caller)?))
+    }
+
+    /// Read a single endian agnostic f64.
+    #[inline]
+    fn ef64<E: EndianAgnostic>(&mut self, caller: &'static Location) -> Result<f64> {
+        Ok(f64::from_bits(self.eu64::<E>(caller)?))
+    }
+
+    /// Read a single f32 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bf32(&mut self) -> Result<f32> {
+        self.ef32::<BigEndian>(Location::caller())
+    }
+
+    /// Read a single f32 in little endian.
+    #[track_caller]
+    #[inline]
+    fn lf32(&mut self) -> Result<f32> {
+        self.ef32::<LittleEndian>(Location::caller())
+    }
+
+    /// Read a single f64 in big endian.
+    #[track_caller]
+    #[inline]
+    fn bf64(&mut self) -> Result<f64> {
+        self.ef64::<BigEndian>(Location::caller())
+    }
+
+    /// Read a single f64 in little endian.
+    #[track_caller]
+    #[inline]
+    fn lf64(&mut self) -> Result<f64> {
+        self.ef64::<LittleEndian>(Location::caller())
+    }
+
+    /// Read raw bytes until `terminator` is encountered. The terminator is
+    /// consumed but not included in the result. Reads one byte at a time, so
+    /// the length doesn't have to be known in advance. Running out of data
+    /// before the terminator fails with
+    /// [`UnexpectedEndOfData`][crate::error::DecodingProblem::UnexpectedEndOfData].
+    #[track_caller]
+    fn read_until_byte(&mut self, terminator: u8) -> Result<Vec<u8>> {
+        let caller = Location::caller();
+        let mut data = Vec::new();
+        loop {
+            let mut byte = [0u8; 1];
+            self.read_exact_into_tracked(&mut byte, caller)?;
+            if byte[0] == terminator {
+                return Ok(data);
+            }
+            data.push(byte[0]);
+        }
+    }
+
     /// Read L bytes and parse them as a string until the first string terminator.
     #[track_caller]
     #[inline]
@@ -81,8 +276,9 @@ pub trait Parser: Reader {
         E::parse_str(buffer)
     }
 
-    /// Read string with the given encoding until the string terminator is
-    /// encountered or `L` bytes have been read.
+    /// Read string with the given encoding until the NULL terminator or the
+    /// end of the data is encountered. The terminator is consumed but not
+    /// included in the result.
     #[inline]
     fn str<E: ParseStringEncoding>(&mut self) -> Result<String>
     where
@@ -91,6 +287,18 @@ pub trait Parser: Reader {
         E::from_binary(self)
     }
 
+    /// Read string with the given encoding until the raw byte `terminator` is
+    /// encountered. The terminator is consumed but not included in the result.
+    /// Bytes are read one at a time so nothing past the terminator is consumed,
+    /// see [`Parser::read_until_byte`].
+    #[track_caller]
+    fn str_until<E: ParseStringEncoding>(&mut self, terminator: u8) -> Result<String>
+    where
+        Self: Sized,
+    {
+        E::parse_all(self.read_until_byte(terminator)?)
+    }
+
     /// Read array of u8 with the given length `L`.
     #[track_caller]
     #[inline]
@@ -151,10 +359,58 @@ pub trait Parser: Reader {
     fn lu32_array<const L: usize>(&mut self) -> Result<[u32; L]> {
         self.eu32_array::<LittleEndian, L>(Location::caller())
     }
+
+    /// Read array of endian agnostic u64 with the given length `L`.
+    #[inline]
+    fn eu64_array<E: EndianAgnostic, const L: usize>(
+        &mut self,
+        caller: &'static Location,
+    ) -> Result<[u64; L]> {
+        let mut buf = self.read_buffer_of_tracked::<u64, L>(caller)?;
+        for value in buf.iter_mut().take(L) {
+            *value = E::u64_from_native(*value);
+        }
+        Ok(buf)
+    }
+
+    /// Read array of big endian u64 with the given length `L`.
+    #[track_caller]
+    #[inline]
+    fn bu64_array<const L: usize>(&mut self) -> Result<[u64; L]> {
+        self.eu64_array::<BigEndian, L>(Location::caller())
+    }
+
+    /// Read array of little endian u64 with the given length `L`.
+    #[track_caller]
+    #[inline]
+    fn lu64_array<const L: usize>(&mut self) -> Result<[u64; L]> {
+        self.eu64_array::<LittleEndian, L>(Location::caller())
+    }
+
+    /// Read array of [`Pod`] values with the given length `L`, in the byte
+    /// order given by `E`, i.e., [`BigEndian`] or [`LittleEndian`]. See
+    /// [`Pod`] for how the byte order of custom types is converted.
+    #[track_caller]
+    #[inline]
+    fn read_pod_array<T: Pod, E: EndianAgnostic, const L: usize>(&mut self) -> Result<[T; L]> {
+        let mut buffer = self.read_buffer_of_tracked::<T, L>(Location::caller())?;
+        if E::u16_from_native(1) != 1 {
+            buffer = buffer.map(T::swap_bytes);
+        }
+        Ok(buffer)
+    }
+}
+
+/// Iterator over the bytes of `reader`, ending at the first failed read.
+pub(crate) fn parser_bytes(reader: &mut impl Parser) -> impl Iterator<Item = u8> + '_ {
+    core::iter::from_fn(move || reader.u8().ok())
 }
 
+#[cfg(feature = "std")]
 impl Parser for std::fs::File {}
+#[cfg(feature = "std")]
 impl<T: Parser> Parser for std::io::BufReader<T> {}
+#[cfg(feature = "std")]
 impl<T> Parser for std::io::Cursor<T>
 where
     Self: Reader,
@@ -168,6 +424,11 @@ pub trait ParseStringEncoding {
         I: IntoIterator,
         I::Item: Borrow<u8> + Sized;
 
+    fn parse_all<I>(data: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized;
+
     fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize>;
 
     fn from_binary(reader: &mut impl Parser) -> Result<String>;
@@ -177,7 +438,7 @@ pub trait ParseStringEncoding {
 // Tests
 // -------------------------------------------------------------------------------
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::io::Cursor;
 
@@ -201,6 +462,16 @@ mod tests {
         assert_eq!(cursor.lu16().unwrap(), 0x0201);
     }
 
+    #[test]
+    fn u24() {
+        let data: &[u8] = &[0x01, 0x02, 0x03, 0x01, 0x02, 0x03, 0x04];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bu24().unwrap(), 0x010203);
+        assert_eq!(cursor.lu24().unwrap(), 0x030201);
+        assert_eq!(cursor.position(), 6);
+        assert!(cursor.bu24().is_err());
+    }
+
     #[test]
     fn u32() {
         let data: &[u8] = &[0x01, 0x02, 0x03, 0x04, 0x01, 0x02, 0x03, 0x04];
@@ -209,6 +480,110 @@ mod tests {
         assert_eq!(cursor.lu32().unwrap(), 0x04030201);
     }
 
+    #[test]
+    fn endian() {
+        let data: &[u8] = &[0x01, 0x02, 0x01, 0x02, 0x03, 0x04, 0x01, 0x02, 0x03, 0x04, 0, 0, 0, 0];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.u16_endian(Endian::Little).unwrap(), 0x0201);
+        assert_eq!(cursor.u32_endian(Endian::Big).unwrap(), 0x01020304);
+        assert_eq!(cursor.u64_endian(Endian::Little).unwrap(), 0x04030201);
+        assert!(cursor.u16_endian(Endian::Big).is_err());
+    }
+
+    #[test]
+    fn read_until_byte() {
+        let data: &[u8] = b"abc\0\0de\nf";
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.read_until_byte(0).unwrap(), b"abc");
+        assert_eq!(cursor.read_until_byte(0).unwrap(), b"");
+        assert_eq!(cursor.read_until_byte(b'\n').unwrap(), b"de");
+        assert!(cursor.read_until_byte(0).is_err());
+    }
+
+    #[test]
+    fn read_pod_array() {
+        let data: &[u8] = &[0x01, 0x02, 0x3F, 0x80, 0x00, 0x00, 0x01, 0x02, 0x00, 0x00, 0x80, 0x3F];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.read_pod_array::<u16, BigEndian, 1>().unwrap(), [0x0102]);
+        assert_eq!(cursor.read_pod_array::<f32, BigEndian, 1>().unwrap(), [1.0]);
+        assert_eq!(cursor.read_pod_array::<[u8; 2], LittleEndian, 1>().unwrap(), [[1, 2]]);
+        assert_eq!(cursor.read_pod_array::<f32, LittleEndian, 1>().unwrap(), [1.0]);
+        assert!(cursor.read_pod_array::<u8, BigEndian, 1>().is_err());
+    }
+
+    #[test]
+    fn u64() {
+        let data: &[u8] = &[
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+            0x07, 0x08,
+        ];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bu64().unwrap(), 0x0102030405060708);
+        assert_eq!(cursor.lu64().unwrap(), 0x0807060504030201);
+        assert!(cursor.bu64().is_err());
+    }
+
+    #[test]
+    fn i8() {
+        let data: &[u8] = &[0x7F, 0x80, 0xFF];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.i8().unwrap(), 127);
+        assert_eq!(cursor.i8().unwrap(), -128);
+        assert_eq!(cursor.i8().unwrap(), -1);
+        assert!(cursor.i8().is_err());
+    }
+
+    #[test]
+    fn i16() {
+        let data: &[u8] = &[0xFF, 0xFE, 0xFE, 0xFF, 0x80, 0x00];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bi16().unwrap(), -2);
+        assert_eq!(cursor.li16().unwrap(), -2);
+        assert_eq!(cursor.bi16().unwrap(), i16::MIN);
+    }
+
+    #[test]
+    fn i32() {
+        let data: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFE, 0xFE, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bi32().unwrap(), -2);
+        assert_eq!(cursor.li32().unwrap(), -2);
+        assert_eq!(cursor.bi32().unwrap(), i32::MAX);
+    }
+
+    #[test]
+    fn i64() {
+        let data: &[u8] = &[
+            0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF,
+        ];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bi64().unwrap(), -2);
+        assert_eq!(cursor.li64().unwrap(), -2);
+        assert!(cursor.bi64().is_err());
+    }
+
+    #[test]
+    fn f32() {
+        let data: &[u8] = &[0x3F, 0x80, 0x00, 0x00, 0x00, 0x00, 0x20, 0xC1, 0x00];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bf32().unwrap(), 1.0);
+        assert_eq!(cursor.lf32().unwrap(), -10.0);
+        assert!(cursor.bf32().is_err());
+    }
+
+    #[test]
+    fn f64() {
+        let data: &[u8] = &[
+            0x3F, 0xF0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x24, 0xC0,
+        ];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bf64().unwrap(), 1.0);
+        assert_eq!(cursor.lf64().unwrap(), -10.0);
+        assert!(cursor.bf64().is_err());
+    }
+
     #[test]
     fn u8_array() {
         let data: &[u8] = &[0x01, 0x02, 0x03, 0x04];
@@ -234,4 +609,40 @@ mod tests {
         assert_eq!(cursor.bu32_array::<2>().unwrap(), [0x01020304, 0x05060708]);
         assert_eq!(cursor.lu32_array::<2>().unwrap(), [0x04030201, 0x08070605]);
     }
+
+    #[test]
+    fn u64_array() {
+        let data: &[u8] = &[
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+            0x07, 0x08,
+        ];
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.bu64_array::<1>().unwrap(), [0x0102030405060708]);
+        assert_eq!(cursor.lu64_array::<1>().unwrap(), [0x0807060504030201]);
+    }
+
+    #[test]
+    fn str() {
+        let data: &[u8] = b"abc\0def\0";
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.str::<crate::Ascii>().unwrap(), "abc");
+        assert_eq!(cursor.str::<crate::Ascii>().unwrap(), "def");
+        assert_eq!(cursor.position(), 8);
+        assert_eq!(cursor.str::<crate::Ascii>().unwrap(), "");
+
+        // unterminated string at the end of the data
+        let mut cursor = Cursor::new(b"abc\0def".as_slice());
+        assert_eq!(cursor.str::<crate::Ascii>().unwrap(), "abc");
+        assert_eq!(cursor.str::<crate::Ascii>().unwrap(), "def");
+    }
+
+    #[test]
+    fn str_until() {
+        let data: &[u8] = b"ab\0c/def/";
+        let mut cursor = Cursor::new(data);
+        assert_eq!(cursor.str_until::<crate::Ascii>(b'/').unwrap(), "ab\0c");
+        assert_eq!(cursor.position(), 5);
+        assert_eq!(cursor.str_until::<crate::Ascii>(b'/').unwrap(), "def");
+        assert!(cursor.str_until::<crate::Ascii>(b'/').is_err());
+    }
 }
@@ -0,0 +1,83 @@
+/// Plain old data, i.e., a type that can be read directly from bytes with
+/// [`Parser::read_pod_array`][`crate::Parser::read_pod_array`].
+///
+/// Implemented for the integer and floating point types and for arrays of
+/// [`Pod`] types. Custom types, e.g., a vertex `struct Vec3 { x: f32, y: f32,
+/// z: f32 }`, can implement it as well:
+///
+/// ```
+/// # use picori::Result;
+/// use picori::{BigEndian, Parser, Pod, SliceParser};
+///
+/// #[repr(C)]
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Vec3 {
+///     x: f32,
+///     y: f32,
+///     z: f32,
+/// }
+///
+/// unsafe impl Pod for Vec3 {
+///     fn swap_bytes(self) -> Self {
+///         Vec3 {
+///             x: self.x.swap_bytes(),
+///             y: self.y.swap_bytes(),
+///             z: self.z.swap_bytes(),
+///         }
+///     }
+/// }
+///
+/// fn main() -> Result<()> {
+///     let data = [0x3F, 0x80, 0, 0, 0x40, 0, 0, 0, 0x40, 0x40, 0, 0];
+///     let mut parser = SliceParser::new(&data);
+///     let [v] = parser.read_pod_array::<Vec3, BigEndian, 1>()?;
+///     assert_eq!(v, Vec3 { x: 1.0, y: 2.0, z: 3.0 });
+///     Ok(())
+/// }
+/// ```
+///
+/// # Endianness
+///
+/// The bytes are read into memory as is and, if the requested endianness
+/// differs from the native endianness, [`Pod::swap_bytes`] is called on
+/// every element. The conversion is only as correct as the implementation of
+/// [`Pod::swap_bytes`]: it must swap every field separately, swapping the
+/// whole struct would also reverse the order of the fields.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value of the type, e.g., `bool`, `char`
+/// and references are not allowed, and the type must not contain padding.
+/// Use `#[repr(C)]` for structs so the field order matches the data.
+pub unsafe trait Pod: Copy + 'static {
+    /// Reverse the byte order of every field.
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! pod_int {
+    ($($ty:ty),*) => {
+        $(
+            unsafe impl Pod for $ty {
+                #[inline]
+                fn swap_bytes(self) -> Self { <$ty>::swap_bytes(self) }
+            }
+        )*
+    };
+}
+
+pod_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+unsafe impl Pod for f32 {
+    #[inline]
+    fn swap_bytes(self) -> Self { f32::from_bits(self.to_bits().swap_bytes()) }
+}
+
+unsafe impl Pod for f64 {
+    #[inline]
+    fn swap_bytes(self) -> Self { f64::from_bits(self.to_bits().swap_bytes()) }
+}
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {
+    #[inline]
+    fn swap_bytes(self) -> Self { self.map(T::swap_bytes) }
+}
@@ -1,10 +1,26 @@
-use std::io::Read;
-use std::panic::Location;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{ErrorKind, Read as ReaderBase};
 
-use crate::{Error, Result};
+use super::Pod;
+use crate::error::ParseProblem;
+#[cfg(feature = "std")]
+use crate::{error::DecodingProblem, Error};
+use crate::Result;
 
-/// A helper trait for types that can read data into a buffer.
-pub trait Reader: Read {
+/// Supertrait of [`Reader`], [`std::io::Read`] with the `std` feature.
+#[cfg(not(feature = "std"))]
+pub trait ReaderBase {}
+
+#[cfg(not(feature = "std"))]
+impl<T: ?Sized> ReaderBase for T {}
+
+/// A helper trait for types that can read data into a buffer. With the `std`
+/// feature, it is implemented on top of [`std::io::Read`], otherwise
+/// [`Reader::read_into_tracked`] must be implemented.
+pub trait Reader: ReaderBase {
     /// Read data into a mutable buffer.
     #[track_caller]
     #[inline]
@@ -13,11 +29,12 @@ pub trait Reader: Read {
     }
 
     /// Read data into a mutable buffer. With caller location.
+    #[cfg(feature = "std")]
     #[inline]
     fn read_into_tracked(
         &mut self,
         buffer: &mut [u8],
-        caller: &'static std::panic::Location,
+        caller: &'static core::panic::Location,
     ) -> Result<()> {
         match self.read_exact(buffer) {
             Ok(..) => Ok(()),
@@ -25,49 +42,182 @@ pub trait Reader: Read {
         }
     }
 
+    /// Read data into a mutable buffer. With caller location.
+    #[cfg(not(feature = "std"))]
+    fn read_into_tracked(
+        &mut self,
+        buffer: &mut [u8],
+        caller: &'static core::panic::Location,
+    ) -> Result<()>;
+
+    /// Read data until `buffer` is full. Short reads are retried, and running
+    /// out of data fails with [`DecodingProblem::UnexpectedEndOfData`]
+    /// instead of an I/O error, so the buffer is never left partially filled
+    /// on success.
+    #[track_caller]
+    #[inline]
+    fn read_exact_into(&mut self, buffer: &mut [u8]) -> Result<()> {
+        self.read_exact_into_tracked(buffer, Location::caller())
+    }
+
+    /// Read data until `buffer` is full. With caller location.
+    #[cfg(feature = "std")]
+    fn read_exact_into_tracked(
+        &mut self,
+        buffer: &mut [u8],
+        caller: &'static core::panic::Location,
+    ) -> Result<()> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.read(&mut buffer[filled..]) {
+                Ok(0) => return Err(DecodingProblem::UnexpectedEndOfData(caller).into()),
+                Ok(n) => filled += n,
+                Err(io) if io.kind() == ErrorKind::Interrupted => {},
+                Err(io) => return Err(Error::ReadFailed(buffer.len(), io, caller)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read data until `buffer` is full. With caller location.
+    #[cfg(not(feature = "std"))]
+    fn read_exact_into_tracked(
+        &mut self,
+        buffer: &mut [u8],
+        caller: &'static core::panic::Location,
+    ) -> Result<()> {
+        self.read_into_tracked(buffer, caller)
+    }
+
     /// Read data into new buffer of u8.
     #[track_caller]
     #[inline]
     fn read_as_vec(&mut self, size: usize) -> Result<Vec<u8>> {
+        self.read_as_vec_max_tracked(size, usize::MAX, Location::caller())
+    }
+
+    /// Read data into new buffer of u8. Fails with
+    /// [`ParseProblem::InvalidRange`] before allocating if `size` is larger
+    /// than `max`.
+    #[track_caller]
+    #[inline]
+    fn read_as_vec_max(&mut self, size: usize, max: usize) -> Result<Vec<u8>> {
+        self.read_as_vec_max_tracked(size, max, Location::caller())
+    }
+
+    /// Read data into new buffer of u8 of at most `max` bytes. With caller
+    /// location.
+    #[inline]
+    fn read_as_vec_max_tracked(
+        &mut self,
+        size: usize,
+        max: usize,
+        caller: &'static core::panic::Location,
+    ) -> Result<Vec<u8>> {
+        if size > max {
+            return Err(ParseProblem::InvalidRange("buffer size exceeds maximum", caller).into());
+        }
+
         let mut vec = vec![0u8; size];
-        self.read_into_tracked(vec.as_mut_slice(), Location::caller())?;
+        self.read_into_tracked(vec.as_mut_slice(), caller)?;
         Ok(vec)
     }
 
-    /// Read `L` items of type `T` from this reader or `L` * `sizeof(T)` bytes.
+    /// Read `L` items of type `T` from this reader or `L` * `sizeof(T)` bytes,
+    /// in native byte order. `T` must be [`Pod`], i.e., any bytes read are a
+    /// valid value.
     #[track_caller]
     #[inline]
-    fn read_buffer_of<T: Sized, const L: usize>(&mut self) -> Result<[T; L]>
-    where
-        T: Copy + Default,
-    {
+    fn read_buffer_of<T: Pod, const L: usize>(&mut self) -> Result<[T; L]> {
         self.read_buffer_of_tracked::<T, L>(Location::caller())
     }
 
     /// Read `L` items of type `T` from this reader or `L` * `sizeof(T)` bytes.
     /// With caller location.
     #[inline]
-    fn read_buffer_of_tracked<T: Sized, const L: usize>(
+    fn read_buffer_of_tracked<T: Pod, const L: usize>(
         &mut self,
-        caller: &'static std::panic::Location,
-    ) -> Result<[T; L]>
-    where
-        T: Copy + Default,
-    {
+        caller: &'static core::panic::Location,
+    ) -> Result<[T; L]> {
+        // SAFETY: `Pod` guarantees that every bit pattern, including zero, is
+        // a valid value and that `T` has no padding, so the buffer can be
+        // zero-initialized and overwritten byte by byte.
+        let mut buffer = [unsafe { core::mem::zeroed::<T>() }; L];
         let length = L * core::mem::size_of::<T>();
-        let mut buffer = [T::default(); L];
         let ptr = buffer.as_mut_ptr() as *mut u8;
-        let slice = unsafe { std::slice::from_raw_parts_mut(ptr, length) };
+        let slice = unsafe { core::slice::from_raw_parts_mut(ptr, length) };
         self.read_into_tracked(slice, caller)?;
         Ok(buffer)
     }
 }
 
+#[cfg(feature = "std")]
 impl Reader for std::fs::File {}
+#[cfg(feature = "std")]
 impl<T: Reader> Reader for std::io::BufReader<T> {}
+#[cfg(feature = "std")]
 impl<T> Reader for std::io::Cursor<T>
 where
-    Self: Read,
+    Self: std::io::Read,
     T: AsRef<[u8]>,
 {
 }
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::*;
+
+    #[test]
+    fn read_as_vec_max() {
+        let data: &[u8] = &[0x01, 0x02, 0x03, 0x04];
+        let mut cursor = Cursor::new(data);
+        assert!(cursor.read_as_vec_max(usize::MAX, 0x10).is_err());
+        assert_eq!(cursor.position(), 0);
+        assert_eq!(cursor.read_as_vec_max(2, 2).unwrap(), vec![0x01, 0x02]);
+        assert_eq!(cursor.read_as_vec(2).unwrap(), vec![0x03, 0x04]);
+        assert!(cursor.read_as_vec_max(1, 1).is_err());
+    }
+
+    /// Reader that returns at most one byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let length = self.0.len().min(buf.len()).min(1);
+            buf[..length].copy_from_slice(&self.0[..length]);
+            self.0 = &self.0[length..];
+            Ok(length)
+        }
+    }
+
+    impl Reader for Trickle<'_> {}
+
+    #[test]
+    fn read_exact_into() {
+        let mut reader = Trickle(&[0x01, 0x02, 0x03, 0x04, 0x05]);
+        let mut buffer = [0u8; 4];
+        reader.read_exact_into(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x01, 0x02, 0x03, 0x04]);
+        assert!(matches!(
+            reader.read_exact_into(&mut buffer),
+            Err(Error::Decoding(DecodingProblem::UnexpectedEndOfData(_)))
+        ));
+    }
+
+    #[test]
+    fn read_buffer_of() {
+        let mut reader = Trickle(&[0x01, 0x02, 0x03, 0x04, 0x05]);
+        let buffer = reader.read_buffer_of::<u16, 2>().unwrap();
+        assert_eq!(buffer, [
+            u16::from_ne_bytes([0x01, 0x02]),
+            u16::from_ne_bytes([0x03, 0x04])
+        ]);
+        assert!(reader.read_buffer_of::<u16, 1>().is_err());
+    }
+}
@@ -1,10 +1,23 @@
-use std::io::{Seek, SeekFrom};
-use std::panic::Location;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{Seek as SeekerBase, SeekFrom};
 
-use crate::{Error, Result};
+#[cfg(feature = "std")]
+use crate::Error;
+use crate::Result;
 
-/// A helper trait for types that can seek.
-pub trait Seeker: Seek {
+/// Supertrait of [`Seeker`], [`std::io::Seek`] with the `std` feature.
+#[cfg(not(feature = "std"))]
+pub trait SeekerBase {}
+
+#[cfg(not(feature = "std"))]
+impl<T: ?Sized> SeekerBase for T {}
+
+/// A helper trait for types that can seek. With the `std` feature, it is
+/// implemented on top of [`std::io::Seek`], otherwise
+/// [`Seeker::goto_tracked`] and [`Seeker::position_tracked`] must be
+/// implemented.
+pub trait Seeker: SeekerBase {
     /// Seek to the given position.
     #[track_caller]
     fn goto(&mut self, pos: u64) -> Result<u64> {
@@ -12,6 +25,7 @@ pub trait Seeker: Seek {
     }
 
     /// Seek to the given position.
+    #[cfg(feature = "std")]
     #[inline]
     fn goto_tracked(&mut self, pos: u64, caller: &'static Location) -> Result<u64> {
         match self.seek(SeekFrom::Start(pos)) {
@@ -20,6 +34,10 @@ pub trait Seeker: Seek {
         }
     }
 
+    /// Seek to the given position.
+    #[cfg(not(feature = "std"))]
+    fn goto_tracked(&mut self, pos: u64, caller: &'static Location) -> Result<u64>;
+
     /// Get the current position.
     #[track_caller]
     fn position(&mut self) -> Result<u64> {
@@ -27,6 +45,7 @@ pub trait Seeker: Seek {
     }
 
     /// Get the current position.
+    #[cfg(feature = "std")]
     #[inline]
     fn position_tracked(&mut self, caller: &'static Location) -> Result<u64> {
         match self.stream_position() {
@@ -34,13 +53,22 @@ pub trait Seeker: Seek {
             Err(e) => Err(Error::SeekFailed(e, caller)),
         }
     }
+
+    /// Get the current position.
+    #[cfg(not(feature = "std"))]
+    fn position_tracked(&mut self, caller: &'static Location) -> Result<u64>;
 }
 
+#[cfg(feature = "std")]
 impl Seeker for std::fs::File {}
+#[cfg(feature = "std")]
 impl<T: Seeker> Seeker for std::io::BufReader<T> {}
+#[cfg(feature = "std")]
+impl<T: Seeker + std::io::Write> Seeker for std::io::BufWriter<T> {}
+#[cfg(feature = "std")]
 impl<T> Seeker for std::io::Cursor<T>
 where
-    Self: Seek,
+    Self: std::io::Seek,
     T: AsRef<[u8]>,
 {
 }
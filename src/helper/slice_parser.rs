@@ -0,0 +1,158 @@
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, SeekFrom};
+
+use super::{DecodingProblem, ParseProblem, Parser, Reader, Seeker};
+use crate::Result;
+
+/// An in-memory [`Parser`] and [`Seeker`] over a byte slice.
+///
+/// Unlike [`std::io::Cursor`], running out of data fails with
+/// [`DecodingProblem::UnexpectedEndOfData`] and seeking past the end of the
+/// slice fails with [`ParseProblem::InvalidRange`], both with the location of
+/// the caller, instead of wrapping an [`std::io::Error`].
+///
+/// # Examples
+///
+/// ```
+/// # use picori::Result;
+/// use picori::{Parser, SliceParser};
+///
+/// fn main() -> Result<()> {
+///     let mut parser = SliceParser::new(&[0x12, 0x34, 0x56, 0x78]);
+///     assert_eq!(parser.bu16()?, 0x1234);
+///     assert_eq!(parser.remaining(), &[0x56, 0x78]);
+///     assert!(parser.bu32().is_err());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SliceParser<'a> {
+    data:     &'a [u8],
+    position: usize,
+}
+
+impl<'a> SliceParser<'a> {
+    /// Create a [`SliceParser`] positioned at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Self { Self { data, position: 0 } }
+
+    /// The whole underlying slice.
+    pub fn data(&self) -> &'a [u8] { self.data }
+
+    /// The data from the current position to the end of the slice.
+    pub fn remaining(&self) -> &'a [u8] { &self.data[self.position..] }
+}
+
+#[cfg(feature = "std")]
+impl Read for SliceParser<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.remaining();
+        let length = remaining.len().min(buf.len());
+        buf[..length].copy_from_slice(&remaining[..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Seek for SliceParser<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.data.len() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => (self.position as u64).checked_add_signed(offset),
+        };
+        match position {
+            Some(position) if position <= self.data.len() as u64 => {
+                self.position = position as usize;
+                Ok(position)
+            },
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek out of bounds",
+            )),
+        }
+    }
+}
+
+impl Reader for SliceParser<'_> {
+    #[inline]
+    fn read_into_tracked(
+        &mut self,
+        buffer: &mut [u8],
+        caller: &'static core::panic::Location,
+    ) -> Result<()> {
+        match self.remaining().get(..buffer.len()) {
+            Some(data) => {
+                buffer.copy_from_slice(data);
+                self.position += buffer.len();
+                Ok(())
+            },
+            None => Err(DecodingProblem::UnexpectedEndOfData(caller).into()),
+        }
+    }
+}
+
+impl Parser for SliceParser<'_> {}
+
+impl Seeker for SliceParser<'_> {
+    #[inline]
+    fn goto_tracked(&mut self, pos: u64, caller: &'static Location) -> Result<u64> {
+        if pos > self.data.len() as u64 {
+            return Err(ParseProblem::InvalidRange("seek out of bounds", caller).into());
+        }
+
+        self.position = pos as usize;
+        Ok(pos)
+    }
+
+    #[inline]
+    fn position_tracked(&mut self, _caller: &'static Location) -> Result<u64> {
+        Ok(self.position as u64)
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn parse() {
+        let mut parser = SliceParser::new(&[0x01, 0x02, 0x03, 0x04, 0x05]);
+        assert_eq!(parser.u8().unwrap(), 0x01);
+        assert_eq!(parser.bu32().unwrap(), 0x02030405);
+        assert_eq!(parser.position().unwrap(), 5);
+        assert!(parser.remaining().is_empty());
+        assert!(matches!(
+            parser.u8(),
+            Err(Error::Decoding(DecodingProblem::UnexpectedEndOfData(_)))
+        ));
+    }
+
+    #[test]
+    fn goto() {
+        let mut parser = SliceParser::new(&[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(parser.goto(2).unwrap(), 2);
+        assert_eq!(parser.bu16().unwrap(), 0x0304);
+        assert_eq!(parser.goto(4).unwrap(), 4);
+        assert!(matches!(
+            parser.goto(5),
+            Err(Error::Parse(ParseProblem::InvalidRange(..)))
+        ));
+        assert_eq!(parser.position().unwrap(), 4);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn seek() {
+        let mut parser = SliceParser::new(&[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(parser.seek(SeekFrom::End(-3)).unwrap(), 1);
+        assert_eq!(parser.read_as_vec(3).unwrap(), vec![0x02, 0x03, 0x04]);
+        assert!(parser.seek(SeekFrom::Current(-5)).is_err());
+    }
+}
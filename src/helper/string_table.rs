@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::panic::Location;
+
+use super::{ParseProblem, ParseStringEncoding, ProblemLocation};
+use crate::Result;
+
+/// A table of NUL-terminated strings indexed by their byte offset into the
+/// table. [FST][`crate::gcm::fst`] and [RARC][`crate::rarc`] both store
+/// names this way.
+#[derive(Debug, Clone, Default)]
+pub struct StringTable {
+    data:    Vec<u8>,
+    offsets: HashMap<Vec<u8>, u32>,
+}
+
+impl StringTable {
+    /// Create a [`StringTable`] from the raw bytes of a string table. Strings
+    /// already in `data` are not considered by [`StringTable::push`] when
+    /// deduplicating.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Decode the string starting at `offset` with the encoding `E`. The string
+    /// ends at the first NUL byte or at the end of the table.
+    #[track_caller]
+    pub fn get<E: ParseStringEncoding>(&self, offset: u32) -> Result<String> {
+        match self.data.get(offset as usize..) {
+            Some(data) if !data.is_empty() => E::parse_str(data),
+            _ => Err(ParseProblem::InvalidRange(
+                "string table offset out of bounds",
+                Location::current(),
+            )
+            .into()),
+        }
+    }
+
+    /// Encode `value` with the encoding `E` and append it, NUL-terminated, to
+    /// the table. Returns the offset of the string. Pushing the same string
+    /// more than once returns the offset of the first copy.
+    #[track_caller]
+    pub fn push<E: ParseStringEncoding>(&mut self, value: &str) -> Result<u32> {
+        let mut buffer = vec![0u8; value.len() * 4];
+        let length = E::write_str(value, &mut buffer)?;
+        buffer.truncate(length);
+
+        if let Some(offset) = self.offsets.get(&buffer) {
+            return Ok(*offset);
+        }
+
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(&buffer);
+        self.data.push(0);
+        self.offsets.insert(buffer, offset);
+        Ok(offset)
+    }
+
+    /// Size of the table in bytes.
+    pub fn len(&self) -> usize { self.data.len() }
+
+    /// Returns `true` if the table contains no bytes.
+    pub fn is_empty(&self) -> bool { self.data.is_empty() }
+
+    /// Raw bytes of the table.
+    pub fn as_bytes(&self) -> &[u8] { &self.data }
+}
+
+impl From<Vec<u8>> for StringTable {
+    fn from(data: Vec<u8>) -> Self { Self::new(data) }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ascii;
+
+    #[test]
+    fn get() {
+        let table = StringTable::new(b"abc\0def\0ghi".to_vec());
+        assert_eq!(table.get::<Ascii>(0).unwrap(), "abc");
+        assert_eq!(table.get::<Ascii>(1).unwrap(), "bc");
+        assert_eq!(table.get::<Ascii>(4).unwrap(), "def");
+        assert_eq!(table.get::<Ascii>(8).unwrap(), "ghi");
+        assert!(table.get::<Ascii>(11).is_err());
+        assert!(table.get::<Ascii>(0xFFFF_FFFF).is_err());
+    }
+
+    #[test]
+    fn push() {
+        let mut table = StringTable::default();
+        assert_eq!(table.push::<Ascii>("abc").unwrap(), 0);
+        assert_eq!(table.push::<Ascii>("def").unwrap(), 4);
+        assert_eq!(table.push::<Ascii>("").unwrap(), 8);
+        assert_eq!(table.as_bytes(), b"abc\0def\0\0");
+        assert_eq!(table.get::<Ascii>(4).unwrap(), "def");
+        assert_eq!(table.get::<Ascii>(8).unwrap(), "");
+    }
+
+    #[test]
+    fn push_deduplicate() {
+        let mut table = StringTable::default();
+        assert_eq!(table.push::<Ascii>("abc").unwrap(), 0);
+        assert_eq!(table.push::<Ascii>("def").unwrap(), 4);
+        assert_eq!(table.push::<Ascii>("abc").unwrap(), 0);
+        assert_eq!(table.len(), 8);
+    }
+
+    #[test]
+    fn push_unrepresentable() {
+        let mut table = StringTable::default();
+        assert!(table.push::<Ascii>("abc\u{ff}").is_err());
+        assert!(table.is_empty());
+    }
+}
@@ -66,6 +66,71 @@ pub trait Writer: Write {
         Ok(())
     }
 
+    #[track_caller]
+    #[inline]
+    fn li32(&mut self, value: i32) -> Result<()> {
+        self.write_buffer_tracked(&value.to_le_bytes(), Location::caller())
+    }
+
+    #[track_caller]
+    #[inline]
+    fn bi32(&mut self, value: i32) -> Result<()> {
+        self.write_buffer_tracked(&value.to_be_bytes(), Location::caller())
+    }
+
+    #[track_caller]
+    #[inline]
+    fn lf32(&mut self, value: f32) -> Result<()> {
+        self.write_buffer_tracked(&value.to_le_bytes(), Location::caller())
+    }
+
+    #[track_caller]
+    #[inline]
+    fn bf32(&mut self, value: f32) -> Result<()> {
+        self.write_buffer_tracked(&value.to_be_bytes(), Location::caller())
+    }
+
+    #[track_caller]
+    #[inline]
+    fn lu64(&mut self, value: u64) -> Result<()> {
+        self.write_buffer_tracked(&value.to_le_bytes(), Location::caller())
+    }
+
+    #[track_caller]
+    #[inline]
+    fn bu64(&mut self, value: u64) -> Result<()> {
+        self.write_buffer_tracked(&value.to_be_bytes(), Location::caller())
+    }
+
+    #[inline]
+    fn bu64_array(&mut self, value: &[u64]) -> Result<()> {
+        for value in value {
+            self.bu64(*value)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn lu64_array(&mut self, value: &[u64]) -> Result<()> {
+        for value in value {
+            self.lu64(*value)?;
+        }
+        Ok(())
+    }
+
+    /// Write `count` zero bytes, e.g., for padding.
+    #[track_caller]
+    fn zeros(&mut self, count: usize) -> Result<()> {
+        let buffer = [0u8; 0x400];
+        let mut remaining = count;
+        while remaining > 0 {
+            let size = remaining.min(buffer.len());
+            self.write_buffer_tracked(&buffer[..size], Location::caller())?;
+            remaining -= size;
+        }
+        Ok(())
+    }
+
     #[track_caller]
     fn str<const L: usize, E: ParseStringEncoding>(&mut self, data: &str) -> Result<()> {
         let mut buffer = [0u8; L];
@@ -76,3 +141,55 @@ pub trait Writer: Write {
 
 impl<Base: Write> Writer for Base {}
 
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u64() {
+        let mut buffer = Vec::new();
+        buffer.bu64(0x0102030405060708).unwrap();
+        buffer.lu64(0x0102030405060708).unwrap();
+        assert_eq!(buffer, [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03,
+            0x02, 0x01,
+        ]);
+    }
+
+    #[test]
+    fn u64_array() {
+        let mut buffer = Vec::new();
+        buffer.bu64_array(&[1, 2]).unwrap();
+        buffer.lu64_array(&[3]).unwrap();
+        assert_eq!(buffer, [
+            0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 3, 0, 0, 0, 0, 0, 0, 0
+        ]);
+    }
+
+    #[test]
+    fn signed_and_float() {
+        let mut buffer = Vec::new();
+        buffer.bi32(-2).unwrap();
+        buffer.li32(-2).unwrap();
+        buffer.bf32(1.0).unwrap();
+        buffer.lf32(1.0).unwrap();
+        assert_eq!(buffer, [
+            0xFF, 0xFF, 0xFF, 0xFE, 0xFE, 0xFF, 0xFF, 0xFF, 0x3F, 0x80, 0x00, 0x00, 0x00, 0x00,
+            0x80, 0x3F,
+        ]);
+    }
+
+    #[test]
+    fn zeros() {
+        let mut buffer = vec![1];
+        buffer.zeros(0).unwrap();
+        assert_eq!(buffer, [1]);
+        buffer.zeros(0x801).unwrap();
+        assert_eq!(buffer.len(), 0x802);
+        assert!(buffer[1..].iter().all(|x| *x == 0));
+    }
+}
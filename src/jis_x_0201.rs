@@ -12,13 +12,14 @@
 //! [JIS X 0201][`JisX0201`] is encoding that [Shift
 //! JIS][`crate::ShiftJis1997`] is based upon.
 
-use std::borrow::Borrow;
-use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
 
 use crate::error::DecodingProblem::*;
-use crate::helper::{ParseStringEncoding, ProblemLocation, Parser};
+use crate::error::EncodingProblem;
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
 use crate::Result;
 
 /// [`JisX0201`] encoding.
@@ -30,8 +31,9 @@ where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter:    <I as IntoIterator>::IntoIter,
-    _marker: PhantomData<&'x ()>,
+    iter:     <I as IntoIterator>::IntoIter,
+    position: usize,
+    _marker:  PhantomData<&'x ()>,
 }
 
 impl<I> Decoder<'_, I>
@@ -41,11 +43,19 @@ where
 {
     fn new<'x>(iter: I) -> Decoder<'x, I> {
         Decoder {
-            iter:    iter.into_iter(),
-            _marker: PhantomData,
+            iter:     iter.into_iter(),
+            position: 0,
+            _marker:  PhantomData,
         }
     }
 
+    /// Decode lossily. Instead of yielding an error, invalid bytes are
+    /// replaced with `U+FFFD` (replacement character) and decoding continues
+    /// with the next byte.
+    pub fn lossy(self) -> core::iter::Map<Self, fn(Result<char>) -> char> {
+        self.map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
     /// Decode a single byte.
     pub fn decode_byte(byte: u8) -> Option<char> {
         match byte {
@@ -73,10 +83,12 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(byte) = self.iter.next() {
-            let byte = byte.borrow();
-            Some(match Self::decode_byte(*byte) {
+            let byte = *byte.borrow();
+            let index = self.position;
+            self.position += 1;
+            Some(match Self::decode_byte(byte) {
                 Some(c) => Ok(c),
-                None => Err(InvalidByte(*byte, Location::current()).into()),
+                None => Err(InvalidByte(byte, index, Location::current()).into()),
             })
         } else {
             None
@@ -85,6 +97,22 @@ where
 }
 
 impl JisX0201 {
+    /// Encode a single character. Returns `None` if the character can't be
+    /// represented in [JIS X 0201][`JisX0201`].
+    pub fn encode_char(c: char) -> Option<u8> {
+        match c {
+            // Modified ASCII character
+            '\u{00a5}' => Some(0x5c),
+            '\u{203e}' => Some(0x7e),
+            '\u{005c}' | '\u{007e}' => None,
+            // Unaltered ASCII character
+            '\u{0000}'..='\u{007f}' => Some(c as u8),
+            // Single-byte half-width katakana
+            '\u{ff61}'..='\u{ff9f}' => Some((c as u32 - 0xFF61) as u8 + 0xa1),
+            _ => None,
+        }
+    }
+
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
     pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
@@ -148,20 +176,31 @@ impl ParseStringEncoding for JisX0201 {
         Self::first(iter)
     }
 
-    fn write_str(_data: &str, _buffer: &mut [u8]) -> Result<usize> {
-        todo!("JisX0201::write_str")
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for c in data.chars() {
+            if i >= buffer.len() {
+                return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+            }
+            let Some(byte) = Self::encode_char(c) else {
+                return Err(EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into());
+            };
+            buffer[i] = byte;
+            i += 1;
+        }
+        Ok(i)
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
-        let buffer = BufReader::new(reader);
-        let iter = buffer
-            .bytes()
-            .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
-        Self::first(iter)
+        Self::first(parser_bytes(reader))
     }
 }
 
@@ -171,11 +210,33 @@ impl ParseStringEncoding for JisX0201 {
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
+
     use super::*;
 
     #[test]
     fn parse_str() {
         let data = b"abc\0def";
-        assert_eq!(JisX0201::parse_str(data).unwrap(), "abc".to_string());
+        assert_eq!(JisX0201::parse_str(data).unwrap(), "abc");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 8];
+        let length = JisX0201::write_str("a\u{a5}\u{203e}\u{ff61}\u{ff9f}", &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], [0x61, 0x5c, 0x7e, 0xa1, 0xdf]);
+        assert!(JisX0201::write_str("\\", &mut buffer).is_err());
+        assert!(JisX0201::write_str("~", &mut buffer).is_err());
+        assert!(JisX0201::write_str("\u{3042}", &mut buffer).is_err());
+        assert!(JisX0201::write_str("abc", &mut buffer[..2]).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buffer = [0u8; 0x100];
+        let bytes = (0x00..=0x7f).chain(0xa1..=0xdf).collect::<Vec<u8>>();
+        let string = JisX0201::all(&bytes).unwrap();
+        let length = JisX0201::write_str(&string, &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], bytes.as_slice());
     }
 }
@@ -0,0 +1,178 @@
+//! [Latin-1][`Latin1`] encoding.
+//!
+//! [Latin-1][`Latin1`] (ISO 8859-1) is a single-byte encoding that extends
+//! [ASCII][`crate::Ascii`] with characters used by most Western European
+//! languages. Every byte maps directly to the Unicode code point with the same
+//! value, i.e., `U+0000` to `U+00FF`, so decoding never fails. This is useful
+//! for strings in PAL region discs that would otherwise be rejected by
+//! [ASCII][`crate::Ascii`].
+
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+
+use crate::error::EncodingProblem;
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
+use crate::Result;
+
+/// [Latin-1][`Latin1`] encoding.
+pub struct Latin1 {}
+
+/// A iterator decoder for the [`Latin1`] encoding.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:    <I as IntoIterator>::IntoIter,
+    _marker: PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I) -> Decoder<'x, I> {
+        Decoder {
+            iter:    iter.into_iter(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Decode a single byte.
+    pub fn decode_byte(byte: u8) -> char { byte as char }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|byte| Ok(Self::decode_byte(*byte.borrow())))
+    }
+}
+
+impl Latin1 {
+    /// Create an iterator that decodes the given iterator of bytes into
+    /// characters.
+    pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Decoder::new(iter)
+    }
+
+    /// Decode all bytes into a string. Will continue passed NULL bytes and only
+    /// stop at the end of the iterator.
+    pub fn all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).collect()
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator.
+    pub fn first<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+}
+
+/// Extension trait for iterators of bytes and adds the helper function
+/// [`IteratorExt::latin1`] for decoding as [Latin-1][`Latin1`] strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [Latin-1][`Latin1`].
+    fn latin1<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+impl ParseStringEncoding for Latin1 {
+    fn parse_str<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::first(iter)
+    }
+
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for c in data.chars() {
+            if i >= buffer.len() {
+                return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+            }
+            if c as u32 > 0xff {
+                return Err(
+                    EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into(),
+                );
+            }
+            buffer[i] = c as u8;
+            i += 1;
+        }
+        Ok(i)
+    }
+
+    fn from_binary(reader: &mut impl Parser) -> Result<String> {
+        Self::first(parser_bytes(reader))
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_str() {
+        let data = b"caf\xe9\0def";
+        assert_eq!(Latin1::parse_str(data).unwrap(), "café");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 4];
+        assert_eq!(Latin1::write_str("café", &mut buffer).unwrap(), 4);
+        assert_eq!(&buffer, b"caf\xe9");
+        assert!(Latin1::write_str("\u{100}", &mut buffer).is_err());
+        assert!(Latin1::write_str("abcde", &mut buffer).is_err());
+    }
+}
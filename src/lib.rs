@@ -18,12 +18,15 @@
 //! ```no_run
 //! # use std::fs::File;
 //! # use picori::Result;
+//! # #[cfg(feature = "std")]
 //! fn main() -> Result<()> {
 //!     let mut file = File::open("main.dol")?;
 //!     let dol = picori::Dol::from_binary(&mut file)?;
 //!     println!("entry point: {:#08x}", dol.entry_point());
 //!     Ok(())
 //! }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
 //! ```
 //!
 //! # Examples
@@ -42,48 +45,136 @@
 //! GameCube and Wii games.
 //!
 //! * [DOL][crate::dol] - Dolphin executable
+//! * [ELF][crate::elf] - Executable and Linkable Format (PowerPC)
 //! * [REL][crate::rel] - Relocatable module
 //! * [GCM][crate::gcm] - GameCube master disc
 //! * [CISO][crate::ciso] - Compact ISO
+//! * [U8][crate::u8_archive] - U8 archive
+//! * [BMG][crate::bmg] - Message files
+//! * [BNR][crate::bnr] - Disc banner
+//! * [THP][crate::thp] - THP video container
+//! * [Texture][crate::texture] - GX texture decoding
 //! * [Yaz0][crate::yaz0] - Yaz0 compression
+//! * [Yay0][crate::yay0] - Yay0 decompression
+//! * [Compression][crate::compression] - Transparent Yaz0 and Yay0 decompression
+//! * [Format detection][crate::format] - Detect the format of a file
+//! * [ASCII][crate::ascii] - ASCII encoding
 //! * [JIS X 0201][crate::jis_x_0201] - JIS X 0201 encoding
 //! * [Shift JIS 1997][crate::shift_jis_1997] - Shift JIS 1997 encoding
 //! * [Shift JIS 2004][crate::shift_jis_2004] - Shift JIS 2004 encoding
+//! * [EUC-JP][crate::euc_jp] - EUC-JP encoding
+//! * [Latin-1][crate::latin1] - Latin-1 (ISO 8859-1) encoding
+//! * [UTF-16][crate::utf16] - UTF-16 (big and little endian) encoding
+//! * [Windows-1252][crate::windows_1252] - Windows-1252 (CP1252) encoding
+//!
+//! # Cargo features
+//!
+//! * `std` (default) - File formats and the [`Parser`] implementations for
+//!   `std::io` types. Without it, the crate is `no_std` (with `alloc`) and
+//!   only the text encodings, [`SliceParser`] and [texture
+//!   decoding][crate::texture] are available.
+//! * `mmap` - `MmapReader`, a reader over a memory-mapped file, using the
+//!   `memmap2` crate. Implies `std`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(missing_docs)]
 #![warn(unused_imports)]
 
+extern crate alloc;
+
 pub mod ascii;
+#[cfg(feature = "std")]
+pub mod bmg;
+#[cfg(feature = "std")]
+pub mod bnr;
+#[cfg(feature = "std")]
 pub mod ciso;
+#[cfg(feature = "std")]
+pub mod compression;
+#[cfg(feature = "std")]
 pub mod dol;
+#[cfg(feature = "std")]
+pub mod elf;
+pub mod euc_jp;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
 pub mod gcm;
 pub mod jis_x_0201;
+pub mod latin1;
+#[cfg(feature = "std")]
 pub mod rarc;
+#[cfg(feature = "std")]
 pub mod rel;
 pub mod shift_jis_1997;
 pub mod shift_jis_2004;
+pub mod texture;
+#[cfg(feature = "std")]
+pub mod thp;
+#[cfg(feature = "std")]
+pub mod u8_archive;
+pub mod utf16;
+pub mod windows_1252;
+#[cfg(feature = "std")]
+pub mod yay0;
+#[cfg(feature = "std")]
 pub mod yaz0;
 
 #[doc(inline)]
 pub use ascii::{Ascii, IteratorExt as AsciiIteratorExt};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use bmg::Bmg;
+#[cfg(feature = "std")]
 #[doc(inline)]
-pub use ciso::CisoReader;
+pub use bnr::Bnr;
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use ciso::{CisoBuilder, CisoReader};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use compression::AutoDecompressReader;
+#[cfg(feature = "std")]
 #[doc(inline)]
 pub use dol::Dol;
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use elf::Elf;
 #[doc(inline)]
-pub use gcm::Gcm;
+pub use euc_jp::{EucJp, IteratorExt as EucJpIteratorExt};
+#[cfg(feature = "std")]
 #[doc(inline)]
-pub use helper::{Error, Result};
+pub use format::{detect_format, Format};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use gcm::{Gcm, GcmReader};
+#[doc(inline)]
+pub use helper::{Error, Result, ResultExt};
 #[doc(inline)]
 pub use jis_x_0201::{IteratorExt as JisX0201IteratorExt, JisX0201};
 #[doc(inline)]
-pub use rarc::RarcReader;
+pub use latin1::{IteratorExt as Latin1IteratorExt, Latin1};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use rarc::{RarcReader, RarcWriter};
+#[cfg(feature = "std")]
 #[doc(inline)]
 pub use rel::Rel;
 #[doc(inline)]
 pub use shift_jis_1997::{IteratorExt as ShiftJis1997IteratorExt, ShiftJis1997};
 #[doc(inline)]
 pub use shift_jis_2004::{IteratorExt as ShiftJis2004IteratorExt, ShiftJis2004};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use thp::Thp;
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use u8_archive::U8Reader;
+#[doc(inline)]
+pub use utf16::{IteratorExt as Utf16IteratorExt, Utf16Be, Utf16Le};
+#[doc(inline)]
+pub use windows_1252::{IteratorExt as Windows1252IteratorExt, Windows1252};
+#[cfg(feature = "std")]
 #[doc(inline)]
 pub use yaz0::Yaz0Reader;
 
@@ -98,7 +189,15 @@ pub mod error {
     };
 }
 
+#[cfg(feature = "mmap")]
+pub use helper::MmapReader;
 pub use helper::Seeker;
+pub use helper::SliceParser;
+#[cfg(feature = "std")]
+pub use helper::StringTable;
 pub use helper::Parser;
+pub use helper::Pod;
+pub use helper::{BigEndian, Endian, EndianAgnostic, LittleEndian, NativeEndian};
 pub use helper::Reader;
+#[cfg(feature = "std")]
 pub use helper::Writer;
@@ -1,8 +1,9 @@
-use crate::error::ParseProblem;
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::alignment::{self, AlignPowerOfTwo};
 use crate::helper::ProblemLocation;
-use crate::helper::{ensure, Parser, Seeker};
+use crate::helper::{ensure, Parser, Seeker, SliceParser, StringTable, Writer};
 use crate::{Ascii, Result};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::panic::Location;
 
@@ -12,6 +13,16 @@ pub struct NamedHash {
     pub hash: u16,
 }
 
+impl NamedHash {
+    /// Create a [`NamedHash`] for `name`, the hash is computed with
+    /// [`name_hash`].
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let hash = name_hash(&name);
+        Self { name, hash }
+    }
+}
+
 impl Display for NamedHash {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name)
@@ -49,6 +60,8 @@ enum RarcDirectory {
     Folder {
         /// Name of the folder.
         name: NamedHash,
+        /// Index of the node with the folder contents.
+        node: u32,
     },
     CurrentFolder,
     ParentFolder,
@@ -56,6 +69,8 @@ enum RarcDirectory {
 
 #[derive(Debug, Clone)]
 struct RarcNode {
+    /// Name of the node.
+    pub name: NamedHash,
     /// Index of first directory.
     pub index: u32,
     /// Number of directories.
@@ -65,20 +80,37 @@ struct RarcNode {
 pub struct RarcReader<Reader> {
     reader: Reader,
     directories: Vec<RarcDirectory>,
-    nodes: HashMap<NamedHash, RarcNode>,
-    root_node: NamedHash,
+    /// Nodes in the order they are stored, the first node is the root.
+    nodes: Vec<RarcNode>,
+    /// Absolute range of the file data in the reader.
+    data_range: std::ops::Range<u64>,
+}
+
+impl<'data> RarcReader<SliceParser<'data>> {
+    /// Create a new RARC reader over an in-memory archive, see
+    /// [`RarcReader::new`].
+    pub fn from_bytes(data: &'data [u8]) -> Result<Self> { Self::new(SliceParser::new(data)) }
 }
 
 impl<Reader: Parser + Seeker> RarcReader<Reader> {
     /// Creates a new RARC reader.
+    ///
+    /// There are two known conventions for what the file data offset in the
+    /// header is relative to: the end of the header (used by most archives)
+    /// or the start of the archive. The end of the header is assumed, unless
+    /// that places file data outside of the archive while the start of the
+    /// archive doesn't.
+    ///
+    /// The file data length from the header is checked against the length of
+    /// the reader, a truncated archive returns [`ParseProblem::InvalidRange`].
     pub fn new(mut reader: Reader) -> Result<Self> {
-        let base = reader.position()?;
+        let start = reader.position()?;
 
         let magic = reader.u32()?;
-        let _file_length = reader.bu32()?;
+        let archive_length = reader.bu32()?;
         let header_length = reader.bu32()?;
         let file_offset = reader.bu32()?;
-        let _file_length = reader.bu32()?;
+        let file_length = reader.bu32()?;
         let _ = reader.bu32()?;
         let _ = reader.bu32()?;
         let _ = reader.bu32()?;
@@ -107,9 +139,12 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
             ParseProblem::InvalidHeader("invalid directory count", Location::current())
         );
 
-        let base = base + header_length as u64;
+        let base = start + header_length as u64;
+        reader.goto(base + string_table_offset as u64)?;
+        let string_table = reader.read_as_vec_max(string_table_length as usize, 0x1000000)?;
+        let string_table = StringTable::new(string_table);
+
         let directory_base = base + directory_offset as u64;
-        let data_base = base + file_offset as u64;
         let mut directories = Vec::with_capacity(directory_count as usize);
         for i in 0..directory_count {
             reader.goto(directory_base + 20 * i as u64)?;
@@ -121,19 +156,7 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
             let data_length = reader.bu32()?;
             let _ = reader.bu32()?;
 
-            let name = {
-                let offset = string_table_offset as u64;
-                let offset = offset + name_offset as u64;
-                ensure!(
-                    (name_offset as u32) < string_table_length,
-                    ParseProblem::InvalidData(
-                        "invalid string table offset",
-                        std::panic::Location::current()
-                    )
-                );
-                reader.goto(base + offset)?;
-                reader.str::<Ascii>()
-            }?;
+            let name = string_table.get::<Ascii>(name_offset as u32)?;
 
             if index == 0xFFFF {
                 if name == "." {
@@ -146,6 +169,7 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
                             name,
                             hash: name_hash,
                         },
+                        node: data_offset,
                     });
                 }
             } else {
@@ -154,15 +178,46 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
                         name,
                         hash: name_hash,
                     },
-                    offset: data_base + data_offset as u64,
+                    offset: data_offset as u64,
                     size: data_length,
                 });
             }
         }
 
+        let data_end = directories
+            .iter()
+            .filter_map(|x| match x {
+                RarcDirectory::File { offset, size, .. } => Some(offset + *size as u64),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let archive_end = start + archive_length as u64;
+        let header_end_base = base + file_offset as u64;
+        let archive_start_base = start + file_offset as u64;
+        let data_base = if header_end_base + data_end > archive_end
+            && archive_start_base + data_end <= archive_end
+        {
+            archive_start_base
+        } else {
+            header_end_base
+        };
+
+        let stream_end = reader.seek(std::io::SeekFrom::End(0))?;
+        let data_range = data_base..data_base + file_length as u64;
+        ensure!(
+            data_range.end <= stream_end,
+            ParseProblem::InvalidRange("file data exceeds archive length", Location::current())
+        );
+
+        for directory in directories.iter_mut() {
+            if let RarcDirectory::File { offset, .. } = directory {
+                *offset += data_base;
+            }
+        }
+
         let node_base = base + node_offset as u64;
-        let mut root_node: Option<NamedHash> = None;
-        let mut nodes = HashMap::with_capacity(node_count as usize);
+        let mut nodes = Vec::with_capacity(node_count as usize);
         for i in 0..node_count {
             reader.goto(node_base + 16 * i as u64)?;
             let _identifier = reader.bu32()?;
@@ -188,61 +243,125 @@ impl<Reader: Parser + Seeker> RarcReader<Reader> {
                 )
             );
 
-            let name = {
-                let offset = string_table_offset as u64;
-                let offset = offset + name_offset as u64;
-                ensure!(
-                    (name_offset as u32) < string_table_length,
-                    ParseProblem::InvalidData(
-                        "invalid string table offset",
-                        std::panic::Location::current()
-                    )
-                );
-                reader.goto(base + offset)?;
-                reader.str::<Ascii>()
-            }?;
-
-            // FIXME: this assumes that the root node is the first node in the list
-            if root_node.is_none() {
-                root_node = Some(NamedHash {
-                    name: name.clone(),
-                    hash: name_hash,
-                });
-            }
-
             let name = NamedHash {
-                name,
+                name: string_table.get::<Ascii>(name_offset)?,
                 hash: name_hash,
             };
-            nodes.insert(name.clone(), RarcNode { index, count });
+            nodes.push(RarcNode { name, index, count });
         }
 
-        if let Some(root_node) = root_node {
-            Ok(Self {
-                reader,
-                directories,
-                nodes,
-                root_node,
-            })
-        } else {
-            Err(ParseProblem::InvalidData("no root node", std::panic::Location::current()).into())
-        }
+        // FIXME: this assumes that the root node is the first node in the list
+        ensure!(
+            !nodes.is_empty(),
+            ParseProblem::InvalidData("no root node", std::panic::Location::current())
+        );
+
+        ensure!(
+            directories.iter().all(|x| match x {
+                RarcDirectory::Folder { node, .. } => *node < node_count,
+                _ => true,
+            }),
+            ParseProblem::InvalidData("folder node index out of bounds", Location::current())
+        );
+
+        Ok(Self {
+            reader,
+            directories,
+            nodes,
+            data_range,
+        })
     }
 
-    /// Get the data for a file.
+    /// Get the data for a file. Returns [`ParseProblem::InvalidRange`] if the
+    /// file is not within the file data of the archive.
     pub fn file_data(&mut self, offset: u64, size: u32) -> Result<Vec<u8>> {
+        ensure!(
+            offset >= self.data_range.start && offset + size as u64 <= self.data_range.end,
+            ParseProblem::InvalidRange("file outside of archive data", Location::current())
+        );
+
         self.reader.goto(offset)?;
         let mut result = vec![0; size as usize];
-        self.reader.read_exact(result.as_mut_slice())?;
+        self.reader.read_exact_into(result.as_mut_slice())?;
         Ok(result)
     }
 
+    /// Read the data of the file at the slash-separated `path`, relative to
+    /// the root folder, e.g. `"dzb/room.dzb"`. Returns
+    /// [`ParseProblem::InvalidData`] if the path doesn't resolve to a file.
+    pub fn read_path(&mut self, path: &str) -> Result<Vec<u8>> {
+        match self.lookup(path) {
+            Some(RarcDirectory::File { offset, size, .. }) => {
+                let (offset, size) = (*offset, *size);
+                self.file_data(offset, size)
+            },
+            _ => Err(ParseProblem::InvalidData(
+                "path does not resolve to a file",
+                Location::current(),
+            )
+            .into()),
+        }
+    }
+
+    /// Returns `true` if the slash-separated `path`, relative to the root
+    /// folder, resolves to a file or folder.
+    pub fn exists(&self, path: &str) -> bool { self.lookup(path).is_some() }
+
+    /// Resolve `path` to its directory entry, starting at the root node.
+    fn lookup(&self, path: &str) -> Option<&RarcDirectory> {
+        let mut node = self.nodes.first()?;
+        let mut components = path.trim_start_matches('/').split('/').peekable();
+        while let Some(component) = components.next() {
+            let start = node.index as usize;
+            let directory = self.directories[start..start + node.count as usize]
+                .iter()
+                .find(|x| match x {
+                    RarcDirectory::File { name, .. } | RarcDirectory::Folder { name, .. } => {
+                        name.name == component
+                    },
+                    _ => false,
+                })?;
+
+            if components.peek().is_none() {
+                return Some(directory);
+            }
+
+            match directory {
+                RarcDirectory::Folder { node: index, .. } => node = &self.nodes[*index as usize],
+                _ => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Verify that the stored hash of every file, folder and node name matches
+    /// [`name_hash`]. This is not done by [`RarcReader::new`], as archives
+    /// with wrong hashes can still be read. Returns
+    /// [`ParseProblem::InvalidData`] on the first mismatch.
+    pub fn verify_hashes(&self) -> Result<()> {
+        let names = self
+            .directories
+            .iter()
+            .filter_map(|x| match x {
+                RarcDirectory::File { name, .. } | RarcDirectory::Folder { name, .. } => Some(name),
+                _ => None,
+            })
+            .chain(self.nodes.iter().map(|x| &x.name));
+        for name in names {
+            ensure!(
+                name.hash == name_hash(&name.name),
+                ParseProblem::InvalidData("name hash mismatch", Location::current())
+            );
+        }
+        Ok(())
+    }
+
     /// Get a iterator over the nodes in the RARC file.
     pub fn nodes(&self) -> Nodes<'_, Reader> {
-        let root_node = self.root_node.clone();
         Nodes {
             parent: self,
-            stack: vec![NodeState::Begin(root_node)],
+            stack: vec![NodeState::Begin(0)],
         }
     }
 }
@@ -265,10 +384,11 @@ pub enum Node {
     ParentDirectory,
 }
 
+/// Iterator state, nodes are referred to by index.
 enum NodeState {
-    Begin(NamedHash),
-    End(NamedHash),
-    File(NamedHash, u32),
+    Begin(u32),
+    End(u32),
+    File(u32, u32),
 }
 
 /// An iterator over the nodes in an RARC file.
@@ -281,29 +401,31 @@ impl<'parent, T: Parser + Seeker> Iterator for Nodes<'parent, T> {
     type Item = Node;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Some(state) = self.stack.pop() else {
-            return None;
-        };
+        let state = self.stack.pop()?;
 
         match state {
-            NodeState::Begin(name) => {
-                self.stack.push(NodeState::File(name.clone(), 0));
+            NodeState::Begin(node) => {
+                self.stack.push(NodeState::File(node, 0));
+                let name = self.parent.nodes[node as usize].name.clone();
                 Some(Node::DirectoryBegin { name })
             },
-            NodeState::End(name) => Some(Node::DirectoryEnd { name }),
-            NodeState::File(name, index) => {
-                if let Some(node) = self.parent.nodes.get(&name) {
+            NodeState::End(node) => {
+                let name = self.parent.nodes[node as usize].name.clone();
+                Some(Node::DirectoryEnd { name })
+            },
+            NodeState::File(node_index, index) => {
+                if let Some(node) = self.parent.nodes.get(node_index as usize) {
                     if index + 1 >= node.count {
-                        self.stack.push(NodeState::End(name.clone()));
+                        self.stack.push(NodeState::End(node_index));
                     } else {
-                        self.stack.push(NodeState::File(name.clone(), index + 1));
+                        self.stack.push(NodeState::File(node_index, index + 1));
                     }
                     let directory = &self.parent.directories[(node.index + index) as usize];
                     match directory {
                         RarcDirectory::CurrentFolder => Some(Node::CurrentDirectory),
                         RarcDirectory::ParentFolder => Some(Node::ParentDirectory),
-                        RarcDirectory::Folder { name } => {
-                            self.stack.push(NodeState::Begin(name.clone()));
+                        RarcDirectory::Folder { node, .. } => {
+                            self.stack.push(NodeState::Begin(*node));
                             self.next()
                         },
                         RarcDirectory::File { name, offset, size } => Some(Node::File {
@@ -319,3 +441,240 @@ impl<'parent, T: Parser + Seeker> Iterator for Nodes<'parent, T> {
         }
     }
 }
+
+/// Hash of a file or folder name as stored in the RARC directory and node
+/// tables. Each byte of the name is accumulated as `hash = hash * 3 + byte`,
+/// wrapping at 16 bits.
+pub fn name_hash(name: &str) -> u16 {
+    name.bytes()
+        .fold(0_u16, |hash, x| hash.wrapping_mul(3).wrapping_add(x as u16))
+}
+
+/// Check if the given data is a [RARC][`crate::rarc`] archive by looking for
+/// the `RARC` magic. The stream position is restored.
+pub fn is_rarc<D: Parser + Seeker>(input: &mut D) -> bool {
+    let Ok(base) = input.position() else {
+        return false;
+    };
+
+    let is_rarc = input.u8_array::<4>().map(|x| &x == b"RARC").unwrap_or(false);
+    input.goto(base).is_ok() && is_rarc
+}
+
+/// Alignment of the sections and file data.
+const ALIGNMENT: u32 = 0x20;
+
+/// Pad `data` with zeros to the next multiple of [`ALIGNMENT`].
+fn pad(data: &mut Vec<u8>) {
+    let padding = alignment::padding(data.len() as u64, ALIGNMENT as u64);
+    data.resize(data.len() + padding as usize, 0);
+}
+
+/// Folder tree used by [`RarcWriter`], children are sorted by name.
+#[derive(Default)]
+struct WriterFolder {
+    children: BTreeMap<String, WriterNode>,
+}
+
+enum WriterNode {
+    File(usize),
+    Folder(WriterFolder),
+}
+
+impl WriterFolder {
+    #[track_caller]
+    fn insert(&mut self, path: &str, file: usize) -> Result<()> {
+        let (name, rest) = match path.split_once('/') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+        ensure!(
+            !name.is_empty() && name != "." && name != "..",
+            BuildProblem::InvalidData("invalid path component", Location::caller())
+        );
+
+        match rest {
+            Some(rest) => {
+                let node = self
+                    .children
+                    .entry(name.to_string())
+                    .or_insert_with(|| WriterNode::Folder(WriterFolder::default()));
+                match node {
+                    WriterNode::Folder(folder) => folder.insert(rest, file),
+                    WriterNode::File(_) => Err(BuildProblem::InvalidData(
+                        "path is both a file and a folder",
+                        Location::caller(),
+                    )
+                    .into()),
+                }
+            },
+            None => {
+                ensure!(
+                    !self.children.contains_key(name),
+                    BuildProblem::InvalidData("duplicate path", Location::caller())
+                );
+                self.children.insert(name.to_string(), WriterNode::File(file));
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Builder for RARC archives.
+///
+/// The archive is laid out as follows: the header, the node table, the
+/// directory table and the string table (each aligned to `0x20` bytes), and
+/// then the file data (each file aligned to `0x20` bytes). Every folder gets
+/// a node, its directory entries are the files and sub-folders sorted by name
+/// followed by `.` and `..`.
+pub struct RarcWriter {
+    root:  String,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl RarcWriter {
+    /// Create a new [`RarcWriter`] without any files. `root` is the name of
+    /// the root folder.
+    pub fn new(root: impl Into<String>) -> Self {
+        Self {
+            root:  root.into(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Add a file at the slash-separated `path`, e.g. `"dzb/room.dzb"`.
+    /// Folders are created implicitly.
+    pub fn add_file(&mut self, path: impl Into<String>, data: Vec<u8>) -> &mut Self {
+        self.files.push((path.into(), data));
+        self
+    }
+
+    /// Build the archive.
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let mut root = WriterFolder::default();
+        for (index, (path, _)) in self.files.iter().enumerate() {
+            root.insert(path.trim_start_matches('/'), index)?;
+        }
+
+        let mut strings = StringTable::default();
+        strings.push::<Ascii>(".")?;
+        strings.push::<Ascii>("..")?;
+
+        // (identifier, name offset, hash, count, first directory)
+        let mut nodes = Vec::new();
+        // (index, hash, kind, name offset, data offset, data size)
+        let mut directories = Vec::new();
+        let mut data = Vec::new();
+
+        // nodes are numbered breadth-first, the entries of each node are
+        // written before the next node is visited
+        let mut queue = std::collections::VecDeque::from([(&self.root, &root, 0xFFFF_FFFF_u32)]);
+        while let Some((name, folder, parent)) = queue.pop_front() {
+            let index = nodes.len() as u32;
+            let first = directories.len() as u32;
+            let name_offset = strings.push::<Ascii>(name)?;
+            for (name, node) in folder.children.iter() {
+                let name_offset = strings.push::<Ascii>(name)?;
+                match node {
+                    WriterNode::File(file) => {
+                        pad(&mut data);
+                        let file = &self.files[*file].1;
+                        let entry = directories.len() as u16;
+                        let hash = name_hash(name);
+                        let offset = data.len() as u32;
+                        let size = file.len() as u32;
+                        directories.push((entry, hash, 0x1100, name_offset, offset, size));
+                        data.extend_from_slice(file);
+                    },
+                    WriterNode::Folder(folder) => {
+                        let node = (nodes.len() + queue.len() + 1) as u32;
+                        let hash = name_hash(name);
+                        directories.push((0xFFFF, hash, 0x0200, name_offset, node, 0x10));
+                        queue.push_back((name, folder, index));
+                    },
+                }
+            }
+            directories.push((0xFFFF, name_hash("."), 0x0200, 0, index, 0x10));
+            directories.push((0xFFFF, name_hash(".."), 0x0200, 2, parent, 0x10));
+
+            let mut identifier = *b"    ";
+            if index == 0 {
+                identifier = *b"ROOT";
+            } else {
+                let upper = name.to_ascii_uppercase();
+                for (a, b) in identifier.iter_mut().zip(upper.bytes()) {
+                    *a = b;
+                }
+            }
+            let count = directories.len() as u32 - first;
+            nodes.push((identifier, name_offset, name_hash(name), count as u16, first));
+        }
+        pad(&mut data);
+
+        ensure!(
+            strings.len() <= 0x10000,
+            BuildProblem::InvalidRange("string table too large", Location::current())
+        );
+        ensure!(
+            directories.len() < 0xFFFF,
+            BuildProblem::InvalidRange("too many directory entries", Location::current())
+        );
+
+        let node_offset = 0x20;
+        let directory_offset = (node_offset + nodes.len() * 0x10).align_next(ALIGNMENT);
+        let string_table_offset =
+            (directory_offset + directories.len() * 0x14).align_next(ALIGNMENT);
+        let string_table_length = strings.len().align_next(ALIGNMENT);
+        let file_offset = string_table_offset + string_table_length;
+        let archive_length = 0x20 + file_offset + data.len();
+        ensure!(
+            archive_length <= u32::MAX as usize,
+            BuildProblem::InvalidRange("archive too large", Location::current())
+        );
+
+        let mut output = Vec::with_capacity(archive_length);
+        output.u8_array(b"RARC")?;
+        output.bu32(archive_length as u32)?;
+        output.bu32(0x20)?;
+        output.bu32(file_offset as u32)?;
+        output.bu32(data.len() as u32)?;
+        output.bu32(data.len() as u32)?;
+        output.bu32(0)?;
+        output.bu32(0)?;
+        output.bu32(nodes.len() as u32)?;
+        output.bu32(node_offset as u32)?;
+        output.bu32(directories.len() as u32)?;
+        output.bu32(directory_offset as u32)?;
+        output.bu32(string_table_length as u32)?;
+        output.bu32(string_table_offset as u32)?;
+        output.bu16(directories.len() as u16)?;
+        output.u8(1)?; // file ids are the directory indices
+        output.u8(0)?;
+        output.bu32(0)?;
+
+        for (identifier, name_offset, hash, count, first) in nodes {
+            output.u8_array(&identifier)?;
+            output.bu32(name_offset)?;
+            output.bu16(hash)?;
+            output.bu16(count)?;
+            output.bu32(first)?;
+        }
+
+        output.resize(0x20 + directory_offset, 0);
+        for (index, hash, kind, name_offset, offset, size) in directories {
+            output.bu16(index)?;
+            output.bu16(hash)?;
+            output.bu16(kind)?;
+            output.bu16(name_offset as u16)?;
+            output.bu32(offset)?;
+            output.bu32(size)?;
+            output.bu32(0)?;
+        }
+
+        output.resize(0x20 + string_table_offset, 0);
+        output.u8_array(strings.as_bytes())?;
+        output.resize(0x20 + file_offset, 0);
+        output.u8_array(&data)?;
+        Ok(output)
+    }
+}
@@ -19,10 +19,26 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Serialize
+//!
+//! A [`Rel`] can be written back to a binary stream with [`Rel::to_binary`].
+//! The header, the section table, the section data, the import tables and
+//! the relocation data are written in that order.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::panic::Location;
 
-use crate::error::ParseProblem;
-use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
-use crate::Result;
+use crate::error::{BuildProblem, ParseProblem};
+use crate::helper::alignment::AlignPowerOfTwo;
+use crate::helper::{
+    ensure, ParseStringEncoding, Parser, ProblemLocation, Seeker, SliceParser, Writer,
+};
+use crate::{Ascii, Error, Result};
+
+/// Maximum `.bss` size, the size of the GameCube main memory (24 MiB).
+pub const MAX_BSS_SIZE: usize = 0x180_0000;
 
 /// `.rel` file object.
 #[derive(Debug, Clone)]
@@ -51,6 +67,12 @@ pub struct Rel {
     pub alignment: u32,
     /// `.bss` section alignment.
     pub bss_alignment: u32,
+    /// `.bss` size from the header. [`Rel::to_binary`] recomputes it from the
+    /// sections without data.
+    pub bss_size: u32,
+    /// Index of the `.bss` section from the header. This is `0` in files and
+    /// set by the loader at runtime, see [`Rel::bss_section_index`].
+    pub bss_section: u8,
     /// `parse`: Unknown.
     pub fix_size: u32,
     /// `parse`: Relocation offset.
@@ -109,6 +131,96 @@ pub enum ImportKind {
     DolphinMRKREF,
 }
 
+impl ImportKind {
+    /// Returns `true` if the import is a PowerPC relocation that is applied
+    /// to the target section, i.e., a [`Relocation`] yielded by
+    /// [`Rel::relocations`]. `None` and the `Dolphin*` kinds only control the
+    /// import table and return `false`.
+    pub fn is_relocation(&self) -> bool {
+        !matches!(
+            self,
+            ImportKind::None
+                | ImportKind::DolphinNop
+                | ImportKind::DolphinSection
+                | ImportKind::DolphinEnd
+                | ImportKind::DolphinMRKREF
+        )
+    }
+}
+
+impl TryFrom<u8> for ImportKind {
+    type Error = Error;
+
+    /// Convert the import kind value stored in the `.rel` file. Returns
+    /// [`ParseProblem::InvalidData`] for unknown values.
+    #[track_caller]
+    fn try_from(value: u8) -> Result<Self> {
+        Ok(match value {
+            0 => ImportKind::None,
+            1 => ImportKind::Addr32,
+            2 => ImportKind::Addr24,
+            3 => ImportKind::Addr16,
+            4 => ImportKind::Addr16Lo,
+            5 => ImportKind::Addr16Hi,
+            6 => ImportKind::Addr16Ha,
+            7 => ImportKind::Addr14,
+            10 => ImportKind::Rel24,
+            11 => ImportKind::Rel14,
+            201 => ImportKind::DolphinNop,
+            202 => ImportKind::DolphinSection,
+            203 => ImportKind::DolphinEnd,
+            204 => ImportKind::DolphinMRKREF,
+            _ => {
+                return Err(
+                    ParseProblem::InvalidData("unknown import kind", Location::current()).into(),
+                )
+            },
+        })
+    }
+}
+
+impl From<ImportKind> for u8 {
+    fn from(kind: ImportKind) -> u8 {
+        match kind {
+            ImportKind::None => 0,
+            ImportKind::Addr32 => 1,
+            ImportKind::Addr24 => 2,
+            ImportKind::Addr16 => 3,
+            ImportKind::Addr16Lo => 4,
+            ImportKind::Addr16Hi => 5,
+            ImportKind::Addr16Ha => 6,
+            ImportKind::Addr14 => 7,
+            ImportKind::Rel24 => 10,
+            ImportKind::Rel14 => 11,
+            ImportKind::DolphinNop => 201,
+            ImportKind::DolphinSection => 202,
+            ImportKind::DolphinEnd => 203,
+            ImportKind::DolphinMRKREF => 204,
+        }
+    }
+}
+
+impl std::fmt::Display for ImportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            ImportKind::None => "None",
+            ImportKind::Addr32 => "Addr32",
+            ImportKind::Addr24 => "Addr24",
+            ImportKind::Addr16 => "Addr16",
+            ImportKind::Addr16Lo => "Addr16Lo",
+            ImportKind::Addr16Hi => "Addr16Hi",
+            ImportKind::Addr16Ha => "Addr16Ha",
+            ImportKind::Addr14 => "Addr14",
+            ImportKind::Rel24 => "Rel24",
+            ImportKind::Rel14 => "Rel14",
+            ImportKind::DolphinNop => "DolphinNop",
+            ImportKind::DolphinSection => "DolphinSection",
+            ImportKind::DolphinEnd => "DolphinEnd",
+            ImportKind::DolphinMRKREF => "DolphinMRKREF",
+        })
+    }
+}
+
 /// Import.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Import {
@@ -171,7 +283,40 @@ impl Rel {
     ///
     /// This function _should_ not panic and if any error occurs, it will return
     /// [`Err`] of type [`Error`][`crate::Error`]/[`ParseProblem`].
-    pub fn from_binary<D: Parser + Seeker>(mut reader: D) -> Result<Self> {
+    pub fn from_binary<D: Parser + Seeker>(reader: D) -> Result<Self> {
+        Self::parse(reader, true)
+    }
+
+    /// Parse [`Rel`] from a byte slice containing the whole `.rel` file, see
+    /// [`Rel::from_binary`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self> { Self::from_binary(SliceParser::new(data)) }
+
+    /// Parse [`Rel`] from binary stream without reading the section data, i.e.,
+    /// [`Section::data`] is empty for every section. Use
+    /// [`Rel::load_section`] to read the data of a section on demand.
+    pub fn from_binary_headers_only<D: Parser + Seeker>(reader: D) -> Result<Self> {
+        Self::parse(reader, false)
+    }
+
+    /// Read the data of the section at `index` into [`Section::data`]. The
+    /// `reader` must be positioned at the start of the `.rel` file, as for
+    /// [`Rel::from_binary`]. Sections without data in the file, e.g., `.bss`,
+    /// are left empty.
+    pub fn load_section<D: Parser + Seeker>(&mut self, reader: &mut D, index: usize) -> Result<()> {
+        let base = reader.position()?;
+        let Some(section) = self.sections.get_mut(index) else {
+            return Err(ParseProblem::InvalidRange(
+                "section index out of bounds",
+                std::panic::Location::current(),
+            )
+            .into());
+        };
+
+        section.data = read_section_data(reader, base, section.offset, section.size)?;
+        Ok(())
+    }
+
+    fn parse<D: Parser + Seeker>(mut reader: D, load_data: bool) -> Result<Self> {
         let base = reader.position()?;
         let module = reader.bu32()?;
         let _next = reader.bu32()?; // should be 0, used at runtime
@@ -181,14 +326,14 @@ impl Rel {
         let name_offset = reader.bu32()?;
         let name_size = reader.bu32()?;
         let version = reader.bu32()?;
-        let _bss_size = reader.bu32()?;
+        let bss_size = reader.bu32()?;
         let relocation_offset = reader.bu32()?;
         let import_offset = reader.bu32()?;
         let import_size = reader.bu32()?;
         let prolog_section = reader.u8()?;
         let epilog_section = reader.u8()?;
         let unresolved_section = reader.u8()?;
-        let _bss_section = reader.u8()?; // should be 0, used at runtime
+        let bss_section = reader.u8()?; // should be 0, used at runtime
         let prolog_offset = reader.bu32()?;
         let epilog_offset = reader.bu32()?;
         let unresolved_offset = reader.bu32()?;
@@ -229,8 +374,16 @@ impl Rel {
         let epilog = optional_symbol(epilog_section, epilog_offset);
         let unresolved = optional_symbol(unresolved_section, unresolved_offset);
 
-        let sections = parse_sections(&mut reader, base, section_offset, section_count)?;
-        let import_tables = parse_imports(&mut reader, base, import_offset, import_size)?;
+        let sections =
+            parse_sections(&mut reader, base, section_offset, section_count, load_data)?;
+        let import_tables = parse_imports(
+            &mut reader,
+            base,
+            import_offset,
+            import_size,
+            module,
+            section_count,
+        )?;
 
         Ok(Rel {
             module,
@@ -244,6 +397,8 @@ impl Rel {
             unresolved,
             alignment: align,
             bss_alignment: bss_align,
+            bss_size,
+            bss_section,
             fix_size,
             relocation_offset: Some(relocation_offset),
             import_offset: Some(import_offset),
@@ -251,8 +406,361 @@ impl Rel {
         })
     }
 
+    /// Write the [`Rel`] to a binary stream. The header fields that only exist
+    /// in newer versions are only written if [`Rel::version`] is high enough,
+    /// i.e., [`Rel::alignment`] and [`Rel::bss_alignment`] for version 2 and
+    /// later, and [`Rel::fix_size`] for version 3.
+    ///
+    /// Section data is written after the section table. [`Section::offset`] is
+    /// kept if it doesn't overlap the previous section, otherwise the section
+    /// is placed directly after the previous one, aligned to 4 bytes. Sections
+    /// without data are written with offset `0`, their size is added to the
+    /// `.bss` size. A section with a non-zero offset but without data, e.g.,
+    /// from [`Rel::from_binary_headers_only`] without [`Rel::load_section`],
+    /// fails with [`BuildProblem::InvalidData`] instead of being written as
+    /// `.bss`, as is a [`Rel::version`] later than 3. The import tables and the relocation data follow the last
+    /// section. [`Rel::relocation_offset`], [`Rel::import_offset`],
+    /// [`Rel::import_size`] and [`Rel::fix_size`] are recomputed, the fix size
+    /// is written as the offset of the relocation data.
+    pub fn to_binary<W: Writer + Seeker>(&self, output: &mut W) -> Result<()> {
+        ensure!(
+            self.version <= 3,
+            BuildProblem::InvalidData("unsupported version", std::panic::Location::current())
+        );
+        ensure!(
+            self.sections
+                .iter()
+                .all(|x| x.offset == 0 || x.size == 0 || !x.data.is_empty()),
+            BuildProblem::InvalidData("section data not loaded", std::panic::Location::current())
+        );
+
+        let header_size = match self.version {
+            0 | 1 => 0x40,
+            2 => 0x48,
+            _ => 0x4C,
+        };
+
+        let section_offset = header_size;
+        let mut offset = section_offset + self.sections.len() as u32 * 8;
+        let mut section_offsets = Vec::with_capacity(self.sections.len());
+        for section in self.sections.iter() {
+            if section.data.is_empty() {
+                section_offsets.push(0);
+            } else {
+                offset = if section.offset >= offset && section.offset % 4 == 0 {
+                    section.offset
+                } else {
+                    offset.align_next(4)
+                };
+                section_offsets.push(offset);
+                offset += section.data.len() as u32;
+            }
+        }
+
+        let bss_size = self
+            .sections
+            .iter()
+            .filter(|x| x.data.is_empty())
+            .map(|x| x.size)
+            .sum::<u32>();
+
+        let streams = self
+            .import_tables
+            .iter()
+            .map(|x| {
+                let mut stream = Vec::new();
+                x.to_binary(&mut stream)?;
+                Ok(stream)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let import_offset = offset.align_next(4);
+        let import_size = self.import_tables.len() as u32 * 8;
+        let relocation_offset = (import_offset + import_size).align_next(4);
+
+        let symbol = |x: &Option<Symbol>| x.map_or((0, 0), |x| (x.section as u8, x.offset));
+        let (prolog_section, prolog_offset) = symbol(&self.prolog);
+        let (epilog_section, epilog_offset) = symbol(&self.epilog);
+        let (unresolved_section, unresolved_offset) = symbol(&self.unresolved);
+
+        let base = output.position()?;
+        output.bu32(self.module)?;
+        output.bu32(0)?; // next
+        output.bu32(0)?; // prev
+        output.bu32(self.sections.len() as u32)?;
+        output.bu32(section_offset)?;
+        output.bu32(self.name_offset)?;
+        output.bu32(self.name_size)?;
+        output.bu32(self.version)?;
+        output.bu32(bss_size)?;
+        output.bu32(relocation_offset)?;
+        output.bu32(import_offset)?;
+        output.bu32(import_size)?;
+        output.u8(prolog_section)?;
+        output.u8(epilog_section)?;
+        output.u8(unresolved_section)?;
+        output.u8(self.bss_section)?;
+        output.bu32(prolog_offset)?;
+        output.bu32(epilog_offset)?;
+        output.bu32(unresolved_offset)?;
+        if self.version >= 2 {
+            output.bu32(self.alignment)?;
+            output.bu32(self.bss_alignment)?;
+        }
+        if self.version >= 3 {
+            output.bu32(relocation_offset)?;
+        }
+
+        for (section, offset) in self.sections.iter().zip(section_offsets.iter()) {
+            let flags = section.executable as u32 | ((section.unknown as u32) << 1);
+            output.bu32(offset | flags)?;
+            output.bu32(section.size)?;
+        }
+
+        for (section, offset) in self.sections.iter().zip(section_offsets.iter()) {
+            if *offset != 0 {
+                pad_to(output, base, *offset)?;
+                output.u8_array(&section.data)?;
+            }
+        }
+
+        pad_to(output, base, import_offset)?;
+        let mut stream_offset = relocation_offset;
+        for (table, stream) in self.import_tables.iter().zip(streams.iter()) {
+            output.bu32(table.module)?;
+            output.bu32(stream_offset)?;
+            stream_offset += stream.len() as u32;
+        }
+
+        pad_to(output, base, relocation_offset)?;
+        for stream in streams.iter() {
+            output.u8_array(stream)?;
+        }
+
+        Ok(())
+    }
+
+    /// Serialize the relocation data region, i.e., the relocation stream of
+    /// every [`ImportTable`] in order. This is the data that
+    /// [`Rel::relocation_offset`] points to and that the runtime loader
+    /// reads when linking the module. The stream for table `n` starts
+    /// directly after the stream for table `n - 1`.
+    pub fn relocation_data(&self) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        for table in self.import_tables.iter() {
+            table.to_binary(&mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Apply all relocations to the section data. `resolver` maps a module
+    /// number and a section index to the address the section is loaded at,
+    /// and is called for the sections of this module as well as for every
+    /// referenced section of an imported module. Module `0` is the main
+    /// executable, its relocations use absolute addresses so the resolver
+    /// should return `0` for it.
+    ///
+    /// The resolver is responsible for the placement of every section,
+    /// including the `.bss` section of this module, which has no offset in
+    /// the `.rel` file.
+    ///
+    /// If any section can't be resolved, no data is modified and
+    /// [`BuildProblem::UnresolvedModules`] is returned with the list of
+    /// modules that have unresolved sections. Likewise, if the target of any
+    /// relocation isn't within the data of its section,
+    /// [`BuildProblem::InvalidRange`] is returned before anything is written.
+    pub fn apply_relocations(&mut self, resolver: impl Fn(u32, u32) -> Option<u32>) -> Result<()> {
+        let relocations = self.relocations().collect::<Vec<_>>();
+
+        let mut missing = relocations
+            .iter()
+            .flat_map(|x| [(x.module, x.reference.section), (self.module, x.target.section)])
+            .filter(|(module, section)| resolver(*module, *section).is_none())
+            .map(|(module, _)| module)
+            .collect::<Vec<_>>();
+        missing.sort_unstable();
+        missing.dedup();
+        ensure!(
+            missing.is_empty(),
+            BuildProblem::UnresolvedModules(missing, std::panic::Location::current())
+        );
+
+        let width = |kind: ImportKind| match kind {
+            ImportKind::Addr16
+            | ImportKind::Addr16Lo
+            | ImportKind::Addr16Hi
+            | ImportKind::Addr16Ha => 2,
+            _ => 4,
+        };
+
+        // validate every target before the first write, so that an error
+        // doesn't leave the sections half patched
+        for relocation in relocations.iter() {
+            let target = &relocation.target;
+            let Some(section) = self.sections.get(target.section as usize) else {
+                return Err(BuildProblem::InvalidRange(
+                    "relocation target section",
+                    std::panic::Location::current(),
+                )
+                .into());
+            };
+
+            let offset = target.offset as usize;
+            ensure!(
+                offset + width(relocation.kind) <= section.data.len(),
+                BuildProblem::InvalidRange(
+                    "relocation target offset",
+                    std::panic::Location::current()
+                )
+            );
+        }
+
+        let section_address =
+            |module: u32, section: u32| resolver(module, section).unwrap_or_default();
+
+        for relocation in relocations {
+            let reference = &relocation.reference;
+            let target = &relocation.target;
+            let s = section_address(relocation.module, reference.section);
+            let value = s.wrapping_add(reference.offset);
+            let p = section_address(self.module, target.section).wrapping_add(target.offset);
+
+            let offset = target.offset as usize;
+            let width = width(relocation.kind);
+            let bytes = &mut self.sections[target.section as usize].data[offset..offset + width];
+
+            if width == 2 {
+                let value = match relocation.kind {
+                    ImportKind::Addr16 | ImportKind::Addr16Lo => value as u16,
+                    ImportKind::Addr16Hi => (value >> 16) as u16,
+                    _ => (value.wrapping_add(0x8000) >> 16) as u16,
+                };
+                bytes.copy_from_slice(&value.to_be_bytes());
+            } else {
+                let old = u32::from_be_bytes(bytes.try_into().unwrap());
+                let new = match relocation.kind {
+                    ImportKind::Addr32 => value,
+                    ImportKind::Addr24 => (old & 0xFC00_0003) | (value & 0x03FF_FFFC),
+                    ImportKind::Addr14 => (old & 0xFFFF_0003) | (value & 0xFFFC),
+                    ImportKind::Rel24 => {
+                        (old & 0xFC00_0003) | (value.wrapping_sub(p) & 0x03FF_FFFC)
+                    },
+                    ImportKind::Rel14 => (old & 0xFFFF_0003) | (value.wrapping_sub(p) & 0xFFFC),
+                    _ => old,
+                };
+                bytes.copy_from_slice(&new.to_be_bytes());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Index of the `.bss` section. This is [`Rel::bss_section`] if it's set
+    /// and in range, otherwise the first section without data in the file
+    /// and a non-zero size. Returns [`None`] if the module has no `.bss`.
+    pub fn bss_section_index(&self) -> Option<usize> {
+        let index = self.bss_section as usize;
+        if index != 0 && index < self.sections.len() {
+            return Some(index);
+        }
+
+        self.sections
+            .iter()
+            .enumerate()
+            .skip(1)
+            .find(|(_, x)| x.offset == 0 && x.size != 0)
+            .map(|(i, _)| i)
+    }
+
+    /// Synthetic `.bss` section, i.e., a non-executable section of
+    /// [`Rel::bss_size`] zero bytes. Returns [`None`] if the module has no
+    /// `.bss`, see [`Rel::bss_section_index`]. Fails with
+    /// [`ParseProblem::LimitExceeded`] if the size is larger than
+    /// [`MAX_BSS_SIZE`].
+    pub fn bss(&self) -> Result<Option<Section>> {
+        if self.bss_section_index().is_none() {
+            return Ok(None);
+        }
+
+        let size = self.bss_size as usize;
+        ensure!(
+            size <= MAX_BSS_SIZE,
+            ParseProblem::LimitExceeded("REL .bss size", size, MAX_BSS_SIZE, Location::current())
+        );
+
+        Ok(Some(Section {
+            offset:     0,
+            size:       self.bss_size,
+            executable: false,
+            unknown:    false,
+            data:       vec![0; size],
+        }))
+    }
+
+    /// The section at `index`, with the `.bss` section replaced by the
+    /// synthetic section from [`Rel::bss`].
+    pub fn section_at(&self, index: usize) -> Result<Option<Cow<'_, Section>>> {
+        if self.bss_section_index() == Some(index) {
+            Ok(self.bss()?.map(Cow::Owned))
+        } else {
+            Ok(self.sections.get(index).map(Cow::Borrowed))
+        }
+    }
+
+    /// Guess the name of the section at `index`. The names aren't stored in
+    /// the [`Rel`], so this is only a heuristic based on the usual layout of
+    /// modules built with the Metrowerks toolchain:
+    /// - section 0 and unused sections have no name (`""`),
+    /// - sections without data in the file are `.bss`,
+    /// - executable sections are `.text`, `.text.2`, ...,
+    /// - non-executable sections are `.ctors`, `.dtors`, `.rodata`, `.data`,
+    ///   `.data.2`, ... in that order.
+    pub fn guess_section_name(&self, index: usize) -> &'static str {
+        const TEXT: [&str; 6] = [".text", ".text.2", ".text.3", ".text.4", ".text.5", ".text.6"];
+        const DATA: [&str; 6] = [".ctors", ".dtors", ".rodata", ".data", ".data.2", ".data.3"];
+
+        let Some(section) = self.sections.get(index) else {
+            return "";
+        };
+
+        if index == 0 || (section.offset == 0 && section.size == 0) {
+            return "";
+        } else if section.offset == 0 {
+            return ".bss";
+        }
+
+        let ordinal = self.sections[1..index]
+            .iter()
+            .filter(|x| x.offset != 0 && x.executable == section.executable)
+            .count();
+        let names = if section.executable { &TEXT } else { &DATA };
+        names.get(ordinal).copied().unwrap_or(if section.executable {
+            ".text.n"
+        } else {
+            ".data.n"
+        })
+    }
+
+    /// Read the module name from `framework.str`. Returns [`None`] if
+    /// [`Rel::name_offset`] or [`Rel::name_size`] is zero.
+    pub fn name<D: Parser + Seeker>(&self, framework_str: &mut D) -> Result<Option<String>> {
+        if self.name_offset == 0 || self.name_size == 0 {
+            return Ok(None);
+        }
+
+        let length = framework_str.seek(std::io::SeekFrom::End(0))?;
+        ensure!(
+            self.name_offset as u64 + self.name_size as u64 <= length,
+            ParseProblem::InvalidRange("name out of bounds", std::panic::Location::current())
+        );
+
+        framework_str.goto(self.name_offset as u64)?;
+        let data = framework_str.read_as_vec(self.name_size as usize)?;
+        Ok(Some(Ascii::parse_str(data)?))
+    }
+
     /// Relocation iterator.
-    pub fn relocations(&self) -> RelocationIterator {
+    pub fn relocations(&self) -> RelocationIterator<'_> {
         RelocationIterator {
             rel:     self,
             table:   0,
@@ -261,6 +769,110 @@ impl Rel {
             offset:  0,
         }
     }
+
+    /// Map of every relocation keyed by `(target.section, target.offset)`,
+    /// i.e., the location of the bytes that are modified by the relocation.
+    ///
+    /// Offsets extended with [`ImportKind::DolphinNop`] are already resolved
+    /// by [`Rel::relocations`], so the keys are the real byte positions within
+    /// each section. If multiple relocations target the same location, the
+    /// last one is kept.
+    pub fn relocation_map(&self) -> HashMap<(u32, u32), Relocation> {
+        self.relocations()
+            .map(|x| ((x.target.section, x.target.offset), x))
+            .collect()
+    }
+
+    /// Relocations that modify the section at index `section`, i.e., with
+    /// [`Relocation::target`] in that section, sorted by the target offset.
+    /// Relocations with the same offset keep the order of
+    /// [`Rel::relocations`].
+    pub fn relocations_for_section(&self, section: u32) -> Vec<Relocation> {
+        let mut relocations = self
+            .relocations()
+            .filter(|x| x.target.section == section)
+            .collect::<Vec<_>>();
+        relocations.sort_by_key(|x| x.target.offset);
+        relocations
+    }
+}
+
+impl std::fmt::Display for Rel {
+    /// Concise summary: module, version, section and relocation count, e.g.,
+    /// `REL module 400 (version 3), 18 sections, 450 relocations`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "REL module {} (version {}), {} sections, {} relocations",
+            self.module,
+            self.version,
+            self.sections.len(),
+            self.relocations().count()
+        )
+    }
+}
+
+/// Check if the given data looks like a [REL][`crate::rel`] module. REL files
+/// have no magic, so this checks that the header is sane: the runtime link
+/// fields are zero, the version is supported, and the section table fits in
+/// the file. The stream position is restored.
+pub fn is_rel<D: Parser + Seeker>(input: &mut D) -> bool {
+    let Ok(base) = input.position() else {
+        return false;
+    };
+
+    let mut check = || -> Result<bool> {
+        let header = input.bu32_array::<9>()?;
+        let end = input.seek(std::io::SeekFrom::End(0))? - base;
+        let [_module, next, prev, section_count, section_offset, _, _, version, _] = header;
+        let header_size = match version {
+            1 => 0x40,
+            2 => 0x48,
+            3 => 0x4C,
+            _ => return Ok(false),
+        };
+        Ok(next == 0
+            && prev == 0
+            && (2..32).contains(&section_count)
+            && section_offset >= header_size
+            && section_offset as u64 + section_count as u64 * 8 <= end)
+    };
+
+    let is_rel = check().unwrap_or(false);
+    input.goto(base).is_ok() && is_rel
+}
+
+impl ImportTable {
+    /// Write the relocation stream of this [`ImportTable`]. The stream is
+    /// always terminated by a [`ImportKind::DolphinEnd`], one is added if
+    /// [`ImportTable::imports`] doesn't end with it.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        for import in self.imports.iter() {
+            output.bu16(import.offset)?;
+            output.u8(import.kind.into())?;
+            output.u8(import.section)?;
+            output.bu32(import.addend)?;
+        }
+
+        if self.imports.last().map(|x| x.kind) != Some(ImportKind::DolphinEnd) {
+            output.bu16(0)?;
+            output.u8(ImportKind::DolphinEnd.into())?;
+            output.u8(0)?;
+            output.bu32(0)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write zeros until `output` is at `base + offset`.
+fn pad_to<W: Writer + Seeker>(output: &mut W, base: u64, offset: u32) -> Result<()> {
+    let position = output.position()?;
+    let target = base + offset as u64;
+    if position < target {
+        output.zeros((target - position) as usize)?;
+    }
+    Ok(())
 }
 
 fn optional_symbol(section: u8, offset: u32) -> Option<Symbol> {
@@ -279,6 +891,7 @@ fn parse_sections<D: Parser + Seeker>(
     base: u64,
     section_offset: u32,
     section_count: u32,
+    load_data: bool,
 ) -> Result<Vec<Section>> {
     let mut sections = Vec::<Section>::with_capacity(section_count as usize);
     for i in 0..section_count {
@@ -289,13 +902,8 @@ fn parse_sections<D: Parser + Seeker>(
         let flags = offset_flags & 0x3_u32;
         let size = reader.bu32()?;
 
-        let data = if offset > 0 {
-            ensure!(
-                size <= 0x2000000,
-                ParseProblem::InvalidRange("section too large", std::panic::Location::current())
-            );
-            reader.goto(base + offset as u64)?;
-            reader.read_as_vec(size as usize)?
+        let data = if load_data {
+            read_section_data(reader, base, offset, size)?
         } else {
             Vec::new()
         };
@@ -312,17 +920,33 @@ fn parse_sections<D: Parser + Seeker>(
     Ok(sections)
 }
 
+fn read_section_data<D: Parser + Seeker>(
+    reader: &mut D,
+    base: u64,
+    offset: u32,
+    size: u32,
+) -> Result<Vec<u8>> {
+    if offset > 0 {
+        reader.goto(base + offset as u64)?;
+        reader.read_as_vec_max(size as usize, 0x2000000)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 fn parse_imports<D: Parser + Seeker>(
     reader: &mut D,
     base: u64,
     import_offset: u32,
     import_size: u32,
+    module: u32,
+    section_count: u32,
 ) -> Result<Vec<ImportTable>> {
     let mut import_tables = Vec::<ImportTable>::new();
     let import_table_count = import_size / 8;
     for i in 0..import_table_count {
         reader.goto(base + (import_offset + i * 8) as u64)?;
-        let module = reader.bu32()?;
+        let table_module = reader.bu32()?;
         let offset = reader.bu32()?;
 
         let mut imports = Vec::new();
@@ -333,29 +957,20 @@ fn parse_imports<D: Parser + Seeker>(
             let section = reader.u8()?;
             let addend = reader.bu32()?;
 
-            let kind = match kind {
-                0 => ImportKind::None,
-                1 => ImportKind::Addr32,
-                2 => ImportKind::Addr24,
-                3 => ImportKind::Addr16,
-                4 => ImportKind::Addr16Lo,
-                5 => ImportKind::Addr16Hi,
-                6 => ImportKind::Addr16Ha,
-                7 => ImportKind::Addr14,
-                10 => ImportKind::Rel24,
-                11 => ImportKind::Rel14,
-                201 => ImportKind::DolphinNop,
-                202 => ImportKind::DolphinSection,
-                203 => ImportKind::DolphinEnd,
-                204 => ImportKind::DolphinMRKREF,
-                _ => {
-                    return Err(ParseProblem::InvalidData(
-                        "unknown import kind",
-                        std::panic::Location::current(),
-                    )
-                    .into())
-                },
-            };
+            let kind = ImportKind::try_from(kind)?;
+
+            // `DolphinSection` selects a section of this module. Other imports
+            // reference a section of the imported module, which can only be
+            // checked if that is this module.
+            let local = kind == ImportKind::DolphinSection
+                || (kind.is_relocation() && table_module == module);
+            ensure!(
+                !local || (section as u32) < section_count,
+                ParseProblem::InvalidData(
+                    "import section out of range",
+                    std::panic::Location::current()
+                )
+            );
 
             imports.push(Import {
                 kind,
@@ -370,7 +985,7 @@ fn parse_imports<D: Parser + Seeker>(
         }
 
         import_tables.push(ImportTable {
-            module,
+            module: table_module,
             offset,
             imports,
         });
@@ -50,15 +50,16 @@
 //! - [Shift JIS Kanji Table](http://www.rikai.com/library/kanjitables/kanji_codes.sjis.shtml)
 //! - [JIS X 0213 Code Mapping Tables](http://x0213.org/codetable/index.en.html)
 
-use std::borrow::Borrow;
-use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
 
 use crate::error::DecodingProblem::*;
-use crate::helper::{ensure, ParseStringEncoding, ProblemLocation, Parser};
+use crate::error::{DecodingProblem, EncodingProblem};
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
 use crate::jis_x_0201::Decoder as JisX0201Decoder;
-use crate::Result;
+use crate::{JisX0201, Result};
 
 mod internal {
     include!(concat!(env!("OUT_DIR"), "/shift_jis_1997.rs"));
@@ -78,8 +79,10 @@ where
     I: IntoIterator,
     I::Item: Borrow<u8> + Sized,
 {
-    iter:    <I as IntoIterator>::IntoIter,
-    _marker: PhantomData<&'x ()>,
+    iter:     <I as IntoIterator>::IntoIter,
+    position: usize,
+    pending:  Option<(u8, usize)>,
+    _marker:  PhantomData<&'x ()>,
 }
 
 impl<I> Decoder<'_, I>
@@ -89,15 +92,43 @@ where
 {
     fn new<'x>(iter: I) -> Decoder<'x, I> {
         Decoder {
-            iter:    iter.into_iter(),
-            _marker: PhantomData,
+            iter:     iter.into_iter(),
+            position: 0,
+            pending:  None,
+            _marker:  PhantomData,
         }
     }
 
-    fn decode_next(iter: &mut <I as IntoIterator>::IntoIter) -> Result<Next> {
-        let byte = iter.next();
-        if let Some(byte) = byte {
-            let byte = *byte.borrow();
+    /// Decode lossily. Instead of yielding an error, invalid bytes are
+    /// replaced with `U+FFFD` (replacement character) and decoding continues
+    /// with the next byte.
+    pub fn lossy(self) -> core::iter::Map<Self, fn(Result<char>) -> char> {
+        self.map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Get the next byte and its index in the input.
+    fn next_byte(&mut self) -> Option<(u8, usize)> {
+        if let Some(pending) = self.pending.take() {
+            return Some(pending);
+        }
+
+        let byte = *self.iter.next()?.borrow();
+        self.position += 1;
+        Some((byte, self.position - 1))
+    }
+
+    /// Report an invalid trail byte. If the byte can't be a trail byte at all
+    /// it is kept and decoded again as the start of the next character.
+    #[track_caller]
+    fn invalid_trail(&mut self, byte: u8, index: usize) -> DecodingProblem {
+        if !ShiftJis1997::is_trail_byte(byte) {
+            self.pending = Some((byte, index));
+        }
+        InvalidByte(byte, index, Location::current())
+    }
+
+    fn decode_next(&mut self) -> Result<Next> {
+        if let Some((byte, index)) = self.next_byte() {
             if let Some(c) = JisX0201Decoder::<I>::decode_byte(byte) {
                 return Ok(Next::One(c));
             }
@@ -105,27 +136,16 @@ where
             match byte {
                 // First byte of a double-byte JIS X 0208 character
                 0x81..=0x9F | 0xE0..=0xFC => {
-                    let next = iter
-                        .next()
+                    let (next, next_index) = self
+                        .next_byte()
                         .ok_or_else(|| UnexpectedEndOfData(Location::current()))?;
-                    let next = *next.borrow();
-                    let (first, last, offset) = internal::SJIS_1997_UTF8_T[byte as usize];
-                    ensure!(
-                        next >= first && next <= last,
-                        InvalidByte(next, Location::current())
-                    );
-                    let relative = (next - first) as usize;
-                    let index = offset + relative;
-                    let value = internal::SJIS_1997_UTF8_S[index];
-                    ensure!(value != 0, InvalidByte(next, Location::current()));
-                    ensure!(
-                        (value & 0x8000_0000) == 0,
-                        InvalidByte(next, Location::current())
-                    );
-                    Ok(Next::One(unsafe { char::from_u32_unchecked(value) }))
+                    match ShiftJis1997::decode_double(byte, next) {
+                        Some(c) => Ok(Next::One(c)),
+                        None => Err(self.invalid_trail(next, next_index).into()),
+                    }
                 },
                 // Invalid as first byte
-                _ => Err(InvalidByte(byte, Location::current()).into()),
+                _ => Err(InvalidByte(byte, index, Location::current()).into()),
             }
         } else {
             Ok(Next::EndOfInput)
@@ -141,7 +161,7 @@ where
     type Item = Result<char>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match Self::decode_next(&mut self.iter) {
+        match self.decode_next() {
             Ok(Next::EndOfInput) => None,
             Ok(Next::One(c)) => Some(Ok(c)),
             Err(e) => Some(Err(e)),
@@ -150,6 +170,38 @@ where
 }
 
 impl ShiftJis1997 {
+    /// Check if `byte` is in the range of trail bytes of a double-byte
+    /// character.
+    pub(crate) fn is_trail_byte(byte: u8) -> bool { matches!(byte, 0x40..=0x7E | 0x80..=0xFC) }
+
+    /// Decode a double-byte JIS X 0208 character from its lead and trail byte.
+    pub(crate) fn decode_double(lead: u8, trail: u8) -> Option<char> {
+        let (first, last, offset) = internal::SJIS_1997_UTF8_T[lead as usize];
+        if last == 0 || trail < first || trail > last {
+            return None;
+        }
+
+        let value = internal::SJIS_1997_UTF8_S[offset + (trail - first) as usize];
+        if value == 0 || (value & 0x8000_0000) != 0 {
+            return None;
+        }
+
+        char::from_u32(value)
+    }
+
+    /// Encode a single character. Returns the encoded bytes and the number of
+    /// bytes used, or `None` if the character can't be represented in [Shift
+    /// JIS 1997][`ShiftJis1997`].
+    pub fn encode_char(c: char) -> Option<([u8; 2], usize)> {
+        if let Some(byte) = JisX0201::encode_char(c) {
+            return Some(([byte, 0], 1));
+        }
+
+        let table = &internal::SJIS_1997_UTF8_R;
+        let index = table.binary_search_by_key(&(c as u32), |x| x.0).ok()?;
+        Some((table[index].1.to_be_bytes(), 2))
+    }
+
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
     pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
@@ -214,20 +266,31 @@ impl ParseStringEncoding for ShiftJis1997 {
         Self::first(iter)
     }
 
-    fn write_str(_data: &str, _buffer: &mut [u8]) -> Result<usize> {
-        todo!("ShiftJis1997::write_str")
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for c in data.chars() {
+            let Some((bytes, length)) = Self::encode_char(c) else {
+                return Err(EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into());
+            };
+            if i + length > buffer.len() {
+                return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+            }
+            buffer[i..i + length].copy_from_slice(&bytes[..length]);
+            i += length;
+        }
+        Ok(i)
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
-        let buffer = BufReader::new(reader);
-        let iter = buffer
-            .bytes()
-            .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
-        Self::first(iter)
+        Self::first(parser_bytes(reader))
     }
 }
 
@@ -242,6 +305,20 @@ mod tests {
     #[test]
     fn parse_str() {
         let data = b"abc\x88\x9f\0def";
-        assert_eq!(ShiftJis1997::parse_str(data).unwrap(), "abc亜".to_string());
+        assert_eq!(ShiftJis1997::parse_str(data).unwrap(), "abc亜");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 8];
+        let string = ShiftJis1997::all(b"\x88\x9f").unwrap();
+        assert_eq!(string, "亜");
+        let length = ShiftJis1997::write_str(&string, &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"\x88\x9f");
+
+        let length = ShiftJis1997::write_str("a\u{ff61}亜", &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"a\xa1\x88\x9f");
+        assert!(ShiftJis1997::write_str("\u{1f600}", &mut buffer).is_err());
+        assert!(ShiftJis1997::write_str("亜", &mut buffer[..1]).is_err());
     }
 }
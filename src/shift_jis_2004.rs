@@ -28,15 +28,16 @@
 //! - [JIS X 0213 Code Mapping Tables](http://x0213.org/codetable/index.en.html)
 //! - [Shift JIS Kanji Table](http://www.rikai.com/library/kanjitables/kanji_codes.sjis.shtml)
 
-use std::borrow::Borrow;
-use std::io::{BufReader, Read};
-use std::marker::PhantomData;
-use std::panic::Location;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
 
 use crate::error::DecodingProblem::*;
-use crate::helper::{ensure, ParseStringEncoding, ProblemLocation, Parser};
+use crate::error::{DecodingProblem, EncodingProblem};
+use crate::helper::{ensure, parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
 use crate::jis_x_0201::Decoder as JisX0201Decoder;
-use crate::Result;
+use crate::{JisX0201, Result, ShiftJis1997};
 
 /// [`ShiftJis2004`] encoding.
 pub struct ShiftJis2004 {}
@@ -58,6 +59,8 @@ where
     I::Item: Borrow<u8> + Sized,
 {
     iter:     <I as IntoIterator>::IntoIter,
+    position: usize,
+    pending:  Option<(u8, usize)>,
     buffered: Option<char>,
     _marker:  PhantomData<&'x ()>,
 }
@@ -70,15 +73,43 @@ where
     fn new<'x>(iter: I) -> Decoder<'x, I> {
         Decoder {
             iter:     iter.into_iter(),
+            position: 0,
+            pending:  None,
             buffered: None,
             _marker:  PhantomData,
         }
     }
 
-    fn decode_next(iter: &mut <I as IntoIterator>::IntoIter) -> Result<Next> {
-        let byte = iter.next();
-        if let Some(byte) = byte {
-            let byte = *byte.borrow();
+    /// Decode lossily. Instead of yielding an error, invalid bytes are
+    /// replaced with `U+FFFD` (replacement character) and decoding continues
+    /// with the next byte.
+    pub fn lossy(self) -> core::iter::Map<Self, fn(Result<char>) -> char> {
+        self.map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Get the next byte and its index in the input.
+    fn next_byte(&mut self) -> Option<(u8, usize)> {
+        if let Some(pending) = self.pending.take() {
+            return Some(pending);
+        }
+
+        let byte = *self.iter.next()?.borrow();
+        self.position += 1;
+        Some((byte, self.position - 1))
+    }
+
+    /// Report an invalid trail byte. If the byte can't be a trail byte at all
+    /// it is kept and decoded again as the start of the next character.
+    #[track_caller]
+    fn invalid_trail(&mut self, byte: u8, index: usize) -> DecodingProblem {
+        if !ShiftJis1997::is_trail_byte(byte) {
+            self.pending = Some((byte, index));
+        }
+        InvalidByte(byte, index, Location::current())
+    }
+
+    fn decode_next(&mut self) -> Result<Next> {
+        if let Some((byte, index)) = self.next_byte() {
             if let Some(c) = JisX0201Decoder::<I>::decode_byte(byte) {
                 return Ok(Next::One(c));
             }
@@ -86,19 +117,15 @@ where
             match byte {
                 // First byte of a double-byte JIS X 0208 or JIS X 0213 character
                 0x81..=0x9F | 0xE0..=0xFC => {
-                    let next = iter
-                        .next()
+                    let (next, next_index) = self
+                        .next_byte()
                         .ok_or_else(|| UnexpectedEndOfData(Location::current()))?;
-                    let next = *next.borrow();
                     let (first, last, offset) = internal::SJIS_2004_UTF8_T[byte as usize];
-                    ensure!(
-                        next >= first && next <= last,
-                        InvalidByte(next, Location::current())
-                    );
+                    ensure!(next >= first && next <= last, self.invalid_trail(next, next_index));
                     let relative = (next - first) as usize;
                     let index = offset + relative;
                     let value = internal::SJIS_2004_UTF8_S[index];
-                    ensure!(value != 0, InvalidByte(next, Location::current()));
+                    ensure!(value != 0, self.invalid_trail(next, next_index));
                     if value & 0x8000_0000 != 0 {
                         let index = (value & 0x7fff_ffff) as usize;
                         let (first, second) = internal::SJIS_2004_UTF8_D[index];
@@ -111,7 +138,7 @@ where
                     }
                 },
                 // Invalid as first byte
-                _ => Err(InvalidByte(byte, Location::current()).into()),
+                _ => Err(InvalidByte(byte, index, Location::current()).into()),
             }
         } else {
             Ok(Next::EndOfInput)
@@ -131,7 +158,7 @@ where
             self.buffered = None;
             Some(Ok(value))
         } else {
-            match Self::decode_next(&mut self.iter) {
+            match self.decode_next() {
                 Ok(Next::EndOfInput) => None,
                 Ok(Next::One(c)) => Some(Ok(c)),
                 Ok(Next::Two(first, second)) => {
@@ -145,6 +172,30 @@ where
 }
 
 impl ShiftJis2004 {
+    /// Encode a single character. Returns the encoded bytes and the number of
+    /// bytes used, or `None` if the character can't be represented in [Shift
+    /// JIS 2004][`ShiftJis2004`].
+    pub fn encode_char(c: char) -> Option<([u8; 2], usize)> {
+        if let Some(byte) = JisX0201::encode_char(c) {
+            return Some(([byte, 0], 1));
+        }
+
+        let table = &internal::SJIS_2004_UTF8_R;
+        let index = table.binary_search_by_key(&(c as u32), |x| x.0).ok()?;
+        Some((table[index].1.to_be_bytes(), 2))
+    }
+
+    /// Encode a pair of characters that map to a single double-byte character,
+    /// e.g., `か` followed by the combining mark `U+309A`. Returns `None` if
+    /// the pair doesn't have its own encoding.
+    pub fn encode_pair(first: char, second: char) -> Option<[u8; 2]> {
+        let table = &internal::SJIS_2004_UTF8_R2;
+        let index = table
+            .binary_search_by_key(&(first as u32, second as u32), |x| (x.0, x.1))
+            .ok()?;
+        Some(table[index].2.to_be_bytes())
+    }
+
     /// Create an iterator that decodes the given iterator of bytes into
     /// characters.
     pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
@@ -209,20 +260,38 @@ impl ParseStringEncoding for ShiftJis2004 {
         Self::first(iter)
     }
 
-    fn write_str(_data: &str, _buffer: &mut [u8]) -> Result<usize> {
-        todo!("ShiftJis2004::write_str")
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        let mut chars = data.chars().peekable();
+        while let Some(c) = chars.next() {
+            let pair = chars.peek().and_then(|next| Self::encode_pair(c, *next));
+            let (bytes, length) = if let Some(bytes) = pair {
+                chars.next();
+                (bytes, 2)
+            } else if let Some(encoded) = Self::encode_char(c) {
+                encoded
+            } else {
+                return Err(EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into());
+            };
+            if i + length > buffer.len() {
+                return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+            }
+            buffer[i..i + length].copy_from_slice(&bytes[..length]);
+            i += length;
+        }
+        Ok(i)
     }
 
     fn from_binary(reader: &mut impl Parser) -> Result<String> {
-        let buffer = BufReader::new(reader);
-        let iter = buffer
-            .bytes()
-            .take_while(|x| x.is_ok())
-            .filter_map(|x| match x {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            });
-        Self::first(iter)
+        Self::first(parser_bytes(reader))
     }
 }
 
@@ -232,11 +301,42 @@ impl ParseStringEncoding for ShiftJis2004 {
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
+
     use super::*;
 
     #[test]
     fn parse_str() {
         let data = b"abc\x88\x9f\0def";
-        assert_eq!(ShiftJis2004::parse_str(data).unwrap(), "abc亜".to_string());
+        assert_eq!(ShiftJis2004::parse_str(data).unwrap(), "abc亜");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 8];
+        let length = ShiftJis2004::write_str("a\u{ff61}亜", &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"a\xa1\x88\x9f");
+
+        // か with a combining semi-voiced mark is a single character
+        let string = ShiftJis2004::all(b"\x82\xf5").unwrap();
+        assert_eq!(string, "\u{304b}\u{309a}");
+        let length = ShiftJis2004::write_str(&string, &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"\x82\xf5");
+
+        // か on its own is still encoded as a single character
+        let length = ShiftJis2004::write_str("\u{304b}a", &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"\x82\xa9a");
+
+        assert!(ShiftJis2004::write_str("\u{1f600}", &mut buffer).is_err());
+        assert!(ShiftJis2004::write_str("\u{304b}\u{309a}", &mut buffer[..1]).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = include_bytes!("../assets/tests/shift-jis/2004.ok.shift-jis.txt");
+        let string = ShiftJis2004::all(data).unwrap();
+        let mut buffer = vec![0u8; data.len()];
+        let length = ShiftJis2004::write_str(&string, &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], data);
     }
 }
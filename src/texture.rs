@@ -0,0 +1,414 @@
+//! GX texture decoding.
+//!
+//! GameCube textures are stored in tiles (blocks) of pixels. The image is
+//! split into blocks of a size that depends on the format, e.g., `8x8` for
+//! 4-bit formats and `4x4` for 16-bit formats. Blocks are stored left to
+//! right, top to bottom, and pixels within a block are stored row-major. If
+//! the width or height isn't a multiple of the block size, the last column or
+//! row of blocks is padded.
+//!
+//! [`decode`] untiles the data and converts each pixel to row-major RGBA8.
+//! Palette formats are decoded with [`decode_indexed`] and a [`Palette`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use picori::Result;
+//! use picori::texture::{decode, GxTextureFormat};
+//!
+//! fn main() -> Result<()> {
+//!     let data = [0xFF; 32]; // one 8x4 block of white I8 pixels
+//!     let rgba = decode(GxTextureFormat::I8, 8, 4, &data)?;
+//!     assert_eq!(rgba, [0xFF; 8 * 4 * 4]);
+//!     Ok(())
+//! }
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{ensure, ProblemLocation};
+use crate::Result;
+
+/// GX texture format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GxTextureFormat {
+    /// 4-bit intensity.
+    I4     = 0x0,
+    /// 8-bit intensity.
+    I8     = 0x1,
+    /// 4-bit intensity and 4-bit alpha.
+    IA4    = 0x2,
+    /// 8-bit intensity and 8-bit alpha.
+    IA8    = 0x3,
+    /// 16-bit color without alpha.
+    RGB565 = 0x4,
+    /// 16-bit color, either RGB555 or RGB4A3.
+    RGB5A3 = 0x5,
+    /// 32-bit color.
+    RGBA8  = 0x6,
+    /// 4-bit palette index.
+    CI4    = 0x8,
+    /// 8-bit palette index.
+    CI8    = 0x9,
+    /// 14-bit palette index.
+    CI14X2 = 0xA,
+    /// Compressed (S3TC/DXT1-like).
+    CMPR   = 0xE,
+}
+
+impl GxTextureFormat {
+    /// Get the [`GxTextureFormat`] from the value used by GX and texture
+    /// files, or [`None`] if the value is unknown.
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0x0 => Some(Self::I4),
+            0x1 => Some(Self::I8),
+            0x2 => Some(Self::IA4),
+            0x3 => Some(Self::IA8),
+            0x4 => Some(Self::RGB565),
+            0x5 => Some(Self::RGB5A3),
+            0x6 => Some(Self::RGBA8),
+            0x8 => Some(Self::CI4),
+            0x9 => Some(Self::CI8),
+            0xA => Some(Self::CI14X2),
+            0xE => Some(Self::CMPR),
+            _ => None,
+        }
+    }
+
+    /// Width and height of a block in pixels.
+    pub fn block_size(self) -> (u32, u32) {
+        match self {
+            Self::I4 | Self::CI4 | Self::CMPR => (8, 8),
+            Self::I8 | Self::IA4 | Self::CI8 => (8, 4),
+            Self::IA8 | Self::RGB565 | Self::RGB5A3 | Self::RGBA8 | Self::CI14X2 => (4, 4),
+        }
+    }
+
+    /// Bits per pixel.
+    pub fn bits_per_pixel(self) -> u32 {
+        match self {
+            Self::I4 | Self::CI4 | Self::CMPR => 4,
+            Self::I8 | Self::IA4 | Self::CI8 => 8,
+            Self::IA8 | Self::RGB565 | Self::RGB5A3 | Self::CI14X2 => 16,
+            Self::RGBA8 => 32,
+        }
+    }
+
+    /// Checks if the format is a palette format (CI4, CI8 or CI14X2).
+    pub fn is_indexed(self) -> bool {
+        matches!(self, Self::CI4 | Self::CI8 | Self::CI14X2)
+    }
+
+    /// Size in bytes of the texture data for an image of the given size,
+    /// including padding blocks. Returns [`ParseProblem::InvalidData`] if the
+    /// size doesn't fit in a `usize`.
+    pub fn data_size(self, width: u32, height: u32) -> Result<usize> {
+        let (block_width, block_height) = self.block_size();
+        let blocks_x = width.div_ceil(block_width) as usize;
+        let blocks_y = height.div_ceil(block_height) as usize;
+        let block_bytes = (block_width * block_height * self.bits_per_pixel() / 8) as usize;
+        blocks_x
+            .checked_mul(blocks_y)
+            .and_then(|x| x.checked_mul(block_bytes))
+            .ok_or_else(|| {
+                ParseProblem::InvalidData("texture size too large", Location::current()).into()
+            })
+    }
+}
+
+/// Palette (TLUT) color format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TlutFormat {
+    /// 8-bit intensity and 8-bit alpha.
+    IA8    = 0x0,
+    /// 16-bit color without alpha.
+    RGB565 = 0x1,
+    /// 16-bit color, either RGB555 or RGB4A3.
+    RGB5A3 = 0x2,
+}
+
+impl TlutFormat {
+    /// Get the [`TlutFormat`] from the value used by GX and texture files,
+    /// or [`None`] if the value is unknown.
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0x0 => Some(Self::IA8),
+            0x1 => Some(Self::RGB565),
+            0x2 => Some(Self::RGB5A3),
+            _ => None,
+        }
+    }
+}
+
+/// Palette used by [`decode_indexed`], e.g., the palette data of a TPL
+/// image. Each entry is a big-endian 16-bit color in [`Palette::format`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    /// Color format of the entries.
+    pub format: TlutFormat,
+    /// Raw palette data.
+    pub data:   Vec<u8>,
+}
+
+impl Palette {
+    /// Create a palette from raw palette data.
+    pub fn new(format: TlutFormat, data: Vec<u8>) -> Self { Self { format, data } }
+
+    /// Number of entries in the palette.
+    pub fn len(&self) -> usize { self.data.len() / 2 }
+
+    /// Checks if the palette has no entries.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Decode the palette entries to RGBA8.
+    pub fn colors(&self) -> Vec<[u8; 4]> {
+        self.data
+            .chunks_exact(2)
+            .map(|x| decode_color(self.format, u16::from_be_bytes([x[0], x[1]])))
+            .collect()
+    }
+}
+
+#[inline]
+fn expand3(x: u16) -> u8 { ((x << 5) | (x << 2) | (x >> 1)) as u8 }
+
+#[inline]
+fn expand4(x: u16) -> u8 { (x * 0x11) as u8 }
+
+#[inline]
+fn expand5(x: u16) -> u8 { ((x << 3) | (x >> 2)) as u8 }
+
+#[inline]
+fn expand6(x: u16) -> u8 { ((x << 2) | (x >> 4)) as u8 }
+
+/// Decode texture `data` in the given `format` to row-major RGBA8.
+///
+/// Returns [`ParseProblem::InvalidData`] if the data is smaller than
+/// [`GxTextureFormat::data_size`] or if the format is a palette format, use
+/// [`decode_indexed`] for those.
+pub fn decode(format: GxTextureFormat, width: u32, height: u32, data: &[u8]) -> Result<Vec<u8>> {
+    ensure!(
+        !format.is_indexed(),
+        ParseProblem::InvalidData("unsupported texture format", Location::current())
+    );
+
+    untile(format, width, height, data, |block, pixels| {
+        decode_block(format, block, pixels);
+        Ok(())
+    })
+}
+
+/// Decode palette texture `indices` in the given `format` (CI4, CI8 or
+/// CI14X2) to row-major RGBA8, looking up each index in `palette`.
+///
+/// Returns [`ParseProblem::InvalidData`] if the format isn't a palette
+/// format, if the data is smaller than [`GxTextureFormat::data_size`] or if
+/// an index is outside of the palette.
+pub fn decode_indexed(
+    format: GxTextureFormat,
+    width: u32,
+    height: u32,
+    indices: &[u8],
+    palette: &Palette,
+) -> Result<Vec<u8>> {
+    ensure!(
+        format.is_indexed(),
+        ParseProblem::InvalidData("not a palette texture format", Location::current())
+    );
+
+    let colors = palette.colors();
+    untile(format, width, height, indices, |block, pixels| {
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let index = match format {
+                GxTextureFormat::CI4 => {
+                    let byte = block[i / 2] as usize;
+                    if i.is_multiple_of(2) { byte >> 4 } else { byte & 0xF }
+                },
+                GxTextureFormat::CI8 => block[i] as usize,
+                _ => (u16::from_be_bytes([block[2 * i], block[2 * i + 1]]) & 0x3FFF) as usize,
+            };
+            *pixel = *colors.get(index).ok_or(ParseProblem::InvalidData(
+                "palette index out of range",
+                Location::current(),
+            ))?;
+        }
+        Ok(())
+    })
+}
+
+/// Untile `data` block by block into a row-major RGBA8 image, `decode`
+/// converts a single block to row-major pixels.
+fn untile<F>(
+    format: GxTextureFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+    mut decode: F,
+) -> Result<Vec<u8>>
+where
+    F: FnMut(&[u8], &mut [[u8; 4]]) -> Result<()>,
+{
+    ensure!(
+        data.len() >= format.data_size(width, height)?,
+        ParseProblem::InvalidData("texture data too small", Location::current())
+    );
+
+    let (block_width, block_height) = format.block_size();
+    let block_pixels = (block_width * block_height) as usize;
+    let block_bytes = block_pixels * format.bits_per_pixel() as usize / 8;
+    let blocks_x = width.div_ceil(block_width);
+    let blocks_y = height.div_ceil(block_height);
+
+    let mut output = vec![0u8; width as usize * height as usize * 4];
+    let mut pixels = vec![[0u8; 4]; block_pixels];
+    for (index, block) in data
+        .chunks_exact(block_bytes)
+        .take((blocks_x * blocks_y) as usize)
+        .enumerate()
+    {
+        decode(block, &mut pixels)?;
+
+        let block_x = (index as u32 % blocks_x) * block_width;
+        let block_y = (index as u32 / blocks_x) * block_height;
+        for (i, pixel) in pixels.iter().enumerate() {
+            let x = block_x + i as u32 % block_width;
+            let y = block_y + i as u32 / block_width;
+            if x < width && y < height {
+                let offset = (y as usize * width as usize + x as usize) * 4;
+                output[offset..offset + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Decode a 16-bit color to RGBA8.
+fn decode_color(format: TlutFormat, value: u16) -> [u8; 4] {
+    match format {
+        TlutFormat::IA8 => {
+            let a = (value >> 8) as u8;
+            let i = value as u8;
+            [i, i, i, a]
+        },
+        TlutFormat::RGB565 => [
+            expand5((value >> 11) & 0x1F),
+            expand6((value >> 5) & 0x3F),
+            expand5(value & 0x1F),
+            0xFF,
+        ],
+        TlutFormat::RGB5A3 => {
+            if value & 0x8000 != 0 {
+                [
+                    expand5((value >> 10) & 0x1F),
+                    expand5((value >> 5) & 0x1F),
+                    expand5(value & 0x1F),
+                    0xFF,
+                ]
+            } else {
+                [
+                    expand4((value >> 8) & 0xF),
+                    expand4((value >> 4) & 0xF),
+                    expand4(value & 0xF),
+                    expand3((value >> 12) & 0x7),
+                ]
+            }
+        },
+    }
+}
+
+/// Decode a single block into row-major RGBA8 pixels.
+fn decode_block(format: GxTextureFormat, block: &[u8], pixels: &mut [[u8; 4]]) {
+    if format == GxTextureFormat::CMPR {
+        decode_cmpr_block(block, pixels);
+        return;
+    }
+
+    let u16_at = |i: usize| u16::from_be_bytes([block[2 * i], block[2 * i + 1]]);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = match format {
+            GxTextureFormat::I4 => {
+                let byte = block[i / 2] as u16;
+                let value = if i.is_multiple_of(2) { byte >> 4 } else { byte & 0xF };
+                let i = expand4(value);
+                [i, i, i, i]
+            },
+            GxTextureFormat::I8 => {
+                let i = block[i];
+                [i, i, i, i]
+            },
+            GxTextureFormat::IA4 => {
+                let byte = block[i] as u16;
+                let a = expand4(byte >> 4);
+                let i = expand4(byte & 0xF);
+                [i, i, i, a]
+            },
+            GxTextureFormat::IA8 => decode_color(TlutFormat::IA8, u16_at(i)),
+            GxTextureFormat::RGB565 => decode_color(TlutFormat::RGB565, u16_at(i)),
+            GxTextureFormat::RGB5A3 => decode_color(TlutFormat::RGB5A3, u16_at(i)),
+            // AR pairs for the whole block followed by GB pairs
+            GxTextureFormat::RGBA8 => [
+                block[2 * i + 1],
+                block[32 + 2 * i],
+                block[32 + 2 * i + 1],
+                block[2 * i],
+            ],
+            GxTextureFormat::CI4
+            | GxTextureFormat::CI8
+            | GxTextureFormat::CI14X2
+            | GxTextureFormat::CMPR => unreachable!(),
+        };
+    }
+}
+
+/// Decode an 8x8 CMPR block. The block is made of four 4x4 DXT1 sub-blocks
+/// (top-left, top-right, bottom-left, bottom-right). Unlike DXT1, the two
+/// RGB565 colors are big-endian and the 2-bit indices of each row are stored
+/// with the leftmost pixel in the most significant bits.
+fn decode_cmpr_block(block: &[u8], pixels: &mut [[u8; 4]]) {
+    for (index, sub_block) in block.chunks_exact(8).enumerate() {
+        let palette = cmpr_palette(
+            u16::from_be_bytes([sub_block[0], sub_block[1]]),
+            u16::from_be_bytes([sub_block[2], sub_block[3]]),
+        );
+
+        let sub_x = (index % 2) * 4;
+        let sub_y = (index / 2) * 4;
+        for (y, row) in sub_block[4..].iter().enumerate() {
+            for x in 0..4 {
+                let value = (row >> (6 - 2 * x)) & 0x3;
+                pixels[(sub_y + y) * 8 + sub_x + x] = palette[value as usize];
+            }
+        }
+    }
+}
+
+/// The four colors of a CMPR sub-block. If `c0 > c1` the two other colors
+/// are interpolated at 1/3 and 2/3, otherwise the third color is the average
+/// and the fourth is transparent black.
+fn cmpr_palette(c0: u16, c1: u16) -> [[u8; 4]; 4] {
+    let rgb = |value: u16| {
+        [
+            expand5((value >> 11) & 0x1F) as u16,
+            expand6((value >> 5) & 0x3F) as u16,
+            expand5(value & 0x1F) as u16,
+        ]
+    };
+    let (a, b) = (rgb(c0), rgb(c1));
+    let mix = |wa: u16, wb: u16| {
+        let mix = |i: usize| ((a[i] * wa + b[i] * wb) / (wa + wb)) as u8;
+        [mix(0), mix(1), mix(2), 0xFF]
+    };
+
+    let first = mix(1, 0);
+    let second = mix(0, 1);
+    if c0 > c1 {
+        [first, second, mix(2, 1), mix(1, 2)]
+    } else {
+        [first, second, mix(1, 1), [0, 0, 0, 0]]
+    }
+}
@@ -0,0 +1,303 @@
+//! THP video container.
+//!
+//! THP is the movie format used by GameCube and Wii games. A THP file starts
+//! with a `0x30` byte [`Header`] (magic `THP\0`), followed by the component
+//! table and the frames. Each component is either a video stream (JPEG
+//! frames) or an audio stream. Only the container is parsed, frames are not
+//! decoded.
+//!
+//! Every frame starts with the size of the next frame, the size of the
+//! previous frame and the size of the data of each component. [`Thp::frames`]
+//! walks this chain from [`Header::first_frame_offset`] to enumerate the
+//! frames.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("movie.thp")?;
+//!     let thp = picori::Thp::from_binary(&mut file)?;
+//!     println!("{} frames at {} fps", thp.header.frame_count, thp.header.frame_rate);
+//!     for frame in thp.frames(&mut file) {
+//!         let frame = frame?;
+//!         println!("{:#x}: {:#x} bytes", frame.offset, frame.size);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// THP magic (`THP\0`).
+pub const MAGIC: u32 = 0x54485000;
+
+/// Version 1.0, used by GameCube games.
+pub const VERSION_1_0: u32 = 0x00010000;
+
+/// Version 1.1, adds the video type and the audio track count to the
+/// component table.
+pub const VERSION_1_1: u32 = 0x00011000;
+
+/// Maximum number of components in the component table.
+const COMPONENT_MAX: usize = 16;
+
+/// Maximum size of a frame read by [`Frame::data`].
+const FRAME_SIZE_MAX: usize = 0x1000000;
+
+/// THP header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    /// THP magic (0x54485000).
+    pub magic:               u32,
+    /// Version, [`VERSION_1_0`] or [`VERSION_1_1`].
+    pub version:             u32,
+    /// Size of the largest frame.
+    pub max_buffer_size:     u32,
+    /// Largest number of audio samples in a frame.
+    pub max_audio_samples:   u32,
+    /// Frames per second.
+    pub frame_rate:          f32,
+    /// Number of frames.
+    pub frame_count:         u32,
+    /// Size of the first frame.
+    pub first_frame_size:    u32,
+    /// Total size of all frames.
+    pub data_size:           u32,
+    /// Offset of the component table.
+    pub component_offset:    u32,
+    /// Offset of the frame offset table, zero if there is none.
+    pub offset_table_offset: u32,
+    /// Offset of the first frame.
+    pub first_frame_offset:  u32,
+    /// Offset of the last frame.
+    pub last_frame_offset:   u32,
+}
+
+/// Video component information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Video {
+    /// Width in pixels.
+    pub width:      u32,
+    /// Height in pixels.
+    pub height:     u32,
+    /// Video type (interlacing), only present in version 1.1.
+    pub video_type: Option<u32>,
+}
+
+/// Audio component information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audio {
+    /// Number of channels.
+    pub channels:     u32,
+    /// Sample rate in Hz.
+    pub frequency:    u32,
+    /// Total number of samples.
+    pub sample_count: u32,
+    /// Number of audio tracks, only present in version 1.1.
+    pub track_count:  Option<u32>,
+}
+
+/// Component of a THP file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    /// Video stream.
+    Video(Video),
+    /// Audio stream.
+    Audio(Audio),
+}
+
+/// Frame of a THP file, see [`Thp::frames`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Offset of the frame, including the frame header.
+    pub offset: u64,
+    /// Size of the frame, including the frame header.
+    pub size:   u32,
+}
+
+/// THP video container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thp {
+    /// THP header.
+    pub header:     Header,
+    /// Components in the order they are stored in each frame.
+    pub components: Vec<Component>,
+}
+
+impl Header {
+    /// Reads a THP header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        Ok(Header {
+            magic:               input.bu32()?,
+            version:             input.bu32()?,
+            max_buffer_size:     input.bu32()?,
+            max_audio_samples:   input.bu32()?,
+            frame_rate:          input.bf32()?,
+            frame_count:         input.bu32()?,
+            first_frame_size:    input.bu32()?,
+            data_size:           input.bu32()?,
+            component_offset:    input.bu32()?,
+            offset_table_offset: input.bu32()?,
+            first_frame_offset:  input.bu32()?,
+            last_frame_offset:   input.bu32()?,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool { self.magic == MAGIC }
+}
+
+impl Frame {
+    /// Read the raw frame data, including the frame header. Fails with
+    /// [`ParseProblem::InvalidRange`] if the frame is larger than 16 MiB.
+    pub fn data<D: Parser + Seeker>(&self, reader: &mut D) -> Result<Vec<u8>> {
+        reader.goto(self.offset)?;
+        reader.read_as_vec_max(self.size as usize, FRAME_SIZE_MAX)
+    }
+}
+
+impl Thp {
+    /// Parse [`Thp`] from binary stream. Offsets in the file are relative to
+    /// the start of the stream.
+    pub fn from_binary<D: Parser + Seeker>(reader: &mut D) -> Result<Self> {
+        let header = Header::from_binary(reader)?;
+        ensure!(
+            header.is_valid(),
+            ParseProblem::InvalidMagic("invalid THP magic", Location::current())
+        );
+        ensure!(
+            header.version == VERSION_1_0 || header.version == VERSION_1_1,
+            ParseProblem::UnsupportedVersion(header.version as usize, Location::current())
+        );
+
+        reader.goto(header.component_offset as u64)?;
+        let count = reader.bu32()? as usize;
+        ensure!(
+            count <= COMPONENT_MAX,
+            ParseProblem::LimitExceeded("THP component", count, COMPONENT_MAX, Location::current())
+        );
+
+        let extended = header.version == VERSION_1_1;
+        let types = reader.u8_array::<COMPONENT_MAX>()?;
+        let mut components = Vec::with_capacity(count);
+        for kind in types.iter().take(count) {
+            let component = match kind {
+                0 => Component::Video(Video {
+                    width:      reader.bu32()?,
+                    height:     reader.bu32()?,
+                    video_type: if extended { Some(reader.bu32()?) } else { None },
+                }),
+                1 => Component::Audio(Audio {
+                    channels:     reader.bu32()?,
+                    frequency:    reader.bu32()?,
+                    sample_count: reader.bu32()?,
+                    track_count:  if extended { Some(reader.bu32()?) } else { None },
+                }),
+                _ => {
+                    return Err(ParseProblem::InvalidData(
+                        "invalid THP component type",
+                        Location::current(),
+                    )
+                    .into())
+                },
+            };
+            components.push(component);
+        }
+
+        Ok(Self { header, components })
+    }
+
+    /// The video component, if any.
+    pub fn video(&self) -> Option<&Video> {
+        self.components.iter().find_map(|x| match x {
+            Component::Video(video) => Some(video),
+            _ => None,
+        })
+    }
+
+    /// The audio component, if any.
+    pub fn audio(&self) -> Option<&Audio> {
+        self.components.iter().find_map(|x| match x {
+            Component::Audio(audio) => Some(audio),
+            _ => None,
+        })
+    }
+
+    /// Iterator over the [`Header::frame_count`] frames. The size of each
+    /// frame after the first is read from the `next frame size` field of the
+    /// previous frame. A frame larger than [`Header::max_buffer_size`] is an
+    /// error, and the iterator stops after the first error.
+    pub fn frames<'reader, D: Parser + Seeker>(
+        &self,
+        reader: &'reader mut D,
+    ) -> Frames<'reader, D> {
+        Frames {
+            reader,
+            offset: self.header.first_frame_offset as u64,
+            size: self.header.first_frame_size,
+            max_size: self.header.max_buffer_size,
+            remaining: self.header.frame_count,
+        }
+    }
+}
+
+/// Iterator over the frames of a [`Thp`], see [`Thp::frames`].
+pub struct Frames<'reader, D: Parser + Seeker> {
+    reader:    &'reader mut D,
+    offset:    u64,
+    size:      u32,
+    max_size:  u32,
+    remaining: u32,
+}
+
+impl<D: Parser + Seeker> Frames<'_, D> {
+    fn advance(&mut self) -> Result<Frame> {
+        ensure!(
+            self.size >= 8,
+            ParseProblem::InvalidData("THP frame too small", Location::current())
+        );
+        ensure!(
+            self.size <= self.max_size,
+            ParseProblem::LimitExceeded(
+                "THP frame size",
+                self.size as usize,
+                self.max_size as usize,
+                Location::current()
+            )
+        );
+
+        let frame = Frame {
+            offset: self.offset,
+            size:   self.size,
+        };
+        if self.remaining > 0 {
+            self.reader.goto(self.offset)?;
+            self.size = self.reader.bu32()?;
+            self.offset += frame.size as u64;
+        }
+        Ok(frame)
+    }
+}
+
+impl<D: Parser + Seeker> Iterator for Frames<'_, D> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        let frame = self.advance();
+        if frame.is_err() {
+            self.remaining = 0;
+        }
+        Some(frame)
+    }
+}
@@ -0,0 +1,246 @@
+//! U8 archive (`.arc`) reader.
+//!
+//! U8 archives are used by GameCube and Wii games next to
+//! [RARC][`crate::rarc`] archives. The archive is a flat table of nodes, each
+//! either a file or a directory, followed by a string table with the node
+//! names. A directory node stores the index of its parent and the index one
+//! past its last descendant, so the tree is given by the order of the nodes.
+//! The first node is the root directory and its end index is the total number
+//! of nodes.
+//!
+//! # Examples
+//!
+//! Compressed archives can be read by wrapping the input in a
+//! [`Yaz0Reader`][`crate::Yaz0Reader`]:
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! use picori::u8_archive::Node;
+//!
+//! fn main() -> Result<()> {
+//!     let file = File::open("archive.arc")?;
+//!     let file = picori::Yaz0Reader::new(file)?;
+//!     let reader = picori::U8Reader::new(file)?;
+//!     for node in reader.nodes() {
+//!         if let Node::File { name, size, .. } = node {
+//!             println!("{name}: {size:#x} bytes");
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::panic::Location;
+
+use crate::error::ParseProblem;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, StringTable};
+use crate::{Ascii, Result};
+
+/// Magic at the start of every U8 archive.
+const MAGIC: u32 = 0x55AA_382D;
+
+/// Size of a node in the node table.
+const NODE_SIZE: u64 = 0x0C;
+
+#[derive(Debug, Clone)]
+enum U8Entry {
+    File {
+        /// Name of the file.
+        name:   String,
+        /// Offset of the file in the reader.
+        offset: u64,
+        /// Size of the file.
+        size:   u32,
+    },
+    Directory {
+        /// Name of the directory.
+        name: String,
+        /// Index one past the last node in the directory.
+        end:  u32,
+    },
+}
+
+/// Reader for U8 archives.
+pub struct U8Reader<Reader> {
+    reader:     Reader,
+    entries:    Vec<U8Entry>,
+    data_range: std::ops::Range<u64>,
+}
+
+impl<Reader: Parser + Seeker> U8Reader<Reader> {
+    /// Creates a new U8 reader.
+    pub fn new(mut reader: Reader) -> Result<Self> {
+        let start = reader.position()?;
+
+        let magic = reader.bu32()?;
+        let root_offset = reader.bu32()?;
+        let header_size = reader.bu32()?;
+        let _data_offset = reader.bu32()?;
+
+        ensure!(
+            magic == MAGIC,
+            ParseProblem::InvalidMagic("invalid U8 magic", Location::current())
+        );
+
+        let node_base = start + root_offset as u64;
+        reader.goto(node_base)?;
+        let root_type = reader.u8()?;
+        let _ = reader.u8_array::<3>()?;
+        let _ = reader.bu32()?;
+        let node_count = reader.bu32()?;
+
+        ensure!(
+            root_type == 1,
+            ParseProblem::InvalidHeader("root node is not a directory", Location::current())
+        );
+
+        ensure!(
+            node_count > 0 && node_count < 0x100000,
+            ParseProblem::InvalidHeader("invalid node count", Location::current())
+        );
+
+        let node_table_size = node_count as u64 * NODE_SIZE;
+        ensure!(
+            node_table_size <= header_size as u64,
+            ParseProblem::InvalidHeader("node table larger than header", Location::current())
+        );
+
+        let string_table_size = (header_size as u64 - node_table_size) as usize;
+        reader.goto(node_base + node_table_size)?;
+        let string_table = reader.read_as_vec_max(string_table_size, 0x1000000)?;
+        let string_table = StringTable::new(string_table);
+
+        let stream_end = reader.seek(std::io::SeekFrom::End(0))?;
+        let mut data_range = stream_end..start;
+        let mut entries = Vec::with_capacity(node_count as usize);
+        reader.goto(node_base)?;
+        for i in 0..node_count {
+            let kind = reader.u8()?;
+            let name_offset = reader.bu24()?;
+            let offset = reader.bu32()?;
+            let size = reader.bu32()?;
+            let name = if i == 0 {
+                String::new()
+            } else {
+                string_table.get::<Ascii>(name_offset)?
+            };
+
+            match kind {
+                0 => {
+                    let offset = start + offset as u64;
+                    ensure!(
+                        offset + size as u64 <= stream_end,
+                        ParseProblem::InvalidRange(
+                            "file data exceeds archive length",
+                            Location::current()
+                        )
+                    );
+                    data_range.start = data_range.start.min(offset);
+                    data_range.end = data_range.end.max(offset + size as u64);
+                    entries.push(U8Entry::File { name, offset, size });
+                },
+                1 => {
+                    ensure!(
+                        size > i && size <= node_count,
+                        ParseProblem::InvalidData(
+                            "directory end index out of bounds",
+                            Location::current()
+                        )
+                    );
+                    ensure!(
+                        i == 0 || offset < i,
+                        ParseProblem::InvalidData(
+                            "parent directory index out of bounds",
+                            Location::current()
+                        )
+                    );
+                    entries.push(U8Entry::Directory { name, end: size });
+                },
+                _ => {
+                    return Err(
+                        ParseProblem::InvalidData("invalid node type", Location::current()).into(),
+                    )
+                },
+            }
+        }
+
+        Ok(Self {
+            reader,
+            entries,
+            data_range,
+        })
+    }
+
+    /// Get the data for a file. Returns [`ParseProblem::InvalidRange`] if the
+    /// file is not within the file data of the archive.
+    pub fn file_data(&mut self, offset: u64, size: u32) -> Result<Vec<u8>> {
+        ensure!(
+            offset >= self.data_range.start && offset + size as u64 <= self.data_range.end,
+            ParseProblem::InvalidRange("file outside of archive data", Location::current())
+        );
+
+        self.reader.goto(offset)?;
+        self.reader.read_as_vec(size as usize)
+    }
+
+    /// Get a iterator over the nodes in the U8 archive. The root directory
+    /// has an empty name.
+    pub fn nodes(&self) -> Nodes<'_, Reader> {
+        Nodes {
+            parent: self,
+            index:  0,
+            stack:  Vec::new(),
+        }
+    }
+}
+
+/// A node in an U8 archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// A directory that has been entered.
+    DirectoryBegin { name: String },
+    /// A directory that has been exited.
+    DirectoryEnd { name: String },
+    /// A file in the current directory.
+    File {
+        name:   String,
+        offset: u64,
+        size:   u32,
+    },
+}
+
+/// An iterator over the nodes in an U8 archive.
+pub struct Nodes<'parent, Reader> {
+    parent: &'parent U8Reader<Reader>,
+    index:  u32,
+    stack:  Vec<(u32, &'parent str)>,
+}
+
+impl<T: Parser + Seeker> Iterator for Nodes<'_, T> {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((end, name)) = self.stack.last() {
+            if *end <= self.index {
+                let name = name.to_string();
+                self.stack.pop();
+                return Some(Node::DirectoryEnd { name });
+            }
+        }
+
+        let entry = self.parent.entries.get(self.index as usize)?;
+        self.index += 1;
+        match entry {
+            U8Entry::File { name, offset, size } => Some(Node::File {
+                name:   name.clone(),
+                offset: *offset,
+                size:   *size,
+            }),
+            U8Entry::Directory { name, end } => {
+                self.stack.push((*end, name));
+                Some(Node::DirectoryBegin { name: name.clone() })
+            },
+        }
+    }
+}
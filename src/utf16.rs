@@ -0,0 +1,284 @@
+//! [UTF-16][`Utf16Be`] encoding.
+//!
+//! UTF-16 encodes every Unicode code point as one or two 16-bit code units.
+//! Code points above `U+FFFF` are split into a surrogate pair, a high
+//! surrogate (`0xD800..=0xDBFF`) followed by a low surrogate
+//! (`0xDC00..=0xDFFF`). [`Utf16Be`] reads the code units as big endian, which
+//! is used by Wii BMG files and banners, and [`Utf16Le`] as little endian.
+//!
+//! A surrogate that isn't part of a pair is an error. The `first` variants
+//! stop at the first `U+0000` code unit.
+
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+
+use crate::error::DecodingProblem::*;
+use crate::error::EncodingProblem;
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
+use crate::Result;
+
+/// [UTF-16][`crate::utf16`] big endian encoding.
+pub struct Utf16Be {}
+
+/// [UTF-16][`crate::utf16`] little endian encoding.
+pub struct Utf16Le {}
+
+/// A iterator decoder for the [`Utf16Be`] and [`Utf16Le`] encodings.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:       <I as IntoIterator>::IntoIter,
+    big_endian: bool,
+    position:   usize,
+    pending:    Option<(u16, usize)>,
+    _marker:    PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I, big_endian: bool) -> Decoder<'x, I> {
+        Decoder {
+            iter: iter.into_iter(),
+            big_endian,
+            position: 0,
+            pending: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Decode lossily. Instead of yielding an error, lone surrogates are
+    /// replaced with `U+FFFD` (replacement character) and decoding continues
+    /// with the next code unit.
+    pub fn lossy(self) -> core::iter::Map<Self, fn(Result<char>) -> char> {
+        self.map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Next code unit and its byte index. A trailing odd byte is an error.
+    fn next_unit(&mut self) -> Option<Result<(u16, usize)>> {
+        if let Some(unit) = self.pending.take() {
+            return Some(Ok(unit));
+        }
+
+        let index = self.position;
+        let first = *self.iter.next()?.borrow();
+        let Some(second) = self.iter.next() else {
+            self.position += 1;
+            return Some(Err(UnexpectedEndOfData(Location::current()).into()));
+        };
+        let bytes = [first, *second.borrow()];
+        self.position += 2;
+        Some(Ok((
+            if self.big_endian {
+                u16::from_be_bytes(bytes)
+            } else {
+                u16::from_le_bytes(bytes)
+            },
+            index,
+        )))
+    }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (unit, index) = match self.next_unit()? {
+            Ok(x) => x,
+            Err(e) => return Some(Err(e)),
+        };
+
+        Some(match unit {
+            0xD800..=0xDBFF => match self.next_unit() {
+                Some(Ok((low @ 0xDC00..=0xDFFF, _))) => {
+                    let c = 0x10000 + (((unit as u32) - 0xD800) << 10) + (low as u32 - 0xDC00);
+                    Ok(char::from_u32(c).unwrap())
+                },
+                Some(Ok(next)) => {
+                    self.pending = Some(next);
+                    Err(InvalidCodePoint(unit as usize, index, Location::current()).into())
+                },
+                _ => Err(InvalidCodePoint(unit as usize, index, Location::current()).into()),
+            },
+            0xDC00..=0xDFFF => {
+                Err(InvalidCodePoint(unit as usize, index, Location::current()).into())
+            },
+            _ => Ok(char::from_u32(unit as u32).unwrap()),
+        })
+    }
+}
+
+macro_rules! utf16_encoding {
+    ($name:ident, $big_endian:expr, $to_bytes:ident) => {
+        impl $name {
+            /// Create an iterator that decodes the given iterator of bytes into
+            /// characters.
+            pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+            where
+                I: IntoIterator,
+                I::Item: Borrow<u8> + Sized,
+            {
+                Decoder::new(iter, $big_endian)
+            }
+
+            /// Decode all bytes into a string. Will continue passed NULL
+            /// characters and only stop at the end of the iterator or if an
+            /// decoding error occurs.
+            pub fn all<I>(iter: I) -> Result<String>
+            where
+                I: IntoIterator,
+                I::Item: Borrow<u8> + Sized,
+            {
+                Self::iter(iter).collect()
+            }
+
+            /// Decode the first string (until a NULL character is reached)
+            /// from the given iterator.
+            pub fn first<I>(iter: I) -> Result<String>
+            where
+                I: IntoIterator,
+                I::Item: Borrow<u8> + Sized,
+            {
+                Self::iter(iter)
+                    .take_while(|c| match c {
+                        Ok(c) => *c != 0 as char,
+                        Err(_) => true,
+                    })
+                    .collect()
+            }
+        }
+
+        impl ParseStringEncoding for $name {
+            fn parse_str<I>(iter: I) -> Result<String>
+            where
+                I: IntoIterator,
+                I::Item: Borrow<u8> + Sized,
+            {
+                Self::first(iter)
+            }
+
+            fn parse_all<I>(iter: I) -> Result<String>
+            where
+                I: IntoIterator,
+                I::Item: Borrow<u8> + Sized,
+            {
+                Self::all(iter)
+            }
+
+            fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+                let mut i = 0;
+                for unit in data.encode_utf16() {
+                    if i + 2 > buffer.len() {
+                        return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+                    }
+                    buffer[i..i + 2].copy_from_slice(&unit.$to_bytes());
+                    i += 2;
+                }
+                Ok(i)
+            }
+
+            fn from_binary(reader: &mut impl Parser) -> Result<String> {
+                Self::first(parser_bytes(reader))
+            }
+        }
+    };
+}
+
+utf16_encoding!(Utf16Be, true, to_be_bytes);
+utf16_encoding!(Utf16Le, false, to_le_bytes);
+
+/// Extension trait for iterators of bytes and adds the helper functions
+/// [`IteratorExt::utf16_be`] and [`IteratorExt::utf16_le`] for decoding as
+/// [UTF-16][`crate::utf16`] strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [UTF-16 big endian][`Utf16Be`].
+    fn utf16_be<'b>(self) -> Decoder<'b, Self> { Decoder::new(self, true) }
+
+    /// Decode self iterator of bytes as [UTF-16 little endian][`Utf16Le`].
+    fn utf16_le<'b>(self) -> Decoder<'b, Self> { Decoder::new(self, false) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn parse_str() {
+        let data = b"\0h\0i\xD8\x3D\xDE\x00\0\0\0x";
+        assert_eq!(Utf16Be::parse_str(data).unwrap(), "hi\u{1F600}");
+        assert_eq!(Utf16Be::parse_all(data).unwrap(), "hi\u{1F600}\0x");
+        let data = b"h\0i\0=\xD8\x00\xDE\0\0x\0";
+        assert_eq!(Utf16Le::parse_str(data).unwrap(), "hi\u{1F600}");
+    }
+
+    #[test]
+    fn lone_surrogate() {
+        assert!(matches!(
+            Utf16Be::parse_all(b"\0a\xDC\x00"),
+            Err(Error::Decoding(InvalidCodePoint(0xDC00, 2, _)))
+        ));
+        assert!(matches!(
+            Utf16Be::parse_all(b"\xD8\x00\0a"),
+            Err(Error::Decoding(InvalidCodePoint(0xD800, 0, _)))
+        ));
+        let lossy = Utf16Be::iter(b"\xD8\x00\0a").lossy().collect::<String>();
+        assert_eq!(lossy, "\u{FFFD}a");
+        assert!(Utf16Le::parse_all(b"a\0b").is_err());
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 6];
+        assert_eq!(Utf16Be::write_str("a\u{1F600}", &mut buffer).unwrap(), 6);
+        assert_eq!(&buffer, b"\0a\xD8\x3D\xDE\x00");
+        assert_eq!(Utf16Le::write_str("ab", &mut buffer).unwrap(), 4);
+        assert_eq!(&buffer[..4], b"a\0b\0");
+        assert!(Utf16Be::write_str("abcd", &mut buffer).is_err());
+    }
+
+    #[test]
+    fn parser_str() {
+        use crate::{Parser, SliceParser};
+
+        let mut parser = SliceParser::new(b"\0h\0i\0\0\0x");
+        assert_eq!(parser.str::<Utf16Be>().unwrap(), "hi");
+        assert_eq!(parser.bu16().unwrap(), 0x78);
+        let mut parser = SliceParser::new(b"h\0i\0\0\0x\0");
+        assert_eq!(parser.str::<Utf16Le>().unwrap(), "hi");
+        assert_eq!(parser.str::<Utf16Le>().unwrap(), "x");
+
+        let mut buffer = [0u8; 10];
+        Utf16Be::write_str("a\u{1F600}", &mut buffer).unwrap();
+        let mut parser = SliceParser::new(&buffer);
+        assert_eq!(parser.str::<Utf16Be>().unwrap(), "a\u{1F600}");
+        Utf16Le::write_str("a\u{1F600}", &mut buffer).unwrap();
+        let mut parser = SliceParser::new(&buffer);
+        assert_eq!(parser.str::<Utf16Le>().unwrap(), "a\u{1F600}");
+    }
+}
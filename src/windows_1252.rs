@@ -0,0 +1,208 @@
+//! [Windows-1252][`Windows1252`] encoding.
+//!
+//! [Windows-1252][`Windows1252`] (CP1252) is a single-byte encoding that is
+//! identical to [Latin-1][`crate::Latin1`] except for the range `0x80` to
+//! `0x9F`, which is used for typographic characters such as `€`, `…` and
+//! curly quotes instead of C1 control characters. It is used by BMG message
+//! files in PAL region games.
+//!
+//! The five bytes that are undefined in Windows-1252 (`0x81`, `0x8D`, `0x8F`,
+//! `0x90` and `0x9D`) are decoded to the C1 control character with the same
+//! value, so decoding never fails and every byte can be encoded again.
+
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+use core::panic::Location;
+
+use crate::error::EncodingProblem;
+use crate::helper::{parser_bytes, ParseStringEncoding, Parser, ProblemLocation};
+use crate::Result;
+
+/// Characters for the bytes `0x80` to `0x9F`.
+const HIGH_TABLE: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// [Windows-1252][`Windows1252`] encoding.
+pub struct Windows1252 {}
+
+/// A iterator decoder for the [`Windows1252`] encoding.
+pub struct Decoder<'x, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    iter:    <I as IntoIterator>::IntoIter,
+    _marker: PhantomData<&'x ()>,
+}
+
+impl<I> Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    fn new<'x>(iter: I) -> Decoder<'x, I> {
+        Decoder {
+            iter:    iter.into_iter(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Decode a single byte.
+    pub fn decode_byte(byte: u8) -> char {
+        match byte {
+            0x80..=0x9F => HIGH_TABLE[(byte - 0x80) as usize],
+            _ => byte as char,
+        }
+    }
+}
+
+impl<I> Iterator for Decoder<'_, I>
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|byte| Ok(Self::decode_byte(*byte.borrow())))
+    }
+}
+
+impl Windows1252 {
+    /// Create an iterator that decodes the given iterator of bytes into
+    /// characters.
+    pub fn iter<'iter, I>(iter: I) -> Decoder<'iter, I>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Decoder::new(iter)
+    }
+
+    /// Decode all bytes into a string. Will continue passed NULL bytes and only
+    /// stop at the end of the iterator.
+    pub fn all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter).collect()
+    }
+
+    /// Decode the first string (until a NULL character is reached) from the
+    /// given iterator.
+    pub fn first<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::iter(iter)
+            .take_while(|c| match c {
+                Ok(c) => *c != 0 as char,
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Encode a single character, or [`None`] if the character can't be
+    /// represented.
+    pub fn encode_char(c: char) -> Option<u8> {
+        match c as u32 {
+            0x00..=0x7F | 0xA0..=0xFF => Some(c as u8),
+            _ => HIGH_TABLE
+                .iter()
+                .position(|x| *x == c)
+                .map(|i| 0x80 + i as u8),
+        }
+    }
+}
+
+/// Extension trait for iterators of bytes and adds the helper function
+/// [`IteratorExt::windows_1252`] for decoding as
+/// [Windows-1252][`Windows1252`] strings.
+pub trait IteratorExt
+where
+    Self: IntoIterator + Sized,
+    Self::Item: Borrow<u8> + Sized,
+{
+    /// Decode self iterator of bytes as [Windows-1252][`Windows1252`].
+    fn windows_1252<'b>(self) -> Decoder<'b, Self> { Decoder::new(self) }
+}
+
+impl<I> IteratorExt for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<u8> + Sized,
+{
+}
+
+impl ParseStringEncoding for Windows1252 {
+    fn parse_str<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::first(iter)
+    }
+
+    fn parse_all<I>(iter: I) -> Result<String>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<u8> + Sized,
+    {
+        Self::all(iter)
+    }
+
+    fn write_str(data: &str, buffer: &mut [u8]) -> Result<usize> {
+        let mut i = 0;
+        for c in data.chars() {
+            if i >= buffer.len() {
+                return Err(EncodingProblem::BufferTooSmall(Location::current()).into());
+            }
+            let Some(byte) = Self::encode_char(c) else {
+                return Err(
+                    EncodingProblem::UnableToEncodeCodePoint(c, Location::current()).into(),
+                );
+            };
+            buffer[i] = byte;
+            i += 1;
+        }
+        Ok(i)
+    }
+
+    fn from_binary(reader: &mut impl Parser) -> Result<String> {
+        Self::first(parser_bytes(reader))
+    }
+}
+
+// -------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_str() {
+        let data = b"\x80 caf\xe9\x85\0def";
+        assert_eq!(Windows1252::parse_str(data).unwrap(), "€ café…");
+    }
+
+    #[test]
+    fn write_str() {
+        let mut buffer = [0u8; 4];
+        assert_eq!(Windows1252::write_str("’é€", &mut buffer).unwrap(), 3);
+        assert_eq!(&buffer[..3], b"\x92\xe9\x80");
+        assert!(Windows1252::write_str("\u{100}", &mut buffer).is_err());
+        assert!(Windows1252::write_str("\u{80}", &mut buffer).is_err());
+        assert!(Windows1252::write_str("abcde", &mut buffer).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Yay0 decompression.
+//!
+//! Yay0 is a variant of [Yaz0][`crate::yaz0`] that stores the group headers,
+//! the back-references and the literal bytes in three separate streams. Only
+//! decompression is supported.
+//!
+//! ## Decompression
+//!
+//! ```no_run
+//! # use std::fs::File;
+//! # use picori::Result;
+//! fn main() -> Result<()> {
+//!     let mut file = File::open("data.yay0")?;
+//!     let decompressed = picori::yay0::decompress(&mut file)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! To read Yay0 or Yaz0 compressed data without knowing which compression is
+//! used, see [`crate::compression::AutoDecompressReader`].
+
+use std::panic::Location;
+
+use crate::error::DecompressionProblem::*;
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
+use crate::Result;
+
+/// Yay0 header.
+pub struct Header {
+    /// Yay0 magic (0x59617930).
+    pub magic:             u32,
+    /// Size of decompressed data.
+    pub decompressed_size: u32,
+    /// Offset of the back-reference stream, relative to the header.
+    pub link_offset:       u32,
+    /// Offset of the literal byte stream, relative to the header.
+    pub chunk_offset:      u32,
+}
+
+impl Header {
+    /// Reads a Yay0 header from a reader.
+    pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
+        Ok(Header {
+            magic:             input.bu32()?,
+            decompressed_size: input.bu32()?,
+            link_offset:       input.bu32()?,
+            chunk_offset:      input.bu32()?,
+        })
+    }
+
+    /// Checks if the header is valid.
+    pub fn is_valid(&self) -> bool { self.magic == 0x59617930 }
+}
+
+/// Check if the given data is compressed with Yay0 by looking for the Yay0
+/// magic. The stream position is restored.
+pub fn is_yay0<D: Parser + Seeker>(input: &mut D) -> bool {
+    let Ok(base) = input.position() else {
+        return false;
+    };
+
+    let is_compressed = Header::from_binary(input)
+        .map(|x| x.is_valid())
+        .unwrap_or(false);
+    input.goto(base).is_ok() && is_compressed
+}
+
+/// Decompresses the Yay0 data, including the header, at the current position
+/// of `input` into a new allocated [`Vec`]. Only the part of `input` that can
+/// be referenced by the header is read.
+pub fn decompress<D: Parser + Seeker>(input: &mut D) -> Result<Vec<u8>> {
+    let base = input.position()?;
+    let header = Header::from_binary(input)?;
+    ensure!(
+        header.is_valid(),
+        InvalidHeader("Invalid magic", Location::current())
+    );
+
+    // no stream uses more bytes than it produces output, so none of them
+    // extends more than `size` bytes past its offset
+    let size = header.decompressed_size as usize;
+    let needed = [0x10, header.link_offset, header.chunk_offset]
+        .into_iter()
+        .map(|x| x as u64 + size as u64)
+        .max()
+        .unwrap_or_default();
+    let end = input.seek(std::io::SeekFrom::End(0))?;
+    input.goto(base)?;
+    let data = input.read_as_vec(end.saturating_sub(base).min(needed) as usize)?;
+
+    let mut output = Vec::with_capacity(size.min(0x2000000));
+    let mut mask_position = 0x10;
+    let mut link_position = header.link_offset as usize;
+    let mut chunk_position = header.chunk_offset as usize;
+    let mut mask = 0_u32;
+    let mut mask_bits = 0;
+
+    let u8_at = |position: usize| -> Result<u8> {
+        match data.get(position) {
+            Some(x) => Ok(*x),
+            None => Err(UnexpectedEndOfData(Location::current()).into()),
+        }
+    };
+
+    while output.len() < size {
+        if mask_bits == 0 {
+            let bytes = data.get(mask_position..mask_position + 4);
+            ensure!(bytes.is_some(), UnexpectedEndOfData(Location::current()));
+            mask = u32::from_be_bytes(bytes.unwrap().try_into().unwrap());
+            mask_position += 4;
+            mask_bits = 32;
+        }
+
+        if mask & 0x8000_0000 != 0 {
+            output.push(u8_at(chunk_position)?);
+            chunk_position += 1;
+        } else {
+            let link = ((u8_at(link_position)? as usize) << 8) | u8_at(link_position + 1)? as usize;
+            link_position += 2;
+            let distance = (link & 0xFFF) + 1;
+            let length = match link >> 12 {
+                0 => {
+                    let length = u8_at(chunk_position)? as usize + 0x12;
+                    chunk_position += 1;
+                    length
+                },
+                length => length + 2,
+            };
+
+            ensure!(
+                distance <= output.len(),
+                InvalidData("back-reference before start", Location::current())
+            );
+            let start = output.len() - distance;
+            for n in 0..length.min(size - output.len()) {
+                output.push(output[start + n]);
+            }
+        }
+
+        mask <<= 1;
+        mask_bits -= 1;
+    }
+
+    Ok(output)
+}
@@ -2,7 +2,19 @@
 //!
 //! ## Compression
 //!
-//! Compression is not yet supported.
+//! Compress data with [`compress`], the [`CompressionLevel`] decides how hard
+//! the compressor will try to find back-references:
+//!
+//! ```no_run
+//! # use picori::Result;
+//! # use picori::yaz0::CompressionLevel;
+//! fn main() -> Result<()> {
+//!     let data = std::fs::read("data.bin")?;
+//!     let compressed = picori::yaz0::compress(&data, CompressionLevel::Best)?;
+//!     std::fs::write("data.yaz0", compressed)?;
+//!     Ok(())
+//! }
+//! ```
 //!
 //! ## Decompression
 //!
@@ -19,19 +31,27 @@
 //! }
 //! ```
 //!
-//! Alternatively, you can use the [`decompress`] or [`decompress_into`] function to decompress:
+//! For large files, [`Yaz0Reader::streaming`] decompresses on demand instead
+//! of decompressing the whole file up front, at the cost of only supporting
+//! forward seeks.
+//!
+//! Alternatively, you can use the [`decompress_all`] function to read the
+//! header and decompress in one call:
 //!
 //! ```no_run
 //! # use std::fs::File;
 //! # use picori::Result;
 //! fn main() -> Result<()> {
 //!     let mut file = File::open("data.yaz0")?;
-//!     let decompressed_size = picori::yaz0::Header::decompressed_size(&mut file)?;
-//!     let decompressed = picori::yaz0::decompress(&mut file, decompressed_size)?;
+//!     let decompressed = picori::yaz0::decompress_all(&mut file)?;
 //!     Ok(())
 //! }
 //! ```
 //!
+//! [`decompress`] and [`decompress_into`] only decompress the data after the
+//! header, the decompressed size must be read first with
+//! [`Header::decompressed_size`].
+//!
 //! ## References
 //!
 //! [Yaz0](http://www.amnoid.de/gc/yaz0.txt) - Implementation of the Yaz0 decompression is based
@@ -40,9 +60,19 @@
 use std::io::{Read, Seek};
 use std::panic::Location;
 
+use crate::error::{CompressionProblem, DecodingProblem};
 use crate::error::DecompressionProblem::*;
-use crate::helper::{ensure, Parser, ProblemLocation, Seeker};
-use crate::{Reader, Result};
+use crate::helper::{ensure, Parser, ProblemLocation, Seeker, SliceParser, Writer};
+use crate::{Error, Reader, Result};
+
+/// Maximum distance of a back-reference.
+const WINDOW_SIZE: usize = 0x1000;
+
+/// Minimum length of a back-reference.
+const MIN_MATCH_LENGTH: usize = 3;
+
+/// Maximum length of a back-reference.
+const MAX_MATCH_LENGTH: usize = 0x111;
 
 /// Yaz0 header.
 pub struct Header {
@@ -55,6 +85,16 @@ pub struct Header {
 }
 
 impl Header {
+    /// Creates a valid Yaz0 header for `decompressed_size` bytes of data.
+    pub fn new(decompressed_size: u32) -> Header {
+        Header {
+            magic: 0x59617A30,
+            decompressed_size,
+            _reserved0: 0,
+            _reserved1: 0,
+        }
+    }
+
     /// Reads a Yaz0 header from a reader.
     pub fn from_binary<D: Parser>(input: &mut D) -> Result<Header> {
         Ok(Header {
@@ -70,6 +110,16 @@ impl Header {
         self.magic == 0x59617A30
     }
 
+    /// Writes the 16 byte Yaz0 header, the magic and the decompressed size
+    /// followed by two reserved zero words.
+    pub fn to_binary<W: Writer>(&self, output: &mut W) -> Result<()> {
+        output.bu32(self.magic)?;
+        output.bu32(self.decompressed_size)?;
+        output.bu32(0)?;
+        output.bu32(0)?;
+        Ok(())
+    }
+
     pub fn decompressed_size(input: &mut impl Parser) -> Result<usize> {
         let header = Header::from_binary(input)?;
         ensure!(
@@ -81,11 +131,117 @@ impl Header {
 }
 
 /// Decompresses a Yaz0 compressed file.
+///
+/// [`Yaz0Reader::new`] decompresses the whole file up front and supports
+/// random access. [`Yaz0Reader::streaming`] instead decompresses on demand
+/// while reading, keeping only the last 0x1000 bytes of output for
+/// back-references, and only supports seeking forward.
 pub struct Yaz0Reader<D: Parser + Seeker> {
     reader: D,
     decompressed: Vec<u8>,
     position: usize,
     transparent: bool,
+    compressed_size: u64,
+    stream: Option<Stream>,
+}
+
+/// State of a [`Yaz0Reader`] in streaming mode.
+struct Stream {
+    /// Decompressed size from the header.
+    size:      usize,
+    /// Number of bytes decompressed so far, i.e., the current position.
+    produced:  usize,
+    /// Number of input bytes consumed so far, including the header.
+    consumed:  u64,
+    /// The last [`WINDOW_SIZE`] decompressed bytes.
+    window:    Vec<u8>,
+    /// Current group header and the number of bits left in it.
+    code:      u8,
+    code_bits: u32,
+    /// Distance and remaining length of the current back-reference.
+    copy:      (usize, usize),
+}
+
+impl Stream {
+    fn new(size: usize) -> Self {
+        Self {
+            size,
+            produced: 0,
+            consumed: 0x10,
+            window: vec![0; WINDOW_SIZE],
+            code: 0,
+            code_bits: 0,
+            copy: (0, 0),
+        }
+    }
+
+    fn u8<D: Parser>(&mut self, input: &mut D) -> Result<u8> {
+        self.consumed += 1;
+        input.u8()
+    }
+
+    fn next<D: Parser>(&mut self, input: &mut D) -> Result<u8> {
+        if self.copy.1 == 0 {
+            if self.code_bits == 0 {
+                self.code = self.u8(input)?;
+                self.code_bits = 8;
+            }
+
+            let literal = self.code & 0x80 != 0;
+            self.code <<= 1;
+            self.code_bits -= 1;
+
+            if literal {
+                let byte = self.u8(input)?;
+                self.push(byte);
+                return Ok(byte);
+            }
+
+            let byte0 = self.u8(input)?;
+            let byte1 = self.u8(input)?;
+            let offset = (((byte0 & 0xf) as usize) << 8) | (byte1 as usize);
+            let length = match byte0 >> 4 {
+                0 => (self.u8(input)? as usize) + 0x12,
+                length => length as usize + 2,
+            };
+
+            ensure!(
+                offset < self.produced,
+                InvalidData("back-reference before start", Location::current())
+            );
+            self.copy = (offset + 1, length);
+        }
+
+        let byte = self.window[(self.produced - self.copy.0) % WINDOW_SIZE];
+        self.copy.1 -= 1;
+        self.push(byte);
+        Ok(byte)
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.window[self.produced % WINDOW_SIZE] = byte;
+        self.produced += 1;
+    }
+
+    fn read<D: Parser>(&mut self, input: &mut D, buf: &mut [u8]) -> Result<usize> {
+        let n = buf.len().min(self.size - self.produced);
+        for x in buf.iter_mut().take(n) {
+            *x = self.next(input)?;
+        }
+        Ok(n)
+    }
+
+    fn skip<D: Parser>(&mut self, input: &mut D, target: usize) -> Result<()> {
+        while self.produced < target.min(self.size) {
+            self.next(input)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'data> Yaz0Reader<SliceParser<'data>> {
+    /// Creates a new Yaz0 reader over in-memory data, see [`Yaz0Reader::new`].
+    pub fn from_bytes(data: &'data [u8]) -> Result<Self> { Self::new(SliceParser::new(data)) }
 }
 
 impl<D: Parser + Seeker> Yaz0Reader<D> {
@@ -96,26 +252,82 @@ impl<D: Parser + Seeker> Yaz0Reader<D> {
         if header.as_ref().map(|x| x.is_valid()).unwrap_or(false) {
             let header = header.unwrap();
             let data = decompress(&mut reader, header.decompressed_size as usize)?;
+            let compressed_size = reader.position()? - base;
             Ok(Yaz0Reader {
                 reader,
                 decompressed: data,
                 position: 0,
                 transparent: false,
+                compressed_size,
+                stream: None,
             })
         } else {
-            reader.goto(base)?;
-            Ok(Yaz0Reader {
+            Self::transparent(reader, base)
+        }
+    }
+
+    /// Creates a new Yaz0 reader that decompresses on demand while reading,
+    /// without buffering the whole output. Only forward seeks are supported,
+    /// seeking backwards returns an error. As with [`Yaz0Reader::new`], input
+    /// that isn't Yaz0 compressed is passed through as is.
+    pub fn streaming(mut reader: D) -> Result<Yaz0Reader<D>> {
+        let base = reader.position()?;
+        let header = Header::from_binary(&mut reader);
+        match header {
+            Ok(header) if header.is_valid() => Ok(Yaz0Reader {
                 reader,
                 decompressed: Vec::new(),
                 position: 0,
-                transparent: true,
-            })
+                transparent: false,
+                compressed_size: 0,
+                stream: Some(Stream::new(header.decompressed_size as usize)),
+            }),
+            _ => Self::transparent(reader, base),
         }
     }
 
+    fn transparent(mut reader: D, base: u64) -> Result<Yaz0Reader<D>> {
+        let end = reader.seek(std::io::SeekFrom::End(0))?;
+        reader.goto(base)?;
+        Ok(Yaz0Reader {
+            reader,
+            decompressed: Vec::new(),
+            position: 0,
+            transparent: true,
+            compressed_size: end.saturating_sub(base),
+            stream: None,
+        })
+    }
+
     /// Decompressed size of the data.
     pub fn decompressed_size(&self) -> usize {
-        self.decompressed.len()
+        match &self.stream {
+            Some(stream) => stream.size,
+            None => self.decompressed.len(),
+        }
+    }
+
+    /// Size of the compressed input in bytes, including the Yaz0 header. If
+    /// the input isn't Yaz0 compressed, this is the size of the input from
+    /// the start position to the end of the stream. In streaming mode, this
+    /// is the number of input bytes consumed so far.
+    pub fn compressed_size(&self) -> u64 {
+        match &self.stream {
+            Some(stream) => stream.consumed,
+            None => self.compressed_size,
+        }
+    }
+
+    /// Ratio between the compressed and decompressed size, e.g., `0.25` if
+    /// the compressed data is a quarter of the decompressed size. If the
+    /// input isn't Yaz0 compressed, the ratio is `1.0`. In streaming mode,
+    /// the ratio is only final once all data has been read.
+    pub fn compression_ratio(&self) -> f32 {
+        if self.transparent || self.decompressed_size() == 0 {
+            1.0
+        } else {
+            self.compressed_size() as f32 / self.decompressed_size() as f32
+        }
     }
 }
 
@@ -123,6 +335,10 @@ impl<D: Parser + Seeker + Read> Read for Yaz0Reader<D> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         if self.transparent {
             self.reader.read(buf)
+        } else if let Some(stream) = self.stream.as_mut() {
+            stream
+                .read(&mut self.reader, buf)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
         } else {
             let size = buf.len();
             let mut n = 0;
@@ -141,10 +357,14 @@ impl<D: Parser + Seeker + Seek> Seek for Yaz0Reader<D> {
         if self.transparent {
             self.reader.seek(pos)
         } else {
+            let (current, size) = match &self.stream {
+                Some(stream) => (stream.produced, stream.size),
+                None => (self.position, self.decompressed.len()),
+            };
             let position = match pos {
                 std::io::SeekFrom::Start(n) => Ok(n as usize),
                 std::io::SeekFrom::Current(n) => {
-                    let absolute = self.position as i64 + n;
+                    let absolute = current as i64 + n;
                     if absolute < 0 {
                         Err(std::io::Error::new(
                             std::io::ErrorKind::InvalidInput,
@@ -155,7 +375,7 @@ impl<D: Parser + Seeker + Seek> Seek for Yaz0Reader<D> {
                     }
                 },
                 std::io::SeekFrom::End(n) => {
-                    let absolute = self.decompressed.len() as i64 + n;
+                    let absolute = size as i64 + n;
                     if absolute < 0 {
                         Err(std::io::Error::new(
                             std::io::ErrorKind::InvalidInput,
@@ -167,6 +387,20 @@ impl<D: Parser + Seeker + Seek> Seek for Yaz0Reader<D> {
                 },
             }?;
 
+            if let Some(stream) = self.stream.as_mut() {
+                if position < stream.produced {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        "backward seek in streaming mode",
+                    ));
+                }
+
+                stream
+                    .skip(&mut self.reader, position)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                return Ok(stream.produced as u64);
+            }
+
             if position > self.decompressed.len() {
                 self.position = self.decompressed.len();
             } else {
@@ -185,15 +419,24 @@ impl<D: Parser + Seeker> Parser for Yaz0Reader<D> {}
 /// Check if the given data is compressed with Yaz0 by looking for the Yaz0
 /// magic.
 pub fn is_yaz0<D: Parser + Seeker>(input: &mut D) -> bool {
-    let mut check = || -> Result<bool> {
-        let base = input.position()?;
-        let header = Header::from_binary(input)?;
-        let is_compressed = header.is_valid();
-        input.goto(base)?;
-        Ok(is_compressed)
+    let Ok(base) = input.position() else {
+        return false;
     };
 
-    check().unwrap_or(false)
+    let is_compressed = Header::from_binary(input)
+        .map(|x| x.is_valid())
+        .unwrap_or(false);
+    input.goto(base).is_ok() && is_compressed
+}
+
+/// Reads the Yaz0 header at the current position of `input` and decompresses
+/// the data into a new allocated [`Vec`].
+///
+/// Returns [`InvalidHeader`] if the input doesn't start with the Yaz0 magic.
+/// Unlike [`Yaz0Reader`], uncompressed input is not passed through.
+pub fn decompress_all<D: Parser + Seeker>(input: &mut D) -> Result<Vec<u8>> {
+    let decompressed_size = Header::decompressed_size(input)?;
+    decompress(input, decompressed_size)
 }
 
 /// Decompresses the data into a new allocated [`Vec`]. `decompressed_size` can be determined
@@ -204,37 +447,61 @@ pub fn decompress<D: Parser + Seeker>(input: &mut D, decompressed_size: usize) -
     Ok(output)
 }
 
-/// Decompresses the data into the given buffer. The buffer must be large
-/// enough to hold the decompressed data.
+/// Decompresses the data into the given buffer. The buffer must be exactly
+/// the size of the decompressed data.
+///
+/// Returns [`InvalidDecompressedSize`] if the stream and the size of
+/// `destination` disagree, i.e., the input ends before the buffer is full, a
+/// back-reference goes past the end of the buffer, or the stream continues
+/// after the buffer is full. The latter is detected by looking at the unused
+/// bits of the last group header, the input after the stream isn't looked at
+/// as Yaz0 data is often followed by unrelated data. The input isn't consumed
+/// past the stream.
 pub fn decompress_into<D: Parser + Seeker>(input: &mut D, destination: &mut [u8]) -> Result<()> {
-    let decompressed_size = destination.len();
-    let size = decompressed_size as usize;
+    let size = destination.len();
     let mut dest = 0;
     let mut code = 0;
     let mut code_bits = 0;
 
+    let next = |input: &mut D| -> Result<u8> {
+        let mut byte = [0u8; 1];
+        match input.read_exact_into(&mut byte) {
+            Ok(()) => Ok(byte[0]),
+            Err(Error::Decoding(DecodingProblem::UnexpectedEndOfData(_))) => {
+                Err(InvalidDecompressedSize(Location::current()).into())
+            },
+            Err(e) => Err(e),
+        }
+    };
+
     while dest < size {
         if code_bits == 0 {
-            code = input.u8()? as u32;
+            code = next(input)? as u32;
             code_bits = 8;
         }
 
         if code & 0x80 != 0 {
-            let byte = input.u8()?;
-            destination[dest] = byte;
+            destination[dest] = next(input)?;
             dest += 1;
         } else {
-            let byte0 = input.u8()?;
-            let byte1 = input.u8()?;
+            let byte0 = next(input)?;
+            let byte1 = next(input)?;
             let a = (byte0 & 0xf) as usize;
             let b = (byte0 >> 4) as usize;
             let offset = (a << 8) | (byte1 as usize);
             let length = match b {
-                0 => (input.u8()? as usize) + 0x12,
+                0 => (next(input)? as usize) + 0x12,
                 length => length + 2,
             };
 
-            ensure!(offset < dest, UnexpectedEndOfData(Location::current()));
+            ensure!(
+                offset < dest,
+                InvalidData("back-reference before start", Location::current())
+            );
+            ensure!(
+                length <= size - dest,
+                InvalidDecompressedSize(Location::current())
+            );
             let base = dest - (offset + 1);
             for n in 0..length {
                 destination[dest] = destination[base + n];
@@ -246,5 +513,159 @@ pub fn decompress_into<D: Parser + Seeker>(input: &mut D, destination: &mut [u8]
         code_bits -= 1;
     }
 
+    // unused bits of the last group header must not mark any literal bytes
+    let unused = (code & 0xFF) >> (8 - code_bits);
+    ensure!(
+        code_bits == 0 || unused == 0,
+        InvalidDecompressedSize(Location::current())
+    );
+
     Ok(())
 }
+
+/// Compression level used by [`compress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressionLevel {
+    /// No back-references are used, every byte is stored as is. The output
+    /// is a valid Yaz0 stream that is slightly larger than the input.
+    None,
+
+    /// Only the most recent occurrences of each 3-byte sequence are
+    /// considered when looking for back-references.
+    #[default]
+    Fast,
+
+    /// Every position in the 0x1000-byte window is considered when looking
+    /// for the longest back-reference (up to 0x111 bytes).
+    Best,
+}
+
+/// Hash chains over the previous [`WINDOW_SIZE`] positions, used to find
+/// back-references without comparing against every position in the window.
+struct MatchFinder {
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl MatchFinder {
+    fn new() -> Self {
+        Self {
+            head: vec![0; 1 << 15],
+            prev: vec![0; WINDOW_SIZE],
+        }
+    }
+
+    fn hash(data: &[u8], position: usize) -> usize {
+        let a = data[position] as usize;
+        let b = data[position + 1] as usize;
+        let c = data[position + 2] as usize;
+        ((a << 10) ^ (b << 5) ^ c) & 0x7FFF
+    }
+
+    fn insert(&mut self, data: &[u8], position: usize) {
+        if position + MIN_MATCH_LENGTH <= data.len() {
+            let hash = Self::hash(data, position);
+            self.prev[position % WINDOW_SIZE] = self.head[hash];
+            self.head[hash] = position + 1;
+        }
+    }
+
+    /// Find the longest back-reference for `position` by following at most
+    /// `max_candidates` links in the hash chain. Returns the distance and the
+    /// length of the match.
+    fn find(&self, data: &[u8], position: usize, max_candidates: usize) -> Option<(usize, usize)> {
+        if position + MIN_MATCH_LENGTH > data.len() {
+            return None;
+        }
+
+        let max_length = MAX_MATCH_LENGTH.min(data.len() - position);
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = self.head[Self::hash(data, position)];
+        for _ in 0..max_candidates {
+            if candidate == 0 {
+                break;
+            }
+
+            let start = candidate - 1;
+            let distance = position - start;
+            if distance > WINDOW_SIZE {
+                break;
+            }
+
+            let length = data[start..]
+                .iter()
+                .zip(data[position..position + max_length].iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length >= MIN_MATCH_LENGTH && best.is_none_or(|x| length > x.1) {
+                best = Some((distance, length));
+                if length == max_length {
+                    break;
+                }
+            }
+
+            candidate = self.prev[start % WINDOW_SIZE];
+        }
+
+        best
+    }
+}
+
+/// Compresses `data` into a new allocated [`Vec`] containing the Yaz0 header
+/// followed by the compressed data. The output can be decompressed with
+/// [`decompress`] or [`Yaz0Reader`].
+pub fn compress(data: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
+    ensure!(
+        data.len() <= u32::MAX as usize,
+        CompressionProblem::InputTooLarge(data.len(), Location::current())
+    );
+
+    let max_candidates = match level {
+        CompressionLevel::None => 0,
+        CompressionLevel::Fast => 16,
+        CompressionLevel::Best => WINDOW_SIZE,
+    };
+
+    let mut output = Vec::with_capacity(16 + data.len() + data.len() / 8 + 1);
+    Header::new(data.len() as u32).to_binary(&mut output)?;
+
+    let mut finder = MatchFinder::new();
+    let mut position = 0;
+    while position < data.len() {
+        let code_position = output.len();
+        output.push(0);
+
+        for bit in 0..8 {
+            if position >= data.len() {
+                break;
+            }
+
+            match finder.find(data, position, max_candidates) {
+                Some((distance, length)) => {
+                    let offset = distance - 1;
+                    if length < 0x12 {
+                        output.push((((length - 2) << 4) | (offset >> 8)) as u8);
+                        output.push(offset as u8);
+                    } else {
+                        output.push((offset >> 8) as u8);
+                        output.push(offset as u8);
+                        output.push((length - 0x12) as u8);
+                    }
+
+                    for i in position..position + length {
+                        finder.insert(data, i);
+                    }
+                    position += length;
+                },
+                None => {
+                    output[code_position] |= 0x80 >> bit;
+                    output.push(data[position]);
+                    finder.insert(data, position);
+                    position += 1;
+                },
+            }
+        }
+    }
+
+    Ok(output)
+}
@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod ascii {
+    use picori::ascii::Decoder;
     use picori::{Ascii, AsciiIteratorExt};
 
     #[test]
@@ -9,8 +10,7 @@ mod ascii {
 
         let ok = (0..=0x7f)
             .zip(result.unwrap().chars())
-            .map(|(a, b)| a as u8 as char == b)
-            .all(|x| x);
+            .all(|(a, b)| a as u8 as char == b);
         assert!(ok);
     }
 
@@ -41,5 +41,25 @@ mod ascii {
     fn all() {
         let data = b"abc\0def";
         assert_eq!(&Ascii::all(data).unwrap()[..], "abc\0def");
+        assert!(&Ascii::all(b"abc\x80def").is_err());
+    }
+
+    #[test]
+    fn all_lossy() {
+        assert_eq!(Ascii::all_lossy(b"abc\0def"), "abc\0def");
+        assert_eq!(Ascii::all_lossy(b"abc\x80d\xff"), "abc\u{fffd}d\u{fffd}");
+    }
+
+    #[test]
+    fn first_lossy() {
+        assert_eq!(Ascii::first_lossy(b"abc\0def"), "abc");
+        assert_eq!(Ascii::first_lossy(b"a\xffc\0\xff"), "a\u{fffd}c");
+    }
+
+    #[test]
+    fn decode_byte() {
+        assert_eq!(Decoder::<&[u8]>::decode_byte(0x41), Some('A'));
+        assert_eq!(Decoder::<&[u8]>::decode_byte(0x7f), Some('\x7f'));
+        assert_eq!(Decoder::<&[u8]>::decode_byte(0x80), None);
     }
 }
@@ -0,0 +1,103 @@
+#[cfg(all(test, feature = "std"))]
+mod bmg {
+    use std::io::Cursor;
+
+    use picori::bmg::{Encoding, Segment};
+    use picori::Bmg;
+
+    /// Build a BMG file with the given encoding byte and messages, each
+    /// message is followed by a NUL terminator of `unit_size` bytes.
+    fn build(encoding: u8, unit_size: usize, messages: &[&[u8]]) -> Vec<u8> {
+        let mut data = vec![0; unit_size];
+        let mut info = Vec::new();
+        info.extend_from_slice(&(messages.len() as u16).to_be_bytes());
+        info.extend_from_slice(&8_u16.to_be_bytes());
+        info.extend_from_slice(&0x1234_u16.to_be_bytes());
+        info.extend_from_slice(&[0x05, 0x00]);
+        for (index, message) in messages.iter().enumerate() {
+            info.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            info.extend_from_slice(&(index as u32).to_be_bytes());
+            data.extend_from_slice(message);
+            data.extend_from_slice(&vec![0; unit_size]);
+        }
+
+        let mut bmg = b"MESGbmg1".to_vec();
+        bmg.extend_from_slice(&0_u32.to_be_bytes());
+        bmg.extend_from_slice(&3_u32.to_be_bytes());
+        bmg.push(encoding);
+        bmg.resize(0x20, 0);
+        for (magic, content) in [(b"INF1", info), (b"FLW1", vec![0; 8]), (b"DAT1", data)] {
+            bmg.extend_from_slice(magic);
+            bmg.extend_from_slice(&(content.len() as u32 + 8).to_be_bytes());
+            bmg.extend_from_slice(&content);
+        }
+        bmg
+    }
+
+    #[test]
+    fn ascii() {
+        let data = build(0, 1, &[b"hello", b"a\x1a\x06\x01\x02\x03\x04b", b""]);
+        let bmg = Bmg::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(bmg.encoding, Encoding::Undefined);
+        assert_eq!(bmg.group, 0x1234);
+        assert_eq!(bmg.default_color, 5);
+        assert_eq!(bmg.strings(), ["hello", "ab", ""]);
+        assert_eq!(bmg.messages[1].attributes, [0, 0, 0, 1]);
+        assert_eq!(bmg.messages[1].segments, [
+            Segment::Text("a".to_string()),
+            Segment::Escape(vec![0x1a, 0x06, 0x01, 0x02, 0x03, 0x04]),
+            Segment::Text("b".to_string()),
+        ]);
+        assert!(bmg.messages[2].segments.is_empty());
+    }
+
+    #[test]
+    fn shift_jis() {
+        let data = build(3, 1, &[b"\x88\x9f"]);
+        let bmg = Bmg::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(bmg.strings(), ["亜"]);
+    }
+
+    #[test]
+    fn utf16() {
+        let data = build(2, 2, &[b"\x00a\x4e\x9c\x00\x1a\x06\xff\x00\x00\x00b"]);
+        let bmg = Bmg::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(bmg.encoding, Encoding::Utf16);
+        assert_eq!(bmg.messages[0].segments, [
+            Segment::Text("a亜".to_string()),
+            Segment::Escape(vec![0x00, 0x1a, 0x06, 0xff, 0x00, 0x00]),
+            Segment::Text("b".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn with_encoding() {
+        let data = build(0, 1, &[b"caf\xe9"]);
+        assert!(Bmg::from_binary(&mut Cursor::new(data.clone())).is_err());
+        let bmg = Bmg::from_binary_with_encoding(&mut Cursor::new(data), Encoding::Windows1252)
+            .unwrap();
+        assert_eq!(bmg.strings(), ["café"]);
+    }
+
+    #[test]
+    fn windows_1252() {
+        let data = build(1, 1, &[b"\x80 5, it\x92s\x85"]);
+        let bmg = Bmg::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(bmg.encoding, Encoding::Windows1252);
+        assert_eq!(bmg.strings(), ["€ 5, it’s…"]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = build(0, 1, &[b"hello"]);
+        data[0] = 0;
+        assert!(Bmg::from_binary(&mut Cursor::new(data)).is_err());
+
+        let data = build(9, 1, &[b"hello"]);
+        assert!(Bmg::from_binary(&mut Cursor::new(data)).is_err());
+
+        // escape sequence past the end of DAT1
+        let data = build(0, 1, &[b"a\x1a\x40"]);
+        assert!(Bmg::from_binary(&mut Cursor::new(data)).is_err());
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(all(test, feature = "std"))]
+mod bnr {
+    use std::io::Cursor;
+
+    use picori::bnr::{Language, Version};
+    use picori::Bnr;
+
+    fn field(text: &[u8], size: usize) -> Vec<u8> {
+        let mut data = text.to_vec();
+        data.resize(size, 0);
+        data
+    }
+
+    fn meta(title: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(field(title, 0x20));
+        data.extend(field(b"Maker", 0x20));
+        data.extend(field(title, 0x40));
+        data.extend(field(b"Maker Inc.", 0x40));
+        data.extend(field(b"A game.", 0x80));
+        data
+    }
+
+    fn banner(magic: &[u8], metas: &[&[u8]]) -> Vec<u8> {
+        let mut data = field(magic, 0x20);
+        data.extend((0..0x1800).map(|x| x as u8));
+        for title in metas {
+            data.extend(meta(title));
+        }
+        data
+    }
+
+    #[test]
+    fn bnr1() {
+        let data = banner(b"BNR1", &[b"\x83\x51\x81\x5B\x83\x80"]);
+        assert_eq!(data.len(), 0x1960);
+        let bnr = Bnr::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bnr.version, Version::Bnr1);
+        assert_eq!(bnr.metas.len(), 1);
+        assert_eq!(bnr.metas[0].short_title, "ゲーム");
+        assert_eq!(bnr.metas[0].short_maker, "Maker");
+        assert_eq!(bnr.metas[0].long_maker, "Maker Inc.");
+        assert_eq!(bnr.metas[0].description, "A game.");
+        assert_eq!(bnr.meta(Language::Dutch), bnr.metas.first());
+        assert_eq!(bnr.image.data, data[0x20..0x1820]);
+        assert_eq!(bnr.image.decode().unwrap().len(), 96 * 32 * 4);
+    }
+
+    #[test]
+    fn bnr2() {
+        let titles: [&[u8]; 6] = [b"Game", b"Spiel", b"Jeu", b"Juego", b"Gioco", b"Spel\xe9"];
+        let data = banner(b"BNR2", &titles);
+        assert_eq!(data.len(), 0x1FA0);
+        let bnr = Bnr::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bnr.version, Version::Bnr2);
+        assert_eq!(bnr.metas.len(), 6);
+        assert_eq!(bnr.meta(Language::French).unwrap().long_title, "Jeu");
+        assert_eq!(bnr.meta(Language::Dutch).unwrap().short_title, "Spelé");
+    }
+
+    #[test]
+    fn invalid() {
+        let data = banner(b"BNR3", &[b"Game"]);
+        assert!(Bnr::from_binary(&mut Cursor::new(&data)).is_err());
+        let data = banner(b"BNR2", &[b"Game"]);
+        assert!(Bnr::from_binary(&mut Cursor::new(&data)).is_err());
+    }
+}
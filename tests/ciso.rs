@@ -1,8 +1,8 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod ciso {
-    use std::io::Cursor;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
 
-    use picori::CisoReader;
+    use picori::{ciso, CisoBuilder, CisoReader};
 
     #[test]
     fn reader() {
@@ -106,4 +106,135 @@ mod ciso {
         let result = decoder.decompress(&mut Cursor::new([0; 4]));
         assert!(result.is_err());
     }
+
+    fn round_trip(data: &[u8], block_size: usize) -> Vec<u8> {
+        let mut ciso = Cursor::new(Vec::new());
+        CisoBuilder::new(block_size)
+            .unwrap()
+            .build(&mut Cursor::new(data), &mut ciso)
+            .unwrap();
+
+        let mut reader = Cursor::new(ciso.into_inner());
+        let mut decoder = CisoReader::new(&mut reader).unwrap();
+        assert_eq!(decoder.block_size(), block_size);
+        let mut output = Vec::new();
+        decoder.decompress(&mut output).unwrap();
+        assert_eq!(output.len(), data.len().div_ceil(block_size) * block_size);
+        assert_eq!(&output[..data.len()], data);
+        reader.into_inner()
+    }
+
+    #[test]
+    fn builder() {
+        let mut data = vec![0_u8; 0x40];
+        data[0x04..0x08].copy_from_slice(&[1, 1, 1, 1]);
+        data[0x24..0x28].copy_from_slice(&[9, 9, 9, 9]);
+        let ciso = round_trip(&data, 4);
+        assert_eq!(ciso.len(), 0x8000 + 3 * 4);
+        assert_eq!(&ciso[0..8], [0x43, 0x49, 0x53, 0x4F, 0x04, 0x00, 0x00, 0x00]);
+        assert_eq!(&ciso[8..24], [0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1]);
+
+        round_trip(&[0; 0x10], 4);
+        round_trip(&[1, 2, 3, 4, 5, 6], 4);
+    }
+
+    #[test]
+    fn builder_zero_threshold() {
+        let mut data = vec![0_u8; 0x30];
+        data[0x00] = 1;
+        data[0x10..0x18].fill(2);
+
+        let mut ciso = Cursor::new(Vec::new());
+        CisoBuilder::new(0x10)
+            .unwrap()
+            .zero_policy(ciso::ZeroPolicy::Threshold(0.9))
+            .build(&mut Cursor::new(&data), &mut ciso)
+            .unwrap();
+
+        let mut reader = Cursor::new(ciso.into_inner());
+        let mut decoder = CisoReader::new(&mut reader).unwrap();
+        assert!(!decoder.block_present(0));
+        assert!(decoder.block_present(1));
+        let mut output = Vec::new();
+        decoder.decompress(&mut output).unwrap();
+        assert_eq!(output[0], 0);
+        assert_eq!(output[1..], data[1..]);
+
+        let mut builder = CisoBuilder::new(0x10).unwrap();
+        builder.zero_policy(ciso::ZeroPolicy::Threshold(1.5));
+        assert!(builder
+            .build(&mut Cursor::new(&data), &mut Cursor::new(Vec::new()))
+            .is_err());
+    }
+
+    #[test]
+    fn builder_invalid() {
+        assert!(CisoBuilder::new(0).is_err());
+        assert!(CisoBuilder::new(0x8000001).is_err());
+
+        let builder = CisoBuilder::new(1).unwrap();
+        let mut output = Cursor::new(Vec::new());
+        assert!(builder.build(&mut Cursor::new(&[]), &mut output).is_err());
+        assert!(builder
+            .build(&mut Cursor::new(vec![1; 32761]), &mut output)
+            .is_err());
+    }
+
+    #[test]
+    fn is_ciso() {
+        let ciso = round_trip(&[1, 2, 3, 4], 4);
+        let mut reader = Cursor::new(&ciso);
+        assert!(ciso::is_ciso(&mut reader));
+        assert_eq!(reader.position(), 0);
+        assert!(!ciso::is_ciso(&mut Cursor::new(&ciso[1..])));
+        assert!(!ciso::is_ciso(&mut Cursor::new(&[0x43, 0x49])));
+    }
+
+    #[test]
+    fn blocks_sparse() {
+        let mut data = vec![0_u8; 0x10];
+        data[0x04..0x08].copy_from_slice(&[1, 2, 3, 4]);
+        data[0x0C..0x10].copy_from_slice(&[5, 6, 7, 8]);
+        let ciso = round_trip(&data, 4);
+
+        let mut reader = Cursor::new(ciso);
+        let mut decoder = CisoReader::new(&mut reader).unwrap();
+        assert_eq!(decoder.block_count(), 4);
+        assert!(!decoder.block_present(0));
+        assert!(decoder.block_present(1));
+        assert!(!decoder.block_present(2));
+        assert!(!decoder.block_present(4));
+
+        let blocks = decoder.blocks_sparse().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(blocks, [
+            (0, None),
+            (1, Some(vec![1, 2, 3, 4])),
+            (2, None),
+            (3, Some(vec![5, 6, 7, 8])),
+        ]);
+    }
+
+    #[test]
+    fn read_seek() {
+        let mut data = vec![0_u8; 0x10];
+        data[0x04..0x0A].copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+        let ciso = round_trip(&data, 4);
+
+        let mut reader = Cursor::new(ciso);
+        let mut decoder = CisoReader::new(&mut reader).unwrap();
+        let mut output = Vec::new();
+        decoder.read_to_end(&mut output).unwrap();
+        assert_eq!(output, data);
+
+        let mut buffer = [0xFF; 4];
+        assert_eq!(decoder.seek(SeekFrom::Start(2)).unwrap(), 2);
+        decoder.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [0, 0, 1, 2]);
+        assert_eq!(decoder.seek(SeekFrom::End(-8)).unwrap(), 8);
+        decoder.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [5, 6, 0, 0]);
+        assert!(decoder.seek(SeekFrom::Current(-13)).is_err());
+        decoder.seek(SeekFrom::Start(0x20)).unwrap();
+        assert_eq!(decoder.read(&mut buffer).unwrap(), 0);
+    }
 }
@@ -0,0 +1,83 @@
+#[cfg(all(test, feature = "std"))]
+mod compression {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    use picori::compression::Compression;
+    use picori::yay0;
+    use picori::{AutoDecompressReader, Parser};
+
+    // "abcabcabc" followed by 20 times "z": three literals, a back-reference
+    // with a short length, a literal and a back-reference with a long length.
+    const YAY0: &[u8] = &[
+        0x59, 0x61, 0x79, 0x30, // magic = 'Yay0'
+        0x00, 0x00, 0x00, 0x1D, // decompressed size = 29
+        0x00, 0x00, 0x00, 0x14, // link offset
+        0x00, 0x00, 0x00, 0x18, // chunk offset
+        0xE8, 0x00, 0x00, 0x00, // mask = 1110 1000
+        0x40, 0x02, // link = length 6, distance 3
+        0x00, 0x00, // link = length from chunk, distance 1
+        b'a', b'b', b'c', b'z', 0x01, // chunk
+    ];
+
+    fn expected() -> Vec<u8> {
+        let mut data = b"abcabcabc".to_vec();
+        data.extend_from_slice(&[b'z'; 20]);
+        data
+    }
+
+    #[test]
+    fn yay0() {
+        let mut cursor = Cursor::new(YAY0);
+        assert!(yay0::is_yay0(&mut cursor));
+        assert_eq!(cursor.position(), 0);
+        assert_eq!(yay0::decompress(&mut cursor).unwrap(), expected());
+    }
+
+    #[test]
+    fn yay0_trailing_data() {
+        let mut data = YAY0.to_vec();
+        data.resize(0x10000, 0xFF);
+        let mut cursor = Cursor::new(data);
+        assert_eq!(yay0::decompress(&mut cursor).unwrap(), expected());
+        assert!(cursor.position() <= 0x18 + 29);
+    }
+
+    #[test]
+    fn yay0_truncated() {
+        let mut cursor = Cursor::new(&YAY0[..YAY0.len() - 1]);
+        assert!(yay0::decompress(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn auto_yay0() {
+        let mut reader = AutoDecompressReader::new(Cursor::new(YAY0)).unwrap();
+        assert_eq!(reader.compression(), Compression::Yay0);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, expected());
+        reader.seek(SeekFrom::Start(3)).unwrap();
+        assert_eq!(reader.bu16().unwrap(), 0x6162);
+    }
+
+    #[test]
+    fn auto_yaz0() {
+        let c = include_bytes!("../assets/tests/yaz0/test.input");
+        let d = include_bytes!("../assets/tests/yaz0/test.output");
+        let mut reader = AutoDecompressReader::new(Cursor::new(c)).unwrap();
+        assert_eq!(reader.compression(), Compression::Yaz0);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), d);
+    }
+
+    #[test]
+    fn auto_uncompressed() {
+        let data = b"RARC not really";
+        let mut reader = AutoDecompressReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(reader.compression(), Compression::None);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), data);
+        assert_eq!(reader.into_inner().position(), data.len() as u64);
+    }
+}
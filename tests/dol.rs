@@ -1,9 +1,9 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod dol {
     use std::io::Cursor;
 
-    use picori::dol::{Section, SectionKind};
-    use picori::Dol;
+    use picori::dol::{self, Section, SectionKind};
+    use picori::{elf, Dol, Elf};
 
     #[test]
     fn invalid_header_size() {
@@ -68,6 +68,35 @@ mod dol {
         assert_eq!(dol.entry_point(), 0x39876543);
     }
 
+    #[test]
+    fn data_end_with_padding() {
+        let mut dol = vec![0; 0x100];
+        dol[0x00..0x04].copy_from_slice(&0x100_u32.to_be_bytes()); // text_offset[0]
+        dol[0x48..0x4C].copy_from_slice(&0x8000_3100_u32.to_be_bytes()); // text_address[0]
+        dol[0x90..0x94].copy_from_slice(&0x20_u32.to_be_bytes()); // text_size[0]
+        dol.extend_from_slice(&[0x60; 0x20]);
+        dol.extend_from_slice(&[0; 0xE0]);
+
+        let dol = Dol::from_binary(&mut Cursor::new(dol)).unwrap();
+        assert_eq!(dol.data_end(), 0x120);
+    }
+
+    #[test]
+    fn display() {
+        let mut dol = vec![0; 0x100];
+        dol[0x00..0x04].copy_from_slice(&0x100_u32.to_be_bytes()); // text_offset[0]
+        dol[0x48..0x4C].copy_from_slice(&0x8000_3100_u32.to_be_bytes()); // text_address[0]
+        dol[0x90..0x94].copy_from_slice(&0x20_u32.to_be_bytes()); // text_size[0]
+        dol[0xE0..0xE4].copy_from_slice(&0x8000_3100_u32.to_be_bytes()); // entry_point
+        dol.extend_from_slice(&[0x60; 0x20]);
+
+        let dol = Dol::from_binary(&mut Cursor::new(dol)).unwrap();
+        assert_eq!(
+            dol.to_string(),
+            "DOL entry 0x80003100, 2 sections, 0x00000120 bytes"
+        );
+    }
+
     #[test]
     fn invalid_section_size() {
         let mut dol = Vec::new();
@@ -79,6 +108,62 @@ mod dol {
         assert!(Dol::from_binary(&mut Cursor::new(dol)).is_err());
     }
 
+    fn header_with(text0: [u32; 3]) -> Vec<u8> {
+        let mut dol = vec![0; 0x100];
+        for (field, value) in [0x00, 0x48, 0x90].iter().zip(text0) {
+            dol[*field..*field + 4].copy_from_slice(&value.to_be_bytes());
+        }
+        dol.resize(0x200, 0);
+        dol
+    }
+
+    #[test]
+    fn overflowing_section() {
+        use picori::error::ParseProblem;
+        use picori::Error;
+
+        for text0 in [
+            [0xFFFF_FFFF, 0x8000_3100, 0x10],
+            [0x100, 0xFFFF_FFF0, 0x20],
+            [0xFFFF_FFF0, 0xFFFF_FFF0, 0xFFFF_FFFF],
+        ] {
+            assert!(matches!(
+                Dol::from_binary(&mut Cursor::new(header_with(text0))),
+                Err(Error::Parse(ParseProblem::InvalidRange(..)))
+            ));
+        }
+
+        // pseudo-random headers must never panic
+        let mut state = 0x1234_5678_u32;
+        for _ in 0..0x400 {
+            let mut dol = header_with([0; 3]);
+            for byte in dol[..0xE4].iter_mut() {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                *byte = if state >> 31 != 0 { 0xFF } else { (state >> 16) as u8 };
+            }
+            let _ = Dol::from_binary(&mut Cursor::new(dol));
+        }
+    }
+
+    #[test]
+    fn sections_by_offset() {
+        let mut data = header_with([0x200, 0x8000_3100, 0x20]);
+        for (field, value) in [(0x1C, 0x100_u32), (0x64, 0x8000_4000), (0xAC, 0x40)] {
+            data[field..field + 4].copy_from_slice(&value.to_be_bytes());
+        }
+        data.resize(0x220, 0);
+        let dol = Dol::from_binary(&mut Cursor::new(data)).unwrap();
+        let offsets = dol
+            .sections_by_offset()
+            .map(|x| x.offset.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [0x100, 0x200]);
+        assert_eq!(dol.gaps(), vec![std::ops::Range {
+            start: 0x140,
+            end:   0x200,
+        }]);
+    }
+
     #[test]
     #[should_panic]
     fn section_name_text_unreachable() { Section::guess_name(SectionKind::Text, 7); }
@@ -114,6 +199,25 @@ mod dol {
         assert_eq!(text.data, vec![0_u8; 0x36_E100]);
     }
 
+    #[test]
+    fn address_to_offset() {
+        let data = include_bytes!("../assets/tests/dol/test1.dol");
+        let dol = Dol::from_binary(&mut Cursor::new(data)).unwrap();
+        let init = dol.section_by_name(".init").unwrap();
+        let offset = init.offset.unwrap();
+        assert_eq!(dol.address_to_offset(init.address), Some(offset));
+        assert_eq!(dol.address_to_offset(init.address + 0x10), Some(offset + 0x10));
+        assert_eq!(
+            data[offset as usize + 0x10..offset as usize + 0x14],
+            *dol.read(init.address + 0x10, 4).unwrap()
+        );
+        assert_eq!(dol.address_to_offset(init.address + init.size), None);
+
+        let bss = dol.section_by_name(".bss").unwrap();
+        assert_eq!(dol.address_to_offset(bss.address), None);
+        assert_eq!(dol.address_to_offset(0), None);
+    }
+
     #[test]
     fn read_zeroed_sections1() {
         let mut file = include_bytes!("../assets/tests/dol/test1.dol");
@@ -122,6 +226,20 @@ mod dol {
         assert!(dol.rom_copy_info.is_some());
         assert!(dol.bss_init_info.is_some());
 
+        let rom_copy_info = dol.rom_copy_info().unwrap();
+        assert_eq!(rom_copy_info.len(), 10);
+        assert_eq!(rom_copy_info[0], dol::RomCopyInfo {
+            rom_address: 0x8000_3100,
+            ram_address: 0x8000_3100,
+            size:        0x24E8,
+        });
+        let bss_init_info = dol.bss_init_info().unwrap();
+        assert_eq!(bss_init_info.len(), 3);
+        assert_eq!(bss_init_info[2], dol::BssInitInfo {
+            ram_address: 0x8045_6B60,
+            size:        0x68,
+        });
+
         let init = dol.section_by_name(".init").unwrap();
         assert_eq!(init.name, ".init");
         assert_eq!(init.kind, SectionKind::Text);
@@ -137,6 +255,8 @@ mod dol {
         assert_eq!(text.aligned_size, 0x36_E100);
         assert_eq!(text.data, vec![0_u8; 0x36_E100]);
 
+        assert_eq!(dol.data_end(), 0x3D_59C0);
+
         let init2 = dol.section_by_address(init.address).unwrap();
         assert_eq!(init2.name, ".init");
         assert_eq!(init2.kind, SectionKind::Text);
@@ -144,4 +264,144 @@ mod dol {
         assert_eq!(init2.size, 0x24E8);
         assert_eq!(init2.aligned_size, 0x2500);
     }
+
+    #[test]
+    fn to_memory_image() {
+        let mut file = include_bytes!("../assets/tests/dol/test1.dol");
+        let dol = Dol::from_binary(&mut Cursor::new(&mut file)).unwrap();
+        let (base, image) = dol.to_memory_image().unwrap();
+        assert_eq!(base, 0x8000_3100);
+        assert_eq!(image.len(), 0x8045_6BC8 - 0x8000_3100);
+        for section in dol.sections.iter().filter(|x| x.kind != SectionKind::Bss) {
+            let offset = (section.address - base) as usize;
+            assert_eq!(&image[offset..offset + section.data.len()], section.data);
+        }
+
+        // header .bss spans .sdata and .sdata2
+        let mut file = include_bytes!("../assets/tests/dol/test0.dol");
+        let dol = Dol::from_binary(&mut Cursor::new(&mut file)).unwrap();
+        assert!(dol.to_memory_image().is_ok());
+    }
+
+    #[test]
+    fn to_memory_image_overlapping() {
+        let mut file = include_bytes!("../assets/tests/dol/test1.dol");
+        let mut dol = Dol::from_binary(&mut Cursor::new(&mut file)).unwrap();
+        dol.sections[1].address = dol.sections[0].address + 0x10;
+        assert!(dol.to_memory_image().is_err());
+    }
+
+    #[test]
+    fn read() {
+        let mut file = include_bytes!("../assets/tests/dol/test1.dol");
+        let dol = Dol::from_binary(&mut Cursor::new(&mut file)).unwrap();
+        let text = dol.section_by_name(".text").unwrap();
+        let end = text.address + text.size;
+        assert_eq!(dol.read(text.address, 8).unwrap(), &text.data[..8]);
+        assert_eq!(dol.read(end - 4, 4).unwrap(), &text.data[text.data.len() - 4..]);
+        assert!(dol.read(end - 4, 0x10).is_err());
+        assert!(dol.read(0, 4).is_err());
+
+        let bss = dol.section_by_name(".bss").unwrap();
+        assert!(dol.read(bss.address + bss.size - 4, 4).is_err());
+    }
+
+    fn elf_section(name: &str, kind: elf::SectionKind, flags: u32, address: u32) -> elf::Section {
+        let size = 0x24;
+        let data = match kind {
+            elf::SectionKind::NoBits => vec![],
+            _ => vec![(address >> 8) as u8; size as usize],
+        };
+        elf::Section {
+            name: name.to_string(),
+            kind,
+            flags,
+            address,
+            offset: 0,
+            size,
+            link: 0,
+            info: 0,
+            alignment: 4,
+            entry_size: 0,
+            data,
+        }
+    }
+
+    fn elf(sections: Vec<elf::Section>) -> Elf {
+        Elf {
+            header: elf::Header {
+                kind:                2,
+                flags:               0,
+                entry_point:         0x8000_3100,
+                program_offset:      0,
+                program_count:       0,
+                section_offset:      0,
+                section_count:       sections.len() as u16,
+                section_names_index: 0,
+            },
+            sections,
+        }
+    }
+
+    #[test]
+    fn from_elf() {
+        let elf = elf(vec![
+            elf_section("", elf::SectionKind::Null, 0, 0),
+            elf_section(".init", elf::SectionKind::ProgBits, 0x6, 0x8000_3100),
+            elf_section(".text", elf::SectionKind::ProgBits, 0x6, 0x8000_3200),
+            elf_section(".data", elf::SectionKind::ProgBits, 0x3, 0x8000_4000),
+            elf_section(".bss", elf::SectionKind::NoBits, 0x3, 0x8000_5000),
+            elf_section(".sbss", elf::SectionKind::NoBits, 0x3, 0x8000_5100),
+            elf_section(".comment", elf::SectionKind::ProgBits, 0, 0),
+        ]);
+        let dol = Dol::from_elf(&elf).unwrap();
+        assert_eq!(dol.entry_point(), 0x8000_3100);
+
+        let mut binary = Vec::new();
+        dol.to_binary(&mut binary).unwrap();
+        assert_eq!(binary.len(), 0x100 + 3 * 0x40);
+
+        let dol = Dol::from_binary(&mut Cursor::new(binary)).unwrap();
+        assert_eq!(dol.entry_point(), 0x8000_3100);
+        assert_eq!(dol.header.text_address[..2], [0x8000_3100, 0x8000_3200]);
+        assert_eq!(dol.header.data_address[0], 0x8000_4000);
+        assert_eq!(dol.header.bss_address, 0x8000_5000);
+        assert_eq!(dol.header.bss_size, 0x124);
+        assert_eq!(dol.read(0x8000_3100, 0x24).unwrap(), &[0x31; 0x24]);
+        assert_eq!(dol.read(0x8000_3200, 4).unwrap(), &[0x32; 4]);
+        assert_eq!(dol.read(0x8000_4000, 4).unwrap(), &[0x40; 4]);
+    }
+
+    #[test]
+    fn from_elf_too_many_sections() {
+        let text = (0..8)
+            .map(|i| elf_section(".text", elf::SectionKind::ProgBits, 0x6, i * 0x100))
+            .collect();
+        assert!(Dol::from_elf(&elf(text)).is_err());
+
+        let data = (0..12)
+            .map(|i| elf_section(".data", elf::SectionKind::ProgBits, 0x3, i * 0x100))
+            .collect();
+        assert!(Dol::from_elf(&elf(data)).is_err());
+    }
+
+    #[test]
+    fn is_dol() {
+        let mut header = [0_u32; 0x40];
+        header[0] = 0x100;
+        header[36] = 0x20;
+        let mut data = header.iter().flat_map(|x| x.to_be_bytes()).collect::<Vec<_>>();
+        data.extend_from_slice(&[0; 0x20]);
+
+        let mut reader = Cursor::new(&data);
+        reader.set_position(4);
+        assert!(!dol::is_dol(&mut reader));
+        assert_eq!(reader.position(), 4);
+        reader.set_position(0);
+        assert!(dol::is_dol(&mut reader));
+        assert_eq!(reader.position(), 0);
+
+        assert!(!dol::is_dol(&mut Cursor::new(&data[..0x110])));
+        assert!(!dol::is_dol(&mut Cursor::new(&[0; 0x100])));
+    }
 }
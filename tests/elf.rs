@@ -0,0 +1,122 @@
+#[cfg(all(test, feature = "std"))]
+mod elf {
+    use std::io::Cursor;
+
+    use picori::elf::SectionKind;
+    use picori::Elf;
+
+    /// Build a PowerPC ELF with the given sections `(name, type, flags,
+    /// address, data)`. A null section and `.shstrtab` are added.
+    pub fn build(sections: &[(&str, u32, u32, u32, &[u8])]) -> Vec<u8> {
+        let mut names = b"\0".to_vec();
+        let mut headers = vec![[0_u32; 10]];
+        let mut data = Vec::new();
+        let sections = sections
+            .iter()
+            .copied()
+            .chain([(".shstrtab", 3, 0, 0, &[][..])]);
+        for (name, kind, flags, address, content) in sections {
+            let name_offset = names.len() as u32;
+            names.extend_from_slice(name.as_bytes());
+            names.push(0);
+
+            let content = if name == ".shstrtab" {
+                names.clone()
+            } else {
+                content.to_vec()
+            };
+            let offset = 0x34 + data.len() as u32;
+            let size = if kind == 8 { 0x100 } else { content.len() as u32 };
+            if kind != 8 {
+                data.extend_from_slice(&content);
+            }
+            headers.push([name_offset, kind, flags, address, offset, size, 0, 0, 4, 0]);
+        }
+
+        let mut elf = b"\x7FELF\x01\x02\x01".to_vec();
+        elf.resize(16, 0);
+        for value in [2_u16, 20] {
+            elf.extend_from_slice(&value.to_be_bytes());
+        }
+        for value in [1_u32, 0x8000_3100, 0, 0x34 + data.len() as u32, 0] {
+            elf.extend_from_slice(&value.to_be_bytes());
+        }
+        let count = headers.len() as u16;
+        for value in [0x34_u16, 0x20, 0, 0x28, count, count - 1] {
+            elf.extend_from_slice(&value.to_be_bytes());
+        }
+        elf.extend_from_slice(&data);
+        for header in headers {
+            for value in header {
+                elf.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        elf
+    }
+
+    #[test]
+    fn sections() {
+        let data = build(&[
+            (".text", 1, 0x6, 0x8000_3100, &[0x4E, 0x80, 0x00, 0x20]),
+            (".data", 1, 0x3, 0x8000_3200, &[1, 2, 3, 4, 5, 6, 7, 8]),
+            (".bss", 8, 0x3, 0x8000_3300, &[]),
+        ]);
+        let elf = Elf::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(elf.entry_point(), 0x8000_3100);
+        assert_eq!(elf.sections.len(), 5);
+        assert_eq!(elf.sections[0].kind, SectionKind::Null);
+
+        let text = elf.section_by_name(".text").unwrap();
+        assert_eq!(text.kind, SectionKind::ProgBits);
+        assert_eq!(text.address, 0x8000_3100);
+        assert_eq!(text.data, [0x4E, 0x80, 0x00, 0x20]);
+        assert!(text.is_alloc() && text.is_executable() && !text.is_writable());
+
+        let data = elf.section_by_name(".data").unwrap();
+        assert_eq!(data.data, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(data.is_writable() && !data.is_executable());
+
+        let bss = elf.section_by_name(".bss").unwrap();
+        assert_eq!(bss.kind, SectionKind::NoBits);
+        assert_eq!(bss.size, 0x100);
+        assert!(bss.data.is_empty());
+
+        assert_eq!(elf.section_by_name(".shstrtab").unwrap().kind, SectionKind::StrTab);
+        assert!(elf.section_by_name(".rodata").is_none());
+    }
+
+    #[test]
+    fn no_section_names() {
+        let mut data = build(&[(".text", 1, 0x6, 0x8000_3100, &[0x4E, 0x80, 0x00, 0x20])]);
+        data[0x32..0x34].copy_from_slice(&0_u16.to_be_bytes());
+        let elf = Elf::from_binary(&mut Cursor::new(data)).unwrap();
+        assert_eq!(elf.sections.len(), 3);
+        assert!(elf.sections.iter().all(|x| x.name.is_empty()));
+        assert_eq!(elf.sections[1].data, [0x4E, 0x80, 0x00, 0x20]);
+    }
+
+    #[test]
+    fn invalid_header() {
+        let data = build(&[]);
+        assert!(Elf::from_binary(&mut Cursor::new(data.clone())).is_ok());
+
+        let mut elf = data.clone();
+        elf[0] = 0;
+        assert!(Elf::from_binary(&mut Cursor::new(elf)).is_err());
+
+        // ELFCLASS64
+        let mut elf = data.clone();
+        elf[4] = 2;
+        assert!(Elf::from_binary(&mut Cursor::new(elf)).is_err());
+
+        // ELFDATA2LSB
+        let mut elf = data.clone();
+        elf[5] = 1;
+        assert!(Elf::from_binary(&mut Cursor::new(elf)).is_err());
+
+        // EM_386
+        let mut elf = data;
+        elf[19] = 3;
+        assert!(Elf::from_binary(&mut Cursor::new(elf)).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod euc_jp {
+    use picori::{EucJp, EucJpIteratorExt};
+
+    #[test]
+    fn ascii() {
+        let result = (0..=0x7f).euc_jp().map(|x| x.unwrap());
+        assert!((0..=0x7f).zip(result).all(|(a, b)| (a as u8) as char == b));
+    }
+
+    #[test]
+    fn halfwidth_katakana() {
+        let data = (0xa1..=0xdf).flat_map(|x| [0x8e, x]).collect::<Vec<u8>>();
+        let result = EucJp::all(&data).unwrap();
+        let expected = (0xff61..=0xff9f)
+            .map(|x| char::from_u32(x).unwrap())
+            .collect::<String>();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn jis_x_0208() {
+        let data = b"\xa5\xbc\xa5\xeb\xa5\xc0\xa5\xf3\xa4\xce\xc5\xc1\xc0\xe2";
+        assert_eq!(EucJp::all(data).unwrap(), "ゼルダンの伝説");
+    }
+
+    #[test]
+    fn err() {
+        assert!(EucJp::all(b"\x80").is_err());
+        assert!(EucJp::all(b"\xff").is_err());
+        assert!(EucJp::all(b"\x8e\xe0").is_err());
+        assert!(EucJp::all(b"\xb0\x41").is_err());
+        assert!(EucJp::all(b"\xb0").is_err());
+        assert!(EucJp::all(b"\x8f\xa1\xa1").is_err());
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(&EucJp::first(b"abc\xb0\xa1\0def").unwrap()[..], "abc亜");
+        assert!(&EucJp::first(b"abc\xa0def").is_err());
+    }
+
+    #[test]
+    fn all() {
+        let data = b"abc\0def";
+        assert_eq!(&EucJp::all(data).unwrap()[..], "abc\0def");
+    }
+}
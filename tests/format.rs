@@ -0,0 +1,43 @@
+#[cfg(all(test, feature = "std"))]
+mod format {
+    use std::io::Cursor;
+
+    use picori::{detect_format, Format};
+
+    fn detect(data: &[u8]) -> Format {
+        let mut reader = Cursor::new(data);
+        let format = detect_format(&mut reader).unwrap();
+        assert_eq!(reader.position(), 0);
+        format
+    }
+
+    #[test]
+    fn assets() {
+        assert_eq!(detect(include_bytes!("../assets/tests/dol/test0.dol")), Format::Dol);
+        assert_eq!(detect(include_bytes!("../assets/tests/rel/test0.rel")), Format::Rel);
+        assert_eq!(detect(include_bytes!("../assets/tests/yaz0/test.input")), Format::Yaz0);
+        assert_eq!(detect(include_bytes!("../assets/tests/yaz0/test.output")), Format::Unknown);
+    }
+
+    #[test]
+    fn magic() {
+        assert_eq!(detect(b"Yay0\0\0\0\x10\0\0\0\0\0\0\0\0"), Format::Yay0);
+        assert_eq!(detect(b"RARC\0\0\0\0"), Format::Rarc);
+        assert_eq!(detect(b"CISO\0\0\0\0"), Format::Ciso);
+
+        let mut gcm = vec![0; 0x20];
+        gcm[0x1C..0x20].copy_from_slice(&[0xC2, 0x33, 0x9F, 0x3D]);
+        assert_eq!(detect(&gcm), Format::Gcm);
+        assert_eq!(detect(&[]), Format::Unknown);
+    }
+
+    #[test]
+    fn position() {
+        let mut data = vec![0xFF; 4];
+        data.extend_from_slice(b"RARC");
+        let mut reader = Cursor::new(&data);
+        reader.set_position(4);
+        assert_eq!(detect_format(&mut reader).unwrap(), Format::Rarc);
+        assert_eq!(reader.position(), 4);
+    }
+}
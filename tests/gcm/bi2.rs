@@ -1,4 +1,70 @@
 #[cfg(test)]
 mod bi2 {
-   
+    use std::io::Cursor;
+
+    use picori::gcm::{Bi2, Bi2Options, PadSpec};
+
+    #[test]
+    fn round_trip() {
+        let mut bi2 = Bi2::default();
+        bi2.set(Bi2Options::DebugFlag, 1);
+        bi2.set(Bi2Options::CountryCode, 2);
+        bi2.set(Bi2Options::Unknown(0x7FF), 0x12345678);
+
+        let mut data = Vec::new();
+        bi2.to_binary(&mut data).unwrap();
+        assert_eq!(data.len(), 0x2000);
+        assert_eq!(data[0x10..0x14], [0, 0, 0, 1]);
+        assert_eq!(data[0x1C..0x20], [0, 0, 0, 2]);
+        assert_eq!(data[0x1FFC..], [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(data.iter().filter(|x| **x != 0).count(), 6);
+
+        let bi2 = Bi2::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(bi2.get(Bi2Options::DebugFlag), Some(&1));
+        assert_eq!(bi2.get(Bi2Options::CountryCode), Some(&2));
+        assert_eq!(bi2.get(Bi2Options::Unknown(0x7FF)), Some(&0x12345678));
+        assert_eq!(bi2.options().len(), 3);
+    }
+
+    #[test]
+    fn typed_options() {
+        let mut bi2 = Bi2::default();
+        assert_eq!(bi2.pad_spec(), None);
+        assert_eq!(bi2.debug_flag(), None);
+
+        bi2.set(Bi2Options::PadSpec, PadSpec::Spec5.into());
+        bi2.set(Bi2Options::DebugFlag, 1);
+        assert_eq!(bi2.pad_spec(), Some(PadSpec::Spec5));
+        assert_eq!(bi2.debug_flag(), Some(true));
+
+        bi2.set(Bi2Options::PadSpec, 6);
+        bi2.set(Bi2Options::DebugFlag, 0);
+        assert_eq!(bi2.pad_spec(), Some(PadSpec::Unknown(6)));
+        assert_eq!(bi2.debug_flag(), Some(false));
+        assert_eq!(u32::from(PadSpec::Unknown(6)), 6);
+    }
+
+    #[test]
+    fn unknown_named_index() {
+        let mut bi2 = Bi2::default();
+        bi2.set(Bi2Options::DebugFlag, 1);
+        bi2.set(Bi2Options::Unknown(4), 2);
+        assert_eq!(bi2.get(Bi2Options::DebugFlag), Some(&2));
+        assert_eq!(bi2.get(Bi2Options::Unknown(4)), Some(&2));
+        assert_eq!(bi2.options().len(), 1);
+
+        let mut data = Vec::new();
+        bi2.to_binary(&mut data).unwrap();
+        assert_eq!(data[0x10..0x14], [0, 0, 0, 2]);
+
+        bi2.clear(Bi2Options::Unknown(4));
+        assert!(bi2.options().is_empty());
+    }
+
+    #[test]
+    fn index_out_of_bounds() {
+        let mut bi2 = Bi2::default();
+        bi2.set(Bi2Options::Unknown(0x800), 1);
+        assert!(bi2.to_binary(&mut Vec::new()).is_err());
+    }
 }
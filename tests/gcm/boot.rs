@@ -110,4 +110,73 @@ mod boot {
 
         assert_eq!(output, data);
     }
+
+    #[test]
+    fn builder() {
+        let boot = Boot::builder()
+            .game_code("ZL")
+            .country_code(b'E')
+            .maker_code("01")
+            .game_name("THE LEGEND OF ZELDA The Wind Waker for USA")
+            .main_executable_offset(0x1E800)
+            .fst(0x3D2000, 0x1C0)
+            .build()
+            .unwrap();
+        assert_eq!(boot.fst_max_size, 0x1C0);
+
+        let mut output = Vec::new();
+        boot.to_binary(&mut output).unwrap();
+        assert_eq!(output.len(), 0x440);
+        assert_eq!(&output[0..7], b"GZLE01\0");
+        assert_eq!(output[0x1C..0x20], [0xC2, 0x33, 0x9F, 0x3D]);
+
+        let parsed = Boot::from_binary(&mut std::io::Cursor::new(&output)).unwrap();
+        assert_eq!(parsed, boot);
+    }
+
+    #[test]
+    fn non_ascii_game_name() {
+        let mut data = Vec::new();
+        Boot::builder()
+            .game_code("ZL")
+            .maker_code("01")
+            .game_name("CAFE")
+            .build()
+            .unwrap()
+            .to_binary(&mut data)
+            .unwrap();
+        data[0x23] = 0xC9;
+
+        let mut boot = Boot::from_binary(&mut std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(boot.game_name, "CAF\u{FFFD}");
+        assert_eq!(&boot.raw_game_name[..5], b"CAF\xC9\0");
+
+        let mut output = Vec::new();
+        boot.to_binary(&mut output).unwrap();
+        assert_eq!(output, data);
+
+        boot.game_name = "TEA".to_string();
+        let mut output = Vec::new();
+        boot.to_binary(&mut output).unwrap();
+        assert_eq!(&output[0x20..0x24], b"TEA\0");
+    }
+
+    #[test]
+    fn builder_invalid() {
+        let build = |game_code: &str, maker_code: &str, game_name: &str| {
+            Boot::builder()
+                .game_code(game_code)
+                .maker_code(maker_code)
+                .game_name(game_name)
+                .build()
+        };
+        assert!(build("ZL", "01", "").is_ok());
+        assert!(build("Z", "01", "").is_err());
+        assert!(build("ZLE", "01", "").is_err());
+        assert!(build("ZL", "\u{e9}1", "").is_err());
+        assert!(build("ZL", "0\0", "").is_err());
+        assert!(build("ZL", "01", "caf\u{e9}").is_err());
+        assert!(build("ZL", "01", &"A".repeat(0x3DF)).is_ok());
+        assert!(build("ZL", "01", &"A".repeat(0x3E0)).is_err());
+    }
 }
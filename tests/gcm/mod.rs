@@ -1,8 +1,817 @@
+pub mod bi2;
 pub mod boot;
 
+/// Build a minimal `.dol` with a single `.init` section containing `data`.
+pub fn dol(data: &[u8]) -> Vec<u8> {
+    let mut header = [0u32; 0x40];
+    header[0] = 0x100; // text_offset[0]
+    header[18] = 0x8000_3100; // text_address[0]
+    header[36] = data.len() as u32; // text_size[0]
+    header[56] = 0x8000_3100; // entry_point
+
+    let mut dol = header
+        .iter()
+        .flat_map(|x| x.to_be_bytes())
+        .collect::<Vec<_>>();
+    dol.extend_from_slice(data);
+    dol
+}
+
+/// Build a minimal `.gcm` image. `files` are slash-separated paths (sorted
+/// so that directory contents are contiguous) and their data.
+pub fn image(files: &[(&str, &[u8])]) -> Vec<u8> {
+    const MAIN_EXECUTABLE_OFFSET: usize = 0x2480;
+    const FST_OFFSET: usize = 0x2600;
+    const DATA_OFFSET: usize = 0x4000;
+
+    // flatten the paths into fst entries: (name, depth, is_directory, file index)
+    let mut entries = Vec::<(String, usize, bool, usize)>::new();
+    let mut current = Vec::<&str>::new();
+    for (index, (path, _)) in files.iter().enumerate() {
+        let parts = path.split('/').collect::<Vec<_>>();
+        let (file, directories) = parts.split_last().unwrap();
+        let common = current
+            .iter()
+            .zip(directories.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        current.truncate(common);
+        for directory in &directories[common..] {
+            entries.push((directory.to_string(), current.len(), true, 0));
+            current.push(directory);
+        }
+        entries.push((file.to_string(), current.len(), false, index));
+    }
+
+    let mut strings = Vec::<u8>::new();
+    let mut raw = vec![[0x0100_0000_u32, 0, entries.len() as u32 + 1]];
+    let mut data_offset = DATA_OFFSET;
+    let mut parents = vec![0_usize];
+    for (i, (name, depth, is_directory, file)) in entries.iter().enumerate() {
+        let index = i + 1;
+        let name_offset = strings.len() as u32;
+        strings.extend_from_slice(name.as_bytes());
+        strings.push(0);
+
+        parents.truncate(depth + 1);
+        if *is_directory {
+            let end = entries[i + 1..]
+                .iter()
+                .position(|x| x.1 <= *depth)
+                .map_or(entries.len() + 1, |x| index + 1 + x);
+            raw.push([0x0100_0000 | name_offset, parents[*depth] as u32, end as u32]);
+            parents.push(index);
+        } else {
+            let size = files[*file].1.len();
+            raw.push([name_offset, data_offset as u32, size as u32]);
+            data_offset = (data_offset + size + 0x1F) & !0x1F;
+        }
+    }
+
+    let mut fst = raw
+        .iter()
+        .flatten()
+        .flat_map(|x| x.to_be_bytes())
+        .collect::<Vec<_>>();
+    fst.extend_from_slice(&strings);
+
+    let mut image = vec![0u8; data_offset];
+    image[0x00] = 0x47; // console
+    image[0x01..0x06].copy_from_slice(b"ZLE01");
+    image[0x1C..0x20].copy_from_slice(&0xC2339F3D_u32.to_be_bytes());
+    image[0x20..0x24].copy_from_slice(b"TEST");
+    for (i, value) in [
+        MAIN_EXECUTABLE_OFFSET as u32,
+        FST_OFFSET as u32,
+        fst.len() as u32,
+        fst.len() as u32,
+    ]
+    .iter()
+    .enumerate()
+    {
+        image[0x420 + i * 4..0x424 + i * 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    // apploader with 0x20 bytes of code and no trailer
+    image[0x2440..0x244A].copy_from_slice(b"2002/12/10");
+    image[0x2450..0x2454].copy_from_slice(&0x8120_0000_u32.to_be_bytes());
+    image[0x2454..0x2458].copy_from_slice(&0x20_u32.to_be_bytes());
+
+    let main = dol(&[0x60, 0x00, 0x00, 0x00]);
+    image[MAIN_EXECUTABLE_OFFSET..MAIN_EXECUTABLE_OFFSET + main.len()].copy_from_slice(&main);
+    image[FST_OFFSET..FST_OFFSET + fst.len()].copy_from_slice(&fst);
+
+    for (entry, (_, data)) in raw
+        .iter()
+        .skip(1)
+        .filter(|x| x[0] & 0x0100_0000 == 0)
+        .zip(files.iter())
+    {
+        let offset = entry[1] as usize;
+        image[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    image
+}
+
 #[cfg(test)]
 mod gcm {
+    use std::io::Cursor;
+
+    use picori::gcm::fst::Entry;
+    use picori::gcm::{self, Apploader, Bi2, Boot, GcmBuilder};
+    use picori::{CisoBuilder, CisoReader, Gcm, GcmReader};
+
+    fn entry(gcm: &Gcm, path: &str) -> Entry {
+        gcm.fst()
+            .files()
+            .find(|(x, _)| x.to_str() == Some(path))
+            .map(|(_, x)| x)
+            .unwrap()
+    }
 
     #[test]
-    fn ok() {}
+    fn ok() {
+        let image = super::image(&[("a.bin", b"abc")]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert_eq!(gcm.boot().game_name, "TEST");
+        assert_eq!(gcm.fst().files().count(), 2);
+    }
+
+    #[test]
+    fn validate() {
+        let image = super::image(&[("a.bin", b"abc")]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert!(gcm.validate().is_ok());
+
+        // apploader entry point before the apploader code
+        let mut invalid = image.clone();
+        invalid[0x2450..0x2454].copy_from_slice(&0x8000_0000_u32.to_be_bytes());
+        let gcm = Gcm::from_binary(&mut Cursor::new(&invalid)).unwrap();
+        assert!(gcm.validate().is_err());
+
+        // file data past the end of a single-disc image is rejected while parsing
+        let mut invalid = image.clone();
+        invalid[0x2610..0x2614].copy_from_slice(&0x1000_0000_u32.to_be_bytes());
+        assert!(Gcm::from_binary(&mut Cursor::new(&invalid)).is_err());
+    }
+
+    #[test]
+    fn fst_to_binary() {
+        let image = super::image(&[
+            ("a.bin", b"abc"),
+            ("dir/a.bin", b"hello"),
+            ("dir/sub/c.bin", &[1, 2, 3, 4]),
+            ("e.bin", &[]),
+        ]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+
+        let mut data = Vec::new();
+        let (size, max_size) = gcm.fst().to_binary(&mut data).unwrap();
+        assert_eq!(size as usize, data.len());
+        assert_eq!(max_size, size);
+        // "a.bin" is only stored once
+        assert!(size < gcm.boot().fst_size);
+
+        let fst = gcm::Fst::from_binary(&mut Cursor::new(&data), size as usize).unwrap();
+        assert!(fst.files().eq(gcm.fst().files()));
+    }
+
+    #[test]
+    fn clone_eq() {
+        let image = super::image(&[("a.bin", b"abc"), ("b.bin", b"de")]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert_eq!(gcm.clone(), gcm);
+        assert_eq!(Gcm::from_binary(&mut Cursor::new(&image)).unwrap(), gcm);
+
+        let other = super::image(&[("a.bin", b"abc"), ("c.bin", b"de")]);
+        let other = Gcm::from_binary(&mut Cursor::new(&other)).unwrap();
+        assert_eq!(other.boot(), gcm.boot());
+        assert_ne!(other.fst(), gcm.fst());
+        assert_ne!(other, gcm);
+    }
+
+    #[test]
+    fn write_file_chunks() {
+        let data = (0..0x18001).map(|x| x as u8).collect::<Vec<_>>();
+        let image = super::image(&[("big.bin", &data)]);
+        let mut reader = GcmReader::new(Cursor::new(&image)).unwrap();
+        let entry = reader.fst().find("big.bin").unwrap().clone();
+        let mut output = Vec::new();
+        reader.write_file(&entry, &mut output).unwrap();
+        assert_eq!(output, data);
+
+        let gcm::fst::Entry::File { offset, size, .. } = entry else {
+            unreachable!()
+        };
+        let past_end = gcm::fst::Entry::File {
+            name:          "big.bin".to_string(),
+            index:         1,
+            offset,
+            size:          image.len() as u32 - offset + 1,
+            on_other_disc: false,
+        };
+        assert!(size < image.len() as u32 - offset);
+        assert!(reader.write_file(&past_end, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn user_data() {
+        let mut image = super::image(&[("a.bin", b"abc")]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert_eq!(gcm.user_data(&mut Cursor::new(&image)).unwrap(), None);
+
+        image[0x430..0x434].copy_from_slice(&0x2440_u32.to_be_bytes());
+        image[0x434..0x438].copy_from_slice(&0x10_u32.to_be_bytes());
+        let mut reader = GcmReader::new(Cursor::new(&image)).unwrap();
+        assert_eq!(reader.user_data().unwrap().unwrap(), image[0x2440..0x2450]);
+
+        // region at the end of the image
+        let end = image.len() as u32 - 0x10;
+        image[0x430..0x434].copy_from_slice(&end.to_be_bytes());
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert_eq!(gcm.user_data(&mut Cursor::new(&image)).unwrap().unwrap(), image[end as usize..]);
+
+        // region past the end of the image, but within the largest disc
+        image[0x430..0x434].copy_from_slice(&(end + 1).to_be_bytes());
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert!(matches!(
+            gcm.user_data(&mut Cursor::new(&image)),
+            Err(picori::Error::Parse(picori::error::ParseProblem::InvalidRange(..)))
+        ));
+
+        image[0x430..0x434].copy_from_slice(&0xFFFF_FFF8_u32.to_be_bytes());
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert!(gcm.user_data(&mut Cursor::new(&image)).is_err());
+
+        // region relative to the position the disc was parsed at
+        image[0x430..0x434].copy_from_slice(&0x2440_u32.to_be_bytes());
+        let mut data = vec![0xFF; 0x100];
+        data.extend_from_slice(&image);
+        let mut reader = Cursor::new(&data);
+        reader.set_position(0x100);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        assert_eq!(gcm.user_data(&mut reader).unwrap().unwrap(), image[0x2440..0x2450]);
+    }
+
+    #[test]
+    fn executable_size() {
+        let mut data = super::dol(&[0x60, 0x00, 0x00, 0x00]);
+        let executable = gcm::Executable::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(executable.offset(), 0);
+        assert_eq!(executable.size() as usize, data.len());
+
+        data[0x90..0x94].copy_from_slice(&0xFFFF_FFF0_u32.to_be_bytes());
+        assert!(gcm::Executable::from_binary(&mut Cursor::new(&data)).is_err());
+    }
+
+    #[test]
+    fn executable_as_dol() {
+        let image = super::image(&[("a.bin", b"abc")]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        let dol = gcm.executable_as_dol(&mut reader).unwrap();
+        assert_eq!(dol.entry_point(), 0x8000_3100);
+        assert_eq!(
+            dol.section_by_name(".init").unwrap().data,
+            [0x60, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn executable_too_large() {
+        use picori::error::ParseProblem;
+        use picori::Error;
+
+        let mut image = super::image(&[("a.bin", b"abc")]);
+        let offset = u32::from_be_bytes(image[0x420..0x424].try_into().unwrap()) as usize;
+        image[offset + 0x90..offset + 0x94].copy_from_slice(&0x0300_0000u32.to_be_bytes());
+        assert!(matches!(
+            Gcm::from_binary(&mut Cursor::new(&image)),
+            Err(Error::Parse(ParseProblem::Context("executable", problem, _)))
+                if matches!(*problem, ParseProblem::InvalidRange(..))
+        ));
+    }
+
+    #[test]
+    fn open_dol() {
+        let extra = super::dol(&[0x4E, 0x80, 0x00, 0x20]);
+        let image = super::image(&[("bin/extra.dol", &extra), ("bin/readme.txt", b"hello")]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+
+        let dol = gcm
+            .open_dol(&mut reader, &entry(&gcm, "bin/extra.dol"))
+            .unwrap();
+        assert_eq!(dol.entry_point(), 0x8000_3100);
+        assert_eq!(
+            dol.section_by_name(".init").unwrap().data,
+            [0x4E, 0x80, 0x00, 0x20]
+        );
+
+        assert!(gcm.open_dol(&mut reader, &entry(&gcm, "bin")).is_err());
+        assert!(gcm
+            .open_dol(&mut reader, &entry(&gcm, "bin/readme.txt"))
+            .is_err());
+    }
+
+    #[test]
+    fn banner() {
+        let mut bnr = b"BNR1".to_vec();
+        bnr.resize(0x1820, 0);
+        bnr.extend_from_slice(b"Title");
+        bnr.resize(0x1960, 0);
+        let image = super::image(&[("a.bin", b"abc"), ("opening.bnr", &bnr)]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        let banner = gcm.banner(&mut reader).unwrap().unwrap();
+        assert_eq!(banner.metas[0].short_title, "Title");
+
+        bnr.resize(0x2000, 0);
+        let image = super::image(&[("a.bin", b"abc"), ("opening.bnr", &bnr)]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        assert!(gcm.banner(&mut reader).is_err());
+
+        let image = super::image(&[("a.bin", b"abc")]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        assert!(gcm.banner(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn open_file() {
+        let image = super::image(&[
+            ("a.bin", b"abc"),
+            ("dir/b.bin", b"hello"),
+            ("dir/sub/c.bin", &[1, 2, 3, 4]),
+            ("dir/d.bin", b"world"),
+            ("e.bin", &[0xFF; 0x30]),
+        ]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+
+        assert_eq!(gcm.open_file(&mut reader, "a.bin").unwrap(), b"abc");
+        assert_eq!(gcm.open_file(&mut reader, "dir/b.bin").unwrap(), b"hello");
+        assert_eq!(gcm.open_file(&mut reader, "/dir/sub/c.bin").unwrap(), [1, 2, 3, 4]);
+        assert_eq!(gcm.open_file(&mut reader, "dir/d.bin").unwrap(), b"world");
+        assert_eq!(gcm.open_file(&mut reader, "e.bin").unwrap(), [0xFF; 0x30]);
+
+        assert!(gcm.open_file(&mut reader, "dir").is_err());
+        assert!(gcm.open_file(&mut reader, "").is_err());
+        assert!(gcm.open_file(&mut reader, "c.bin").is_err());
+        assert!(gcm.open_file(&mut reader, "dir/e.bin").is_err());
+        assert!(gcm.open_file(&mut reader, "a.bin/x").is_err());
+    }
+
+    #[test]
+    fn embedded() {
+        let image = super::image(&[("a.bin", b"abc"), ("dir/b.bin", b"hello")]);
+        let mut data = vec![0xFF; 0x100];
+        data.extend_from_slice(&image);
+        let mut reader = Cursor::new(&data);
+        reader.set_position(0x100);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        assert_eq!(gcm.base(), 0x100);
+        assert_eq!(gcm.open_file(&mut reader, "a.bin").unwrap(), b"abc");
+        assert_eq!(gcm.open_file(&mut reader, "dir/b.bin").unwrap(), b"hello");
+
+        let mut files = Vec::new();
+        gcm.extract_all(&mut reader, |_, data| {
+            files.push(data.to_vec());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(files, [b"abc".to_vec(), b"hello".to_vec()]);
+
+        let dol = gcm.executable_as_dol(&mut reader).unwrap();
+        assert_eq!(dol.section_by_name(".init").unwrap().data, [0x60, 0x00, 0x00, 0x00]);
+
+        let mut bnr = b"BNR1".to_vec();
+        bnr.resize(0x1820, 0);
+        bnr.extend_from_slice(b"Title");
+        bnr.resize(0x1960, 0);
+        let mut data = vec![0xFF; 0x100];
+        let extra = super::dol(&[0x4E, 0x80, 0x00, 0x20]);
+        data.extend_from_slice(&super::image(&[("extra.dol", &extra), ("opening.bnr", &bnr)]));
+        let mut reader = Cursor::new(&data);
+        reader.set_position(0x100);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        let banner = gcm.banner(&mut reader).unwrap().unwrap();
+        assert_eq!(banner.metas[0].short_title, "Title");
+
+        let dol = gcm.open_dol(&mut reader, &entry(&gcm, "extra.dol")).unwrap();
+        assert_eq!(dol.section_by_name(".init").unwrap().data, [0x4E, 0x80, 0x00, 0x20]);
+
+        reader.set_position(0x100);
+        let mut reader = GcmReader::new(reader).unwrap();
+        let entry = entry(reader.gcm(), "extra.dol");
+        assert_eq!(reader.file_data(&entry).unwrap(), extra);
+        let mut output = Vec::new();
+        reader.write_file(&entry, &mut output).unwrap();
+        assert_eq!(output, extra);
+        let files = reader.files().map(|x| x.unwrap().1).collect::<Vec<_>>();
+        assert_eq!(files, [extra, bnr]);
+    }
+
+    #[test]
+    fn extract_all() {
+        let image = super::image(&[
+            ("a.bin", b"abc"),
+            ("dir/b.bin", b"hello"),
+            ("dir/sub/c.bin", &[1, 2, 3, 4]),
+            ("e.bin", &[]),
+        ]);
+        let mut reader = Cursor::new(&image);
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+
+        let mut files = Vec::new();
+        gcm.extract_all(&mut reader, |path, data| {
+            files.push((path.to_str().unwrap().to_string(), data.to_vec()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(files, [
+            ("a.bin".to_string(), b"abc".to_vec()),
+            ("dir/b.bin".to_string(), b"hello".to_vec()),
+            ("dir/sub/c.bin".to_string(), vec![1, 2, 3, 4]),
+            ("e.bin".to_string(), vec![]),
+        ]);
+
+        let mut count = 0;
+        let result = gcm.extract_all(&mut reader, |_, _| {
+            count += 1;
+            Err(std::io::Error::other("stop").into())
+        });
+        assert!(result.is_err());
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn reader() {
+        let image = super::image(&[("a.bin", b"abc"), ("dir/b.bin", b"hello")]);
+        let mut reader = GcmReader::new(Cursor::new(&image)).unwrap();
+        assert_eq!(reader.boot().game_name, "TEST");
+        assert_eq!(reader.open("dir/b.bin").unwrap(), b"hello");
+        assert!(reader.open("dir").is_err());
+
+        let a = entry(reader.gcm(), "a.bin");
+        assert_eq!(reader.file_data(&a).unwrap(), b"abc");
+        let dir = entry(reader.gcm(), "dir");
+        assert!(reader.file_data(&dir).is_err());
+
+        // size past the end of the largest disc
+        let huge = Entry::File {
+            name:          "huge.bin".to_string(),
+            index:         1,
+            offset:        0x4000,
+            size:          0xFFFF_FF00,
+            on_other_disc: false,
+        };
+        assert!(matches!(
+            reader.file_data(&huge),
+            Err(picori::Error::Parse(picori::error::ParseProblem::InvalidRange(..)))
+        ));
+        assert!(reader.write_file(&huge, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn builder() {
+        let image = super::image(&[("a.bin", b"abc")]);
+        let boot = Boot::from_binary(&mut Cursor::new(&image)).unwrap();
+        let bi2 = Bi2::from_binary(&mut Cursor::new(&image[0x440..])).unwrap();
+        let apploader = Apploader::from_binary(&mut Cursor::new(&image[0x2440..])).unwrap();
+        let executable = super::dol(&[0x4E, 0x80, 0x00, 0x20]);
+
+        let mut builder = GcmBuilder::new(boot, bi2, apploader, executable.clone());
+        builder
+            .add_file("b.bin", b"hello".to_vec())
+            .add_file("dir/sub/c.bin", vec![1, 2, 3])
+            .add_file("A.bin", vec![0xFF; 0x21])
+            .add_file("dir/d.bin", vec![])
+            .add_file("e.bin", b"world".to_vec());
+
+        let mut output = Vec::new();
+        builder.build(&mut output).unwrap();
+        assert_eq!(output.len() % 0x20, 0);
+
+        let mut reader = GcmReader::new(Cursor::new(&output)).unwrap();
+        assert_eq!(reader.boot().game_name, "TEST");
+        assert_eq!(reader.boot().main_executable_offset, 0x2500);
+        assert_eq!(reader.boot().fst_offset % 0x100, 0);
+        assert_eq!(reader.executable().data(), executable.as_slice());
+        assert_eq!(reader.executable().size() as usize, executable.len());
+        assert_eq!(reader.executable().range(), 0x2500..0x2500 + executable.len() as u64);
+        assert_eq!(reader.open("A.bin").unwrap(), [0xFF; 0x21]);
+        assert_eq!(reader.open("b.bin").unwrap(), b"hello");
+        assert_eq!(reader.open("dir/sub/c.bin").unwrap(), [1, 2, 3]);
+        assert_eq!(reader.open("dir/d.bin").unwrap(), b"");
+        assert_eq!(reader.open("e.bin").unwrap(), b"world");
+
+        let paths = reader
+            .fst()
+            .files()
+            .map(|(x, _)| x.to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [
+            "",
+            "A.bin",
+            "b.bin",
+            "dir",
+            "dir/d.bin",
+            "dir/sub",
+            "dir/sub/c.bin",
+            "e.bin"
+        ]);
+
+        let mut output = Vec::new();
+        let entry = reader.fst().find("A.bin").unwrap().clone();
+        reader.write_file(&entry, &mut output).unwrap();
+        assert_eq!(output, [0xFF; 0x21]);
+        let entry = reader.fst().find("dir").unwrap().clone();
+        assert!(reader.write_file(&entry, &mut output).is_err());
+
+        let files = reader
+            .files()
+            .map(|x| x.map(|(path, data)| (path.to_str().unwrap().to_string(), data)))
+            .collect::<picori::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(files, [
+            ("A.bin".to_string(), vec![0xFF; 0x21]),
+            ("b.bin".to_string(), b"hello".to_vec()),
+            ("dir/d.bin".to_string(), vec![]),
+            ("dir/sub/c.bin".to_string(), vec![1, 2, 3]),
+            ("e.bin".to_string(), b"world".to_vec()),
+        ]);
+    }
+
+    #[test]
+    fn builder_invalid_paths() {
+        let image = super::image(&[]);
+        let build = |files: &[(&str, &[u8])]| {
+            let boot = Boot::from_binary(&mut Cursor::new(&image)).unwrap();
+            let bi2 = Bi2::from_binary(&mut Cursor::new(&image[0x440..])).unwrap();
+            let apploader = Apploader::from_binary(&mut Cursor::new(&image[0x2440..])).unwrap();
+            let mut builder = GcmBuilder::new(boot, bi2, apploader, super::dol(&[]));
+            for (path, data) in files {
+                builder.add_file(*path, data.to_vec());
+            }
+            builder.build(&mut Vec::new())
+        };
+
+        assert!(build(&[("a", b"")]).is_ok());
+        assert!(build(&[("a", b""), ("a", b"")]).is_err());
+        assert!(build(&[("a", b""), ("a/b", b"")]).is_err());
+        assert!(build(&[("a/b", b""), ("a", b"")]).is_err());
+        assert!(build(&[("a//b", b"")]).is_err());
+    }
+
+    #[test]
+    fn fst_find() {
+        let image = super::image(&[
+            ("a.bin", b"abc"),
+            ("dir/b.bin", b"hello"),
+            ("dir/sub/c.bin", &[1, 2, 3, 4]),
+            ("dir/d.bin", b"world"),
+            ("e.bin", b""),
+        ]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        let fst = gcm.fst();
+
+        assert_eq!(fst.find(""), Some(&Entry::Root));
+        assert!(matches!(
+            fst.find("dir/sub/c.bin"),
+            Some(Entry::File { name, size: 4, .. }) if name == "c.bin"
+        ));
+        assert!(matches!(
+            fst.find("/dir/sub/"),
+            Some(Entry::Directory { name, .. }) if name == "sub"
+        ));
+        assert_eq!(fst.find("DIR/b.bin"), None);
+        assert_eq!(fst.find("dir/c.bin"), None);
+        assert_eq!(fst.find("a.bin/x"), None);
+    }
+
+    #[test]
+    fn fst_children() {
+        let image = super::image(&[
+            ("a.bin", b"abc"),
+            ("dir/b.bin", b"hello"),
+            ("dir/sub/c.bin", &[1, 2, 3, 4]),
+            ("dir/d.bin", b"world"),
+            ("e.bin", b""),
+        ]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        let fst = gcm.fst();
+        let names = |entry: &Entry| {
+            fst.children(entry)
+                .map(|x| match x {
+                    Entry::File { name, .. } | Entry::Directory { name, .. } => name.as_str(),
+                    Entry::Root => "",
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&Entry::Root), ["a.bin", "dir", "e.bin"]);
+        assert_eq!(names(fst.find("dir").unwrap()), ["b.bin", "sub", "d.bin"]);
+        assert_eq!(names(fst.find("dir/sub").unwrap()), ["c.bin"]);
+        assert!(names(fst.find("a.bin").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn is_gcm() {
+        let image = super::image(&[("a.bin", b"abc")]);
+        let mut reader = Cursor::new(&image);
+        assert!(gcm::is_gcm(&mut reader));
+        assert_eq!(reader.position(), 0);
+        assert!(!gcm::is_gcm(&mut Cursor::new(&image[4..])));
+        assert!(!gcm::is_gcm(&mut Cursor::new(&image[..0x1E])));
+    }
+
+    #[test]
+    fn apploader_trailer() {
+        let mut data = b"2022/01/01\0\0\0\0\0\0".to_vec();
+        for value in [0x8120_0000_u32, 4, 2, 0] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+
+        let apploader = Apploader::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(apploader.code(), [1, 2, 3, 4]);
+        assert_eq!(apploader.trailer(), [5, 6]);
+
+        data[0x14..0x18].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(Apploader::from_binary(&mut Cursor::new(&data)).is_err());
+        assert!(Apploader::from_binary(&mut Cursor::new(&data[..0x25])).is_err());
+    }
+
+    #[test]
+    fn multidisc() {
+        let image = super::image(&[("a.bin", b"abc"), ("b.bin", b"hello")]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert!(!gcm.fst().is_multidisc());
+        assert_eq!(gcm.disc_id(), 0);
+        assert_eq!(gcm.disc_count(), 1);
+
+        // second disc without the data of b.bin
+        let Entry::File { offset, .. } = entry(&gcm, "b.bin") else {
+            unreachable!()
+        };
+        let mut image = image[..offset as usize].to_vec();
+        image[0x06] = 1;
+        let fst_size = u32::from_be_bytes(image[0x428..0x42C].try_into().unwrap());
+        image[0x42C..0x430].copy_from_slice(&(fst_size + 0x20).to_be_bytes());
+
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        assert!(gcm.fst().is_multidisc());
+        assert_eq!(gcm.disc_id(), 1);
+        assert_eq!(gcm.disc_count(), 2);
+        assert!(matches!(entry(&gcm, "a.bin"), Entry::File {
+            on_other_disc: false,
+            ..
+        }));
+        assert!(matches!(entry(&gcm, "b.bin"), Entry::File {
+            on_other_disc: true,
+            ..
+        }));
+
+        let mut reader = Cursor::new(&image);
+        assert_eq!(gcm.open_file(&mut reader, "a.bin").unwrap(), b"abc");
+        assert!(matches!(
+            gcm.open_file(&mut reader, "b.bin"),
+            Err(picori::Error::Parse(picori::error::ParseProblem::InvalidData(..)))
+        ));
+
+        let mut files = Vec::new();
+        gcm.extract_all(&mut reader, |path, data| {
+            files.push((path.to_str().unwrap().to_string(), data.to_vec()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(files, [("a.bin".to_string(), b"abc".to_vec())]);
+
+        let mut reader = GcmReader::new(Cursor::new(&image)).unwrap();
+        let files = reader
+            .files()
+            .map(|x| x.map(|(path, data)| (path.to_str().unwrap().to_string(), data)))
+            .collect::<picori::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(files, [("a.bin".to_string(), b"abc".to_vec())]);
+    }
+
+    #[test]
+    fn truncated() {
+        use picori::error::ParseProblem;
+        use picori::Error;
+
+        // single-disc image without the data of b.bin
+        let image = super::image(&[("a.bin", b"abc"), ("b.bin", b"hello")]);
+        let gcm = Gcm::from_binary(&mut Cursor::new(&image)).unwrap();
+        let Entry::File { offset, .. } = entry(&gcm, "b.bin") else {
+            unreachable!()
+        };
+        let image = &image[..offset as usize];
+        assert!(matches!(
+            Gcm::from_binary(&mut Cursor::new(image)),
+            Err(Error::Parse(ParseProblem::Context("fst", problem, _)))
+                if matches!(*problem, ParseProblem::InvalidRange(..))
+        ));
+
+        assert!(matches!(
+            GcmReader::new(Cursor::new(image)),
+            Err(Error::Parse(ParseProblem::Context("fst", problem, _)))
+                if matches!(*problem, ParseProblem::InvalidRange(..))
+        ));
+
+        // data read through a parsed single-disc gcm is never skipped
+        let mut files = Vec::new();
+        let result = gcm.extract_all(&mut Cursor::new(image), |path, _| {
+            files.push(path.to_path_buf());
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn fst_invalid_directory_end() {
+        // root, "d" directory with the given end, "f" file
+        let fst = |end: u32, parent: u32| {
+            let mut fst = Vec::new();
+            for entry in [[0x0100_0000_u32, 0, 3], [0x0100_0000, parent, end], [2, 0, 0]] {
+                for value in entry {
+                    fst.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            fst.extend_from_slice(b"d\0f\0");
+            let size = fst.len();
+            gcm::Fst::from_binary(&mut Cursor::new(fst), size)
+        };
+
+        assert!(fst(3, 0).is_ok());
+        assert!(fst(2, 0).is_ok());
+        assert!(fst(0, 0).is_err());
+        assert!(fst(1, 0).is_err());
+        assert!(fst(4, 0).is_err());
+        assert!(fst(u32::MAX, 0).is_err());
+        assert!(fst(3, 1).is_err());
+
+        // three entries don't fit in 12 bytes
+        let fst = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3];
+        assert!(gcm::Fst::from_binary(&mut Cursor::new(fst), 12).is_err());
+    }
+
+    #[test]
+    fn fst_entry_limit() {
+        use picori::error::ParseProblem;
+        use picori::Error;
+
+        let image = super::image(&[("a.bin", b"abc"), ("dir/b.bin", b"hello")]);
+        let size = u32::from_be_bytes(image[0x428..0x42C].try_into().unwrap()) as usize;
+        let fst = &image[0x2600..0x2600 + size];
+        assert!(gcm::Fst::from_binary_with_limit(&mut Cursor::new(fst), size, 4).is_ok());
+        assert!(matches!(
+            gcm::Fst::from_binary_with_limit(&mut Cursor::new(fst), size, 3),
+            Err(Error::Parse(ParseProblem::LimitExceeded(_, 4, 3, _)))
+        ));
+
+        let mut fst = vec![0; 12];
+        fst[8..12].copy_from_slice(&(gcm::fst::DEFAULT_MAX_ENTRIES as u32 + 1).to_be_bytes());
+        assert!(matches!(
+            gcm::Fst::from_binary(&mut Cursor::new(fst), 12),
+            Err(Error::Parse(ParseProblem::LimitExceeded(..)))
+        ));
+    }
+
+    #[test]
+    fn error_context() {
+        use picori::error::ParseProblem;
+        use picori::Error;
+
+        let mut image = super::image(&[("a.bin", b"abc")]);
+        image[0x2608..0x260C].copy_from_slice(&u32::MAX.to_be_bytes());
+        let error = Gcm::from_binary(&mut Cursor::new(&image)).err().unwrap();
+        assert!(matches!(
+            &error,
+            Error::Parse(ParseProblem::Context("fst", problem, _))
+                if matches!(**problem, ParseProblem::LimitExceeded(..))
+        ));
+        assert!(error.to_string().starts_with("parse: fst: fst entry count limit exceeded"));
+    }
+
+    #[test]
+    fn ciso() {
+        let image = super::image(&[("a.bin", b"abc"), ("dir/b.bin", b"hello")]);
+        let mut ciso = Cursor::new(Vec::new());
+        CisoBuilder::new(0x800)
+            .unwrap()
+            .build(&mut Cursor::new(&image), &mut ciso)
+            .unwrap();
+
+        let mut input = Cursor::new(ciso.into_inner());
+        let mut reader = CisoReader::new(&mut input).unwrap();
+        let gcm = Gcm::from_binary(&mut reader).unwrap();
+        assert_eq!(gcm.boot().game_name, "TEST");
+        assert_eq!(gcm.open_file(&mut reader, "dir/b.bin").unwrap(), b"hello");
+    }
 }
@@ -14,8 +14,7 @@ mod jis_x_0201 {
             .filter(|x| *x != 0x5c)
             .filter(|x| *x != 0x7e)
             .zip(result)
-            .map(|(a, b)| (a as u8) as char == b)
-            .all(|x| x);
+            .all(|(a, b)| (a as u8) as char == b);
 
         assert!(ok);
         assert!([0x5c].iter().jisx0201().next().unwrap().unwrap() == '\u{00a5}');
@@ -67,4 +66,10 @@ mod jis_x_0201 {
         assert_eq!(&JisX0201::all(data).unwrap()[..], "abc\0def");
         assert!(&JisX0201::all(b"abc\xa0def").is_err());
     }
+
+    #[test]
+    fn lossy() {
+        let data = b"a\x80b\xe0";
+        assert_eq!(JisX0201::iter(data).lossy().collect::<String>(), "a\u{fffd}b\u{fffd}");
+    }
 }
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod latin1 {
+    use picori::{Latin1, Latin1IteratorExt, Parser, SliceParser};
+
+    #[test]
+    fn ok() {
+        let result = (0..=0xff).latin1().collect::<Result<String, _>>();
+        assert!(result.is_ok());
+
+        let ok = (0..=0xff)
+            .zip(result.unwrap().chars())
+            .all(|(a, b)| a as u8 as char == b);
+        assert!(ok);
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(&Latin1::first(b"Pok\xe9mon\0def").unwrap()[..], "Pokémon");
+    }
+
+    #[test]
+    fn all() {
+        let data = b"abc\0d\xfcf";
+        assert_eq!(&Latin1::all(data).unwrap()[..], "abc\0düf");
+    }
+
+    #[test]
+    fn str_fixed() {
+        let mut data = SliceParser::new(b"Z\xfcrich\0\0rest");
+        assert_eq!(data.str_fixed::<8, Latin1>().unwrap(), "Zürich");
+        assert_eq!(data.str_fixed::<4, Latin1>().unwrap(), "rest");
+    }
+}
@@ -1 +1,4 @@
+#![allow(clippy::module_inception)]
+
+#[cfg(feature = "std")]
 mod gcm;
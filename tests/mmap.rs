@@ -0,0 +1,44 @@
+#[cfg(all(test, feature = "mmap"))]
+mod mmap {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use picori::{Error, MmapReader, Parser, Seeker};
+
+    fn file(name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("picori-{}-{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn parse() {
+        let path = file("parse", &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        let mut reader = MmapReader::open(&path).unwrap();
+        assert_eq!(reader.data().len(), 6);
+        assert_eq!(reader.bu16().unwrap(), 0x0102);
+        assert_eq!(reader.read_slice(2).unwrap(), [0x03, 0x04]);
+        assert_eq!(reader.position().unwrap(), 4);
+        assert_eq!(reader.remaining(), [0x05, 0x06]);
+        assert!(reader.read_slice(3).is_err());
+        assert!(matches!(reader.bu32(), Err(Error::Decoding(_))));
+        assert_eq!(reader.position().unwrap(), 4);
+
+        assert_eq!(reader.goto(1).unwrap(), 1);
+        assert_eq!(reader.u8().unwrap(), 0x02);
+        assert!(reader.goto(7).is_err());
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 5);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [0x06]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn dol() {
+        let mut reader = MmapReader::open("assets/tests/dol/test1.dol").unwrap();
+        let dol = picori::Dol::from_binary(&mut reader).unwrap();
+        let data = include_bytes!("../assets/tests/dol/test1.dol");
+        assert_eq!(reader.data(), data);
+        assert!(dol.rom_copy_info().is_some());
+    }
+}
@@ -0,0 +1,399 @@
+#[cfg(all(test, feature = "std"))]
+mod rarc {
+    use std::io::Cursor;
+
+    use picori::rarc::name_hash as hash;
+    use picori::{rarc, RarcReader, RarcWriter};
+
+    fn align(value: usize) -> usize { (value + 0x1F) & !0x1F }
+
+    /// Build a RARC archive from slash-separated paths. Folder names must be
+    /// unique. If `archive_relative` is set, the file data offset in the
+    /// header is relative to the start of the archive instead of the end of
+    /// the header.
+    pub fn build(files: &[(&str, &[u8])], archive_relative: bool) -> Vec<u8> {
+        // folders in order of discovery, root first
+        let mut folders = vec![(String::new(), String::from("root"))];
+        for (path, _) in files {
+            let parts = path.split('/').collect::<Vec<_>>();
+            for i in 1..parts.len() {
+                let folder = parts[..i].join("/");
+                if !folders.iter().any(|x| x.0 == folder) {
+                    folders.push((folder, parts[i - 1].to_string()));
+                }
+            }
+        }
+
+        let mut strings = b".\0..\0".to_vec();
+        let mut string = |name: &str| {
+            let offset = strings.len();
+            strings.extend_from_slice(name.as_bytes());
+            strings.push(0);
+            offset
+        };
+
+        let mut nodes = Vec::new();
+        let mut directories = Vec::new();
+        let mut data = Vec::new();
+        let mut file_index = 0_u16;
+        for (index, (folder, name)) in folders.iter().enumerate() {
+            let first = directories.len() as u32;
+            let prefix = if folder.is_empty() {
+                String::new()
+            } else {
+                format!("{folder}/")
+            };
+
+            let children = files
+                .iter()
+                .filter_map(|(path, x)| Some((path.strip_prefix(&prefix)?, x)))
+                .filter(|(path, _)| !folder.is_empty() || !path.is_empty());
+            let mut seen = Vec::new();
+            for (path, file) in children {
+                let child = path.split('/').next().unwrap();
+                if seen.contains(&child) {
+                    continue;
+                }
+                seen.push(child);
+
+                let name_offset = string(child) as u16;
+                if path.contains('/') {
+                    let node = folders.iter().position(|x| x.1 == child).unwrap();
+                    directories.push((
+                        0xFFFF,
+                        hash(child),
+                        0x0200,
+                        name_offset,
+                        node as u32,
+                        0x10,
+                    ));
+                } else {
+                    data.resize(align(data.len()), 0);
+                    let offset = data.len() as u32;
+                    data.extend_from_slice(file);
+                    directories.push((
+                        file_index,
+                        hash(child),
+                        0x1100,
+                        name_offset,
+                        offset,
+                        file.len() as u32,
+                    ));
+                    file_index += 1;
+                }
+            }
+
+            let parent = folders
+                .iter()
+                .position(|x| Some(x.0.as_str()) == folder.rsplit_once('/').map(|x| x.0))
+                .map_or(0xFFFF_FFFF, |x| x as u32);
+            directories.push((0xFFFF, hash("."), 0x0200, 0, index as u32, 0x10));
+            directories.push((0xFFFF, hash(".."), 0x0200, 2, parent, 0x10));
+
+            let count = directories.len() as u32 - first;
+            let identifier = if index == 0 {
+                *b"ROOT"
+            } else {
+                let mut identifier = *b"    ";
+                for (a, b) in identifier.iter_mut().zip(name.to_uppercase().bytes()) {
+                    *a = b;
+                }
+                identifier
+            };
+            let name_offset = string(name) as u32;
+            nodes.push((identifier, name_offset, hash(name), count as u16, first));
+        }
+
+        let node_offset = 0x20;
+        let directory_offset = align(node_offset + nodes.len() * 0x10);
+        let string_table_offset = align(directory_offset + directories.len() * 0x14);
+        let string_table_length = align(strings.len());
+        let file_offset = string_table_offset + string_table_length;
+        let archive_length = 0x20 + file_offset + data.len();
+
+        let mut rarc = Vec::new();
+        rarc.extend_from_slice(b"RARC");
+        rarc.extend_from_slice(&(archive_length as u32).to_be_bytes());
+        rarc.extend_from_slice(&0x20_u32.to_be_bytes());
+        let header_file_offset = if archive_relative {
+            file_offset + 0x20
+        } else {
+            file_offset
+        };
+        rarc.extend_from_slice(&(header_file_offset as u32).to_be_bytes());
+        rarc.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        rarc.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        rarc.extend_from_slice(&[0; 8]);
+        for value in [
+            nodes.len(),
+            node_offset,
+            directories.len(),
+            directory_offset,
+            string_table_length,
+            string_table_offset,
+        ] {
+            rarc.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        rarc.extend_from_slice(&file_index.to_be_bytes());
+        rarc.extend_from_slice(&[0x01, 0x00, 0, 0, 0, 0]);
+
+        for (identifier, name_offset, hash, count, first) in nodes {
+            rarc.extend_from_slice(&identifier);
+            rarc.extend_from_slice(&name_offset.to_be_bytes());
+            rarc.extend_from_slice(&hash.to_be_bytes());
+            rarc.extend_from_slice(&count.to_be_bytes());
+            rarc.extend_from_slice(&first.to_be_bytes());
+        }
+
+        rarc.resize(0x20 + directory_offset, 0);
+        for (index, hash, kind, name_offset, offset, size) in directories {
+            rarc.extend_from_slice(&index.to_be_bytes());
+            rarc.extend_from_slice(&hash.to_be_bytes());
+            rarc.extend_from_slice(&(kind as u16).to_be_bytes());
+            rarc.extend_from_slice(&name_offset.to_be_bytes());
+            rarc.extend_from_slice(&offset.to_be_bytes());
+            rarc.extend_from_slice(&size.to_be_bytes());
+            rarc.extend_from_slice(&[0; 4]);
+        }
+
+        rarc.resize(0x20 + string_table_offset, 0);
+        rarc.extend_from_slice(&strings);
+        rarc.resize(0x20 + file_offset, 0);
+        rarc.extend_from_slice(&data);
+        rarc
+    }
+
+    fn read_all<T: picori::Parser + picori::Seeker>(
+        reader: &mut RarcReader<T>,
+    ) -> Vec<(String, Vec<u8>)> {
+        let files = reader
+            .nodes()
+            .filter_map(|x| match x {
+                rarc::Node::File { name, offset, size } => Some((name.name, offset, size)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        files
+            .into_iter()
+            .map(|(name, offset, size)| (name, reader.file_data(offset, size).unwrap()))
+            .collect()
+    }
+
+    static FILES: &[(&str, &[u8])] = &[
+        ("a.txt", b"hello"),
+        ("dir/b.bin", &[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        ("dir/c.bin", &[0xAA; 0x1C]),
+    ];
+
+    #[test]
+    fn header_relative() {
+        let data = build(FILES, false);
+        let mut reader = RarcReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(read_all(&mut reader), [
+            ("a.txt".to_string(), b"hello".to_vec()),
+            ("b.bin".to_string(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            ("c.bin".to_string(), vec![0xAA; 0x1C]),
+        ]);
+    }
+
+    #[test]
+    fn archive_relative() {
+        let data = build(FILES, true);
+        let mut reader = RarcReader::from_bytes(&data).unwrap();
+        assert_eq!(read_all(&mut reader), [
+            ("a.txt".to_string(), b"hello".to_vec()),
+            ("b.bin".to_string(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            ("c.bin".to_string(), vec![0xAA; 0x1C]),
+        ]);
+    }
+
+    #[test]
+    fn invalid_magic() {
+        let mut data = build(FILES, false);
+        data[0] = 0;
+        assert!(RarcReader::new(Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = build(FILES, false);
+        data.truncate(data.len() - 1);
+        assert!(RarcReader::from_bytes(&data).is_err());
+        assert!(RarcReader::new(Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn file_data_out_of_range() {
+        let data = build(FILES, false);
+        let length = data.len() as u64;
+        let mut reader = RarcReader::new(Cursor::new(data)).unwrap();
+        assert!(reader.file_data(length - 4, 4).is_ok());
+        assert!(reader.file_data(length - 4, 5).is_err());
+        assert!(reader.file_data(0, 4).is_err());
+    }
+
+    #[test]
+    fn writer_round_trip() {
+        let files: &[(&str, &[u8])] = &[
+            ("a.txt", b"hello"),
+            ("dir/b.bin", &[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            ("dir/sub/c.bin", &[0xAA; 0x1C]),
+            ("other/d.bin", &[0x55; 0x40]),
+        ];
+
+        let mut writer = RarcWriter::new("archive");
+        for (path, data) in files {
+            writer.add_file(*path, data.to_vec());
+        }
+        let data = writer.build().unwrap();
+        assert_eq!(&data[..4], b"RARC");
+        assert_eq!(u32::from_be_bytes(data[4..8].try_into().unwrap()), data.len() as u32);
+
+        let mut reader = RarcReader::new(Cursor::new(data)).unwrap();
+        let mut result = read_all(&mut reader);
+        result.sort();
+        let mut expected = files
+            .iter()
+            .map(|(path, data)| (path.rsplit('/').next().unwrap().to_string(), data.to_vec()))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(result, expected);
+
+        let folders = reader
+            .nodes()
+            .filter_map(|x| match x {
+                rarc::Node::DirectoryBegin { name } => Some(name.name),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(folders, ["archive", "dir", "sub", "other"]);
+    }
+
+    #[test]
+    fn writer_round_trip_same_folder_names() {
+        let files: &[(&str, &[u8])] = &[
+            ("a/data/x.bin", &[1, 2, 3]),
+            ("a/data/y.bin", &[4, 5]),
+            ("b/data/z.bin", &[6; 0x30]),
+        ];
+
+        let mut writer = RarcWriter::new("archive");
+        for (path, data) in files {
+            writer.add_file(*path, data.to_vec());
+        }
+        let data = writer.build().unwrap();
+        let mut reader = RarcReader::from_bytes(&data).unwrap();
+
+        let mut path = Vec::new();
+        let mut result = Vec::new();
+        for node in reader.nodes() {
+            match node {
+                rarc::Node::DirectoryBegin { name } => path.push(name.name),
+                rarc::Node::DirectoryEnd { .. } => {
+                    path.pop();
+                },
+                rarc::Node::File { name, offset, size } => {
+                    result.push((format!("{}/{}", path[1..].join("/"), name), offset, size))
+                },
+                _ => {},
+            }
+        }
+        let result = result
+            .into_iter()
+            .map(|(path, offset, size)| (path, reader.file_data(offset, size).unwrap()))
+            .collect::<Vec<_>>();
+
+        let expected = files
+            .iter()
+            .map(|(path, data)| (path.to_string(), data.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn read_path() {
+        let mut writer = RarcWriter::new("archive");
+        writer
+            .add_file("a.txt", b"hello".to_vec())
+            .add_file("dir/b.bin", vec![1, 2, 3])
+            .add_file("dir/sub/c.bin", vec![0xAA; 0x1C]);
+        let data = writer.build().unwrap();
+        let mut reader = RarcReader::from_bytes(&data).unwrap();
+
+        assert_eq!(reader.read_path("a.txt").unwrap(), b"hello");
+        assert_eq!(reader.read_path("/dir/b.bin").unwrap(), [1, 2, 3]);
+        assert_eq!(reader.read_path("dir/sub/c.bin").unwrap(), [0xAA; 0x1C]);
+        assert!(reader.read_path("dir").is_err());
+        assert!(reader.read_path("b.bin").is_err());
+        assert!(reader.read_path("a.txt/b.bin").is_err());
+
+        assert!(reader.exists("dir/sub"));
+        assert!(reader.exists("dir/sub/c.bin"));
+        assert!(!reader.exists("dir/sub/d.bin"));
+        assert!(!reader.exists(""));
+    }
+
+    #[test]
+    fn read_path_same_folder_names() {
+        let mut writer = RarcWriter::new("archive");
+        writer
+            .add_file("a/data/x.bin", vec![1])
+            .add_file("b/data/y.bin", vec![2]);
+        let data = writer.build().unwrap();
+        let mut reader = RarcReader::from_bytes(&data).unwrap();
+
+        assert_eq!(reader.read_path("a/data/x.bin").unwrap(), [1]);
+        assert_eq!(reader.read_path("b/data/y.bin").unwrap(), [2]);
+        assert!(!reader.exists("a/data/y.bin"));
+        assert!(!reader.exists("b/data/x.bin"));
+    }
+
+    #[test]
+    fn writer_invalid_path() {
+        let mut writer = RarcWriter::new("archive");
+        writer.add_file("a", vec![]).add_file("a/b", vec![]);
+        assert!(writer.build().is_err());
+
+        let mut writer = RarcWriter::new("archive");
+        writer.add_file("a", vec![]).add_file("a", vec![]);
+        assert!(writer.build().is_err());
+
+        let mut writer = RarcWriter::new("archive");
+        writer.add_file("a//b", vec![]);
+        assert!(writer.build().is_err());
+    }
+
+    #[test]
+    fn name_hash() {
+        assert_eq!(rarc::name_hash(""), 0);
+        assert_eq!(rarc::name_hash("."), 0x2E);
+        assert_eq!(rarc::name_hash(".."), 0xB8);
+        assert_eq!(rarc::name_hash("root"), 0x11AE);
+        assert_eq!(rarc::name_hash("archive.bin"), 0xC9C7);
+        assert_eq!(rarc::name_hash("a_very_long_file_name.dat"), 0x13B7);
+        assert_eq!(rarc::NamedHash::new("root").hash, 0x11AE);
+    }
+
+    #[test]
+    fn verify_hashes() {
+        let mut data = build(FILES, false);
+        let reader = RarcReader::new(Cursor::new(data.clone())).unwrap();
+        assert!(reader.verify_hashes().is_ok());
+
+        // hash of the first directory entry
+        let directory_offset = 0x20 + u32::from_be_bytes(data[0x2C..0x30].try_into().unwrap());
+        data[directory_offset as usize + 3] ^= 1;
+        let reader = RarcReader::new(Cursor::new(data)).unwrap();
+        assert!(reader.verify_hashes().is_err());
+    }
+
+    #[test]
+    fn is_rarc() {
+        let data = build(FILES, false);
+        let mut reader = Cursor::new(&data);
+        assert!(rarc::is_rarc(&mut reader));
+        assert_eq!(reader.position(), 0);
+        assert!(!rarc::is_rarc(&mut Cursor::new(&data[1..])));
+    }
+}
@@ -1,4 +1,4 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod rel {
     use std::io::Cursor;
 
@@ -44,6 +44,343 @@ mod rel {
         assert_eq!(reloctions, 450);
     }
 
+    #[test]
+    fn test0_relocation_data() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        let relocation_offset = rel.relocation_offset.unwrap() as usize;
+        let relocation_data = rel.relocation_data().unwrap();
+        assert_eq!(relocation_data.len(), 0xe40);
+        assert_eq!(
+            relocation_data.as_slice(),
+            &data[relocation_offset..relocation_offset + relocation_data.len()]
+        );
+    }
+
+    #[test]
+    fn test0_to_binary() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        let mut output = Cursor::new(Vec::new());
+        rel.to_binary(&mut output).unwrap();
+        let output = output.into_inner();
+        assert_eq!(output.as_slice(), data.as_slice());
+
+        let rel2 = Rel::from_binary(Cursor::new(&output)).unwrap();
+        assert_eq!(rel2.module, rel.module);
+        assert_eq!(rel2.version, rel.version);
+        assert_eq!(rel2.sections.len(), rel.sections.len());
+        assert_eq!(rel2.relocations().count(), rel.relocations().count());
+    }
+
+    #[test]
+    fn to_binary_versions() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        for (version, section_offset) in [(1, 0x40_u32), (2, 0x48), (3, 0x4C)] {
+            rel.version = version;
+            let mut output = Cursor::new(Vec::new());
+            rel.to_binary(&mut output).unwrap();
+            let output = output.into_inner();
+            assert_eq!(output[0x10..0x14], section_offset.to_be_bytes());
+
+            let rel2 = Rel::from_binary(Cursor::new(&output)).unwrap();
+            assert_eq!(rel2.version, version);
+            assert_eq!(rel2.relocations().count(), 450);
+            assert!(rel2
+                .sections
+                .iter()
+                .zip(rel.sections.iter())
+                .all(|(a, b)| a.data == b.data && a.size == b.size));
+        }
+
+        rel.version = 4;
+        assert!(matches!(
+            rel.to_binary(&mut Cursor::new(Vec::new())),
+            Err(picori::Error::Build(picori::error::BuildProblem::InvalidData(..)))
+        ));
+    }
+
+    #[test]
+    fn name() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        let mut framework = vec![0_u8; 0x4216];
+        framework.extend_from_slice(b"d_a_test.rel\0");
+        rel.name_size = 13;
+        assert_eq!(
+            rel.name(&mut Cursor::new(&framework)).unwrap(),
+            Some("d_a_test.rel".to_string())
+        );
+
+        rel.name_size = 0x28;
+        assert!(rel.name(&mut Cursor::new(&framework)).is_err());
+
+        rel.name_size = 0;
+        assert_eq!(rel.name(&mut Cursor::new(&framework)).unwrap(), None);
+    }
+
+    #[test]
+    fn import_table_end() {
+        let table = rel::ImportTable {
+            module:  0,
+            offset:  0,
+            imports: vec![rel::Import {
+                kind:    rel::ImportKind::DolphinSection,
+                section: 1,
+                offset:  0,
+                addend:  0,
+            }],
+        };
+
+        let mut output = Vec::new();
+        table.to_binary(&mut output).unwrap();
+        assert_eq!(output, [
+            0x00, 0x00, 0xca, 0x01, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0xcb, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]);
+    }
+
+    fn import(kind: rel::ImportKind, section: u8, offset: u16, addend: u32) -> rel::Import {
+        rel::Import {
+            kind,
+            section,
+            offset,
+            addend,
+        }
+    }
+
+    #[test]
+    fn apply_relocations() {
+        use rel::ImportKind::{
+            Addr16Ha, Addr16Hi, Addr16Lo, Addr32, DolphinEnd, DolphinSection, Rel14, Rel24,
+        };
+
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        rel.sections = vec![
+            rel::Section::default(),
+            rel::Section {
+                offset: 0x100,
+                size: 0x20,
+                executable: true,
+                data: [0x48000001_u32, 0x3C600000, 0x38630000, 0, 0, 0x41820000, 0, 0]
+                    .iter()
+                    .flat_map(|x| x.to_be_bytes())
+                    .collect(),
+                ..Default::default()
+            },
+            rel::Section {
+                offset: 0x200,
+                size: 0x8,
+                data: vec![0; 8],
+                ..Default::default()
+            },
+        ];
+        rel.import_tables = vec![
+            rel::ImportTable {
+                module:  400,
+                offset:  0,
+                imports: vec![
+                    import(DolphinSection, 1, 0, 0),
+                    import(Rel24, 2, 0, 0x4),
+                    import(Addr16Ha, 2, 6, 0x8000),
+                    import(Addr16Lo, 2, 4, 0x8000),
+                    import(Rel14, 1, 0xA, 0),
+                    import(DolphinSection, 2, 0, 0),
+                    import(Addr32, 1, 0, 0x8),
+                    import(DolphinEnd, 0, 0, 0),
+                ],
+            },
+            rel::ImportTable {
+                module:  0,
+                offset:  0,
+                imports: vec![
+                    import(DolphinSection, 1, 0, 0),
+                    import(Addr32, 0, 0xC, 0x8000_1234),
+                    import(Addr16Hi, 0, 4, 0x8000_1234),
+                    import(DolphinEnd, 0, 0, 0),
+                ],
+            },
+        ];
+
+        let resolver = |module: u32, section: u32| match module {
+            0 => Some(0),
+            400 => [0, 0x8050_0100, 0x8050_0200].get(section as usize).copied(),
+            _ => None,
+        };
+        rel.apply_relocations(resolver).unwrap();
+
+        let text = rel.sections[1]
+            .data
+            .chunks(4)
+            .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(text, [
+            0x48000105, 0x3C608051, 0x38638200, 0x80001234, 0x80000000, 0x4182FFEC, 0, 0
+        ]);
+        assert_eq!(rel.sections[2].data, [0x80, 0x50, 0x01, 0x08, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn apply_relocations_sections() {
+        use rel::ImportKind::{Addr32, DolphinEnd, DolphinSection};
+
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        rel.sections = vec![
+            rel::Section::default(),
+            rel::Section {
+                offset: 0x100,
+                size: 0x8,
+                data: vec![0; 8],
+                ..Default::default()
+            },
+            // .bss, without data in the file
+            rel::Section {
+                size: 0x40,
+                ..Default::default()
+            },
+        ];
+        rel.import_tables = vec![
+            rel::ImportTable {
+                module:  400,
+                offset:  0,
+                imports: vec![
+                    import(DolphinSection, 1, 0, 0),
+                    import(Addr32, 2, 0, 0x10),
+                    import(DolphinEnd, 0, 0, 0),
+                ],
+            },
+            rel::ImportTable {
+                module:  5,
+                offset:  0,
+                imports: vec![
+                    import(DolphinSection, 1, 0, 0),
+                    import(Addr32, 3, 4, 0x8),
+                    import(DolphinEnd, 0, 0, 0),
+                ],
+            },
+        ];
+
+        let resolver = |module: u32, section: u32| match (module, section) {
+            (400, 1) => Some(0x8050_0100),
+            (400, 2) => Some(0x8060_0000),
+            (5, 3) => Some(0x8070_0200),
+            _ => None,
+        };
+        rel.apply_relocations(resolver).unwrap();
+        assert_eq!(rel.sections[1].data, [0x80, 0x60, 0x00, 0x10, 0x80, 0x70, 0x02, 0x08]);
+
+        let error = rel
+            .apply_relocations(|module, section| resolver(module, section).filter(|_| module != 5))
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            picori::Error::Build(picori::error::BuildProblem::UnresolvedModules(ref x, _))
+                if x == &[5]
+        ));
+    }
+
+    #[test]
+    fn relocation_map() {
+        use rel::ImportKind::{Addr32, DolphinEnd, DolphinNop, DolphinSection, Rel24};
+
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        rel.import_tables = vec![rel::ImportTable {
+            module:  400,
+            offset:  0,
+            imports: vec![
+                import(DolphinSection, 1, 0, 0),
+                import(Rel24, 2, 0x10, 0x4),
+                import(DolphinNop, 0, 0xFFFF, 0),
+                import(Addr32, 2, 0x1, 0x8),
+                import(DolphinEnd, 0, 0, 0),
+            ],
+        }];
+
+        let map = rel.relocation_map();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&(1, 0x10)].kind, Rel24);
+        assert_eq!(map[&(1, 0x10)].reference.offset, 0x4);
+        assert_eq!(map[&(1, 0x10 + 0xFFFF + 0x1)].kind, Addr32);
+        assert_eq!(map[&(1, 0x10 + 0xFFFF + 0x1)].reference.section, 2);
+    }
+
+    #[test]
+    fn relocations_for_section() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        let mut total = 0;
+        for section in 0..rel.sections.len() as u32 {
+            let relocations = rel.relocations_for_section(section);
+            assert!(relocations.iter().all(|x| x.target.section == section));
+            assert!(relocations
+                .windows(2)
+                .all(|x| x[0].target.offset <= x[1].target.offset));
+            total += relocations.len();
+        }
+        assert_eq!(total, rel.relocations().count());
+        assert!(!rel.relocations_for_section(1).is_empty());
+        assert!(rel.relocations_for_section(100).is_empty());
+    }
+
+    #[test]
+    fn apply_relocations_unresolved() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        let original = rel.sections.clone();
+        let error = rel.apply_relocations(|_, _| None).unwrap_err();
+        assert!(matches!(
+            error,
+            picori::Error::Build(picori::error::BuildProblem::UnresolvedModules(ref x, _))
+                if x == &[0, 400]
+        ));
+        assert!(rel
+            .sections
+            .iter()
+            .zip(original.iter())
+            .all(|(a, b)| a.data == b.data));
+    }
+
+    #[test]
+    fn apply_relocations_out_of_range() {
+        use rel::ImportKind::{Addr32, DolphinEnd, DolphinSection};
+
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        rel.sections = vec![
+            rel::Section::default(),
+            rel::Section {
+                offset: 0x100,
+                size: 0x8,
+                data: vec![0; 8],
+                ..Default::default()
+            },
+        ];
+        // the second relocation is past the end of the section
+        rel.import_tables = vec![rel::ImportTable {
+            module:  400,
+            offset:  0,
+            imports: vec![
+                import(DolphinSection, 1, 0, 0),
+                import(Addr32, 1, 0, 0x10),
+                import(Addr32, 1, 6, 0x10),
+                import(DolphinEnd, 0, 0, 0),
+            ],
+        }];
+
+        let error = rel
+            .apply_relocations(|_, section| Some(0x8050_0000 + section * 0x100))
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            picori::Error::Build(picori::error::BuildProblem::InvalidRange(..))
+        ));
+        assert_eq!(rel.sections[1].data, [0; 8]);
+    }
+
     #[test]
     fn test0_v1() {
         let data = include_bytes!("../assets/tests/rel/test0_v1.rel");
@@ -102,6 +439,30 @@ mod rel {
         assert!(rel.is_err());
     }
 
+    #[test]
+    fn import_kind_is_relocation() {
+        assert!(rel::ImportKind::Addr32.is_relocation());
+        assert!(rel::ImportKind::Rel14.is_relocation());
+        assert!(!rel::ImportKind::None.is_relocation());
+        assert!(!rel::ImportKind::DolphinSection.is_relocation());
+        assert!(!rel::ImportKind::DolphinEnd.is_relocation());
+
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        assert!(rel.relocations().all(|x| x.kind.is_relocation()));
+    }
+
+    #[test]
+    fn test0_import_section() {
+        let data = include_bytes!("../assets/tests/rel/test0_import_section.rel");
+        let rel = Rel::from_binary(Cursor::new(&data));
+        assert!(rel.is_err());
+
+        let data = include_bytes!("../assets/tests/rel/test0_import_reference_section.rel");
+        let rel = Rel::from_binary(Cursor::new(&data));
+        assert!(rel.is_err());
+    }
+
     #[test]
     fn test0_relocation_without_section() {
         let data = include_bytes!("../assets/tests/rel/test0_relocation_without_section.rel");
@@ -125,4 +486,119 @@ mod rel {
         assert_eq!(rel.module, 202);
         assert_eq!(rel.version, 3);
     }
+
+    #[test]
+    fn is_rel() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut reader = Cursor::new(&data);
+        assert!(rel::is_rel(&mut reader));
+        assert_eq!(reader.position(), 0);
+
+        let mut data = data.to_vec();
+        data[0x1F] = 4; // version
+        assert!(!rel::is_rel(&mut Cursor::new(&data)));
+        assert!(!rel::is_rel(&mut Cursor::new(&data[..0x20])));
+    }
+
+    #[test]
+    fn headers_only() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let full = Rel::from_binary(Cursor::new(&data)).unwrap();
+        let mut rel = Rel::from_binary_headers_only(Cursor::new(&data)).unwrap();
+        assert_eq!(rel.sections.len(), full.sections.len());
+        assert!(rel.sections.iter().all(|x| x.data.is_empty()));
+        assert_eq!(rel.relocations().count(), full.relocations().count());
+        assert!(matches!(
+            rel.to_binary(&mut Cursor::new(Vec::new())),
+            Err(picori::Error::Build(picori::error::BuildProblem::InvalidData(..)))
+        ));
+
+        for i in 0..rel.sections.len() {
+            rel.load_section(&mut Cursor::new(&data), i).unwrap();
+            assert_eq!(rel.sections[i].offset, full.sections[i].offset);
+            assert_eq!(rel.sections[i].data, full.sections[i].data);
+        }
+
+        let mut output = Cursor::new(Vec::new());
+        rel.to_binary(&mut output).unwrap();
+        assert_eq!(output.into_inner().as_slice(), data.as_slice());
+
+        let index = rel.sections.len();
+        assert!(rel.load_section(&mut Cursor::new(&data), index).is_err());
+    }
+
+    #[test]
+    fn guess_section_name() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary_headers_only(Cursor::new(&data)).unwrap();
+        let names = (0..7)
+            .map(|i| rel.guess_section_name(i))
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["", ".text", ".ctors", ".dtors", ".rodata", ".data", ""]);
+        assert_eq!(rel.guess_section_name(rel.sections.len()), "");
+
+        rel.sections[6].size = 0x20;
+        assert_eq!(rel.guess_section_name(6), ".bss");
+    }
+
+    #[test]
+    fn bss() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let mut rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        assert_eq!(rel.bss_size, 0);
+        assert_eq!(rel.bss_section, 0);
+        assert_eq!(rel.bss_section_index(), None);
+        assert!(rel.bss().unwrap().is_none());
+
+        rel.sections[6].size = 0x20;
+        let mut output = Cursor::new(Vec::new());
+        rel.to_binary(&mut output).unwrap();
+        let rel = Rel::from_binary(Cursor::new(output.into_inner())).unwrap();
+        assert_eq!(rel.bss_size, 0x20);
+        assert_eq!(rel.bss_section_index(), Some(6));
+
+        let bss = rel.section_at(6).unwrap().unwrap();
+        assert_eq!(bss.size, 0x20);
+        assert_eq!(bss.data, vec![0; 0x20]);
+        assert!(!bss.executable);
+        assert_eq!(rel.section_at(1).unwrap().unwrap().data, rel.sections[1].data);
+        assert!(rel.section_at(rel.sections.len()).unwrap().is_none());
+
+        let mut rel = rel;
+        rel.bss_size = 0xFFFF_FFFF;
+        assert!(rel.bss().is_err());
+        assert!(rel.section_at(6).is_err());
+    }
+
+    #[test]
+    fn from_bytes() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let rel = Rel::from_bytes(data).unwrap();
+        assert_eq!(rel.module, 400);
+        assert_eq!(rel.sections.len(), 18);
+        assert_eq!(rel.relocations().count(), 450);
+        assert!(Rel::from_bytes(&data[..0x40]).is_err());
+    }
+
+    #[test]
+    fn display() {
+        let data = include_bytes!("../assets/tests/rel/test0.rel");
+        let rel = Rel::from_binary(Cursor::new(&data)).unwrap();
+        assert_eq!(
+            rel.to_string(),
+            "REL module 400 (version 3), 18 sections, 450 relocations"
+        );
+    }
+
+    #[test]
+    fn import_kind() {
+        for value in 0..=u8::MAX {
+            if let Ok(kind) = rel::ImportKind::try_from(value) {
+                assert_eq!(u8::from(kind), value);
+            }
+        }
+        assert_eq!(rel::ImportKind::try_from(10).unwrap(), rel::ImportKind::Rel24);
+        assert!(rel::ImportKind::try_from(8).is_err());
+        assert_eq!(format!("{:<8}|", rel::ImportKind::Addr32), "Addr32  |");
+    }
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod shift_jis_1997 {
-    use picori::{ShiftJis1997, ShiftJis1997IteratorExt};
+    use picori::error::DecodingProblem;
+    use picori::{Error, ShiftJis1997, ShiftJis1997IteratorExt};
 
     static TEST_UTF8: &[u8] = include_bytes!("../assets/tests/shift-jis/1997.ok.utf-8.txt");
     static TEST_SHIFTJIS: &[u8] = include_bytes!("../assets/tests/shift-jis/1997.ok.shift-jis.txt");
@@ -24,6 +25,18 @@ mod shift_jis_1997 {
         }
     }
 
+    #[test]
+    fn err_index() {
+        assert!(matches!(
+            ShiftJis1997::all(b"ab\xff"),
+            Err(Error::Decoding(DecodingProblem::InvalidByte(0xFF, 2, _)))
+        ));
+        assert!(matches!(
+            ShiftJis1997::all(b"a\x81\x20"),
+            Err(Error::Decoding(DecodingProblem::InvalidByte(0x20, 2, _)))
+        ));
+    }
+
     #[test]
     fn first() {
         let data = b"\x83\x5b\x83\x8b\x83\x5f\x83\x93\x82\xcc\x93\x60\x90\xe0\0\x95\x97\x82\xcc\x83\x5e\x83\x4e\x83\x67";
@@ -57,4 +70,14 @@ mod shift_jis_1997 {
         let data = b"abc\0def";
         assert_eq!(&ShiftJis1997::all(data).unwrap()[..], "abc\0def");
     }
+
+    #[test]
+    fn lossy() {
+        // invalid lead byte, invalid trail byte that is kept, missing trail byte
+        let data = b"a\xffb\x81\x20\x88\x9f\x81";
+        assert_eq!(
+            data.sjis1997().lossy().collect::<String>(),
+            "a\u{fffd}b\u{fffd} 亜\u{fffd}"
+        );
+    }
 }
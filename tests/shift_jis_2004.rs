@@ -57,4 +57,14 @@ mod shift_jis_2004 {
         let data = b"abc\0def";
         assert_eq!(&ShiftJis2004::all(data).unwrap()[..], "abc\0def");
     }
+
+    #[test]
+    fn lossy() {
+        // invalid lead byte, invalid trail byte that is kept, missing trail byte
+        let data = b"a\xffb\x81\x20\x82\xf5\x81";
+        assert_eq!(
+            data.sjis2004().lossy().collect::<String>(),
+            "a\u{fffd}b\u{fffd} \u{304b}\u{309a}\u{fffd}"
+        );
+    }
 }
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod texture {
+    use picori::texture::{decode, decode_indexed, GxTextureFormat, Palette, TlutFormat};
+
+    /// Pixel values of an 8x8 RGB5A3 image, both opaque (RGB555) and
+    /// translucent (RGB4A3) pixels.
+    fn rgb5a3(x: usize, y: usize) -> u16 {
+        if (x + y).is_multiple_of(2) {
+            0x8000 | ((x as u16 * 4) << 10) | ((y as u16 * 4) << 5) | 0x1F
+        } else {
+            ((y as u16 % 8) << 12) | ((x as u16) << 8) | ((y as u16) << 4) | 0x3
+        }
+    }
+
+    #[test]
+    fn rgb5a3_8x8() {
+        // four 4x4 blocks: top-left, top-right, bottom-left, bottom-right
+        let mut data = Vec::new();
+        for (block_x, block_y) in [(0, 0), (4, 0), (0, 4), (4, 4)] {
+            for y in 0..4 {
+                for x in 0..4 {
+                    data.extend_from_slice(&rgb5a3(block_x + x, block_y + y).to_be_bytes());
+                }
+            }
+        }
+
+        let rgba = decode(GxTextureFormat::RGB5A3, 8, 8, &data).unwrap();
+        assert_eq!(rgba.len(), 8 * 8 * 4);
+        assert_eq!(&rgba[0..4], [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(&rgba[4..8], [0x11, 0x00, 0x33, 0x00]);
+        for y in 0..8 {
+            for x in 0..8 {
+                let pixel = &rgba[(y * 8 + x) * 4..][..4];
+                let expected = if (x + y).is_multiple_of(2) {
+                    let r = (x * 4) as u8;
+                    let g = (y * 4) as u8;
+                    [r << 3 | r >> 2, g << 3 | g >> 2, 0xFF, 0xFF]
+                } else {
+                    let a = (y % 8) as u8;
+                    [x as u8 * 0x11, y as u8 * 0x11, 0x33, a << 5 | a << 2 | a >> 1]
+                };
+                assert_eq!(pixel, expected, "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn i4() {
+        // one 8x8 block, each row is 0x0..0x7 or 0x8..0xF
+        let data = (0..32).map(|i| if i % 4 < 2 { 0x01 } else { 0x23 }).collect::<Vec<u8>>();
+        let rgba = decode(GxTextureFormat::I4, 8, 8, &data).unwrap();
+        assert_eq!(&rgba[0..8], [0, 0, 0, 0, 0x11, 0x11, 0x11, 0x11]);
+        assert_eq!(&rgba[4 * 4..4 * 4 + 4], [0x22; 4]);
+    }
+
+    #[test]
+    fn rgba8() {
+        let mut data = Vec::new();
+        for _ in 0..16 {
+            data.extend_from_slice(&[0x80, 0x10]); // AR
+        }
+        for _ in 0..16 {
+            data.extend_from_slice(&[0x20, 0x30]); // GB
+        }
+        let rgba = decode(GxTextureFormat::RGBA8, 4, 4, &data).unwrap();
+        assert!(rgba.chunks(4).all(|x| x == [0x10, 0x20, 0x30, 0x80]));
+    }
+
+    #[test]
+    fn padded() {
+        // 5x3 IA8 uses 2x1 blocks of 4x4
+        let mut data = vec![0; 2 * 32];
+        data[32..34].copy_from_slice(&[0x40, 0x50]);
+        let rgba = decode(GxTextureFormat::IA8, 5, 3, &data).unwrap();
+        assert_eq!(rgba.len(), 5 * 3 * 4);
+        assert_eq!(&rgba[4 * 4..5 * 4], [0x50, 0x50, 0x50, 0x40]);
+        assert!(decode(GxTextureFormat::IA8, 5, 3, &data[..32]).is_err());
+    }
+
+    #[test]
+    fn data_size() {
+        assert_eq!(GxTextureFormat::IA8.data_size(5, 3).unwrap(), 2 * 32);
+        assert_eq!(GxTextureFormat::CMPR.data_size(8, 8).unwrap(), 32);
+        assert!(matches!(
+            GxTextureFormat::RGBA8.data_size(u32::MAX, u32::MAX),
+            Err(picori::Error::Parse(picori::error::ParseProblem::InvalidData(..)))
+        ));
+        assert!(decode(GxTextureFormat::RGBA8, u32::MAX, u32::MAX, &[0; 64]).is_err());
+    }
+
+    #[test]
+    fn cmpr() {
+        let mut data = Vec::new();
+        // top-left: red/blue, four colors, each row uses indices 0, 1, 2, 3
+        data.extend_from_slice(&[0xF8, 0x00, 0x00, 0x1F, 0x1B, 0x1B, 0x1B, 0x1B]);
+        // top-right: white/white, three colors and transparent
+        data.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0xE4, 0xE4, 0xE4, 0xE4]);
+        // bottom-left: black/green, three colors and transparent
+        data.extend_from_slice(&[0x00, 0x00, 0x07, 0xE0, 0x00, 0x55, 0xAA, 0xFF]);
+        // bottom-right: solid green (index 0)
+        data.extend_from_slice(&[0x07, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+        let rgba = decode(GxTextureFormat::CMPR, 8, 8, &data).unwrap();
+        let pixel = |x: usize, y: usize| &rgba[(y * 8 + x) * 4..][..4];
+        for y in 0..4 {
+            assert_eq!(pixel(0, y), [0xFF, 0x00, 0x00, 0xFF]);
+            assert_eq!(pixel(1, y), [0x00, 0x00, 0xFF, 0xFF]);
+            assert_eq!(pixel(2, y), [0xAA, 0x00, 0x55, 0xFF]);
+            assert_eq!(pixel(3, y), [0x55, 0x00, 0xAA, 0xFF]);
+
+            assert_eq!(pixel(4, y), [0x00, 0x00, 0x00, 0x00]);
+            assert_eq!(pixel(5, y), [0xFF, 0xFF, 0xFF, 0xFF]);
+            assert_eq!(pixel(7, y), [0xFF, 0xFF, 0xFF, 0xFF]);
+        }
+        assert_eq!(pixel(0, 4), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(pixel(3, 5), [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(pixel(1, 6), [0x00, 0x7F, 0x00, 0xFF]);
+        assert_eq!(pixel(2, 7), [0x00, 0x00, 0x00, 0x00]);
+        assert!((4..8).all(|x| (4..8).all(|y| pixel(x, y) == [0x00, 0xFF, 0x00, 0xFF])));
+        assert!(decode(GxTextureFormat::CMPR, 8, 8, &data[..24]).is_err());
+    }
+
+    #[test]
+    fn ci4() {
+        // black, red, green, blue and a translucent RGB4A3 entry
+        let palette = Palette::new(TlutFormat::RGB5A3, vec![
+            0x80, 0x00, 0xFC, 0x00, 0x83, 0xE0, 0x80, 0x1F, 0x4F, 0x00,
+        ]);
+        assert_eq!(palette.len(), 5);
+        assert_eq!(palette.colors()[4], [0xFF, 0x00, 0x00, 0x92]);
+
+        // one 8x8 block, each row is 0, 1, 2, 3, 4, 0, 0, 0
+        let data = [0x01, 0x23, 0x40, 0x00].repeat(8);
+        let rgba = decode_indexed(GxTextureFormat::CI4, 8, 8, &data, &palette).unwrap();
+        for y in 0..8 {
+            let row = &rgba[y * 8 * 4..][..8 * 4];
+            assert_eq!(&row[0..4], [0x00, 0x00, 0x00, 0xFF]);
+            assert_eq!(&row[4..8], [0xFF, 0x00, 0x00, 0xFF]);
+            assert_eq!(&row[12..16], [0x00, 0x00, 0xFF, 0xFF]);
+            assert_eq!(&row[16..20], [0xFF, 0x00, 0x00, 0x92]);
+        }
+
+        let data = [0x05; 32];
+        assert!(decode_indexed(GxTextureFormat::CI4, 8, 8, &data, &palette).is_err());
+        assert!(decode_indexed(GxTextureFormat::CI4, 8, 8, &data[..16], &palette).is_err());
+    }
+
+    #[test]
+    fn ci8() {
+        let palette = Palette::new(TlutFormat::IA8, (0..=255u8).flat_map(|x| [0xFF, x]).collect());
+
+        // 10x4 uses 2x1 blocks of 8x4
+        let data = (0..64).map(|x| x as u8).collect::<Vec<_>>();
+        let rgba = decode_indexed(GxTextureFormat::CI8, 10, 4, &data, &palette).unwrap();
+        assert_eq!(rgba.len(), 10 * 4 * 4);
+        assert_eq!(&rgba[0..4], [0, 0, 0, 0xFF]);
+        assert_eq!(&rgba[(10 + 1) * 4..][..4], [9, 9, 9, 0xFF]);
+        assert_eq!(&rgba[9 * 4..][..4], [33, 33, 33, 0xFF]);
+    }
+
+    #[test]
+    fn ci14x2() {
+        let palette = Palette::new(TlutFormat::RGB565, vec![0x00, 0x00, 0xFF, 0xFF]);
+        let data = [0xC0, 0x01].repeat(16);
+        let rgba = decode_indexed(GxTextureFormat::CI14X2, 4, 4, &data, &palette).unwrap();
+        assert!(rgba.chunks(4).all(|x| x == [0xFF; 4]));
+        assert!(decode_indexed(GxTextureFormat::I8, 8, 4, &[0; 32], &palette).is_err());
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(decode(GxTextureFormat::CI8, 8, 4, &[0; 32]).is_err());
+        assert_eq!(GxTextureFormat::from_u32(0xE), Some(GxTextureFormat::CMPR));
+        assert_eq!(GxTextureFormat::from_u32(0x7), None);
+    }
+}
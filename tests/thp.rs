@@ -0,0 +1,167 @@
+#[cfg(all(test, feature = "std"))]
+mod thp {
+    use std::io::Cursor;
+
+    use picori::thp::{Audio, Component, Frame, Video};
+    use picori::{Error, Thp};
+
+    fn u32s(values: &[u32]) -> Vec<u8> { values.iter().flat_map(|x| x.to_be_bytes()).collect() }
+
+    /// Builds a THP file with one video and one audio component and frames of
+    /// the given sizes.
+    fn thp(version: u32, sizes: &[u32]) -> Vec<u8> {
+        let extended = version == picori::thp::VERSION_1_1;
+        let mut components = u32s(&[2]);
+        components.extend([0, 1]);
+        components.extend([0xFF; 14]);
+        components.extend(u32s(&[640, 480]));
+        if extended {
+            components.extend(u32s(&[0]));
+        }
+        components.extend(u32s(&[2, 32000, 1000]));
+        if extended {
+            components.extend(u32s(&[1]));
+        }
+
+        let first = 0x30 + components.len() as u32;
+        let last = first + sizes[..sizes.len() - 1].iter().sum::<u32>();
+        let mut data = u32s(&[
+            picori::thp::MAGIC,
+            version,
+            *sizes.iter().max().unwrap(),
+            1000,
+            29.97_f32.to_bits(),
+            sizes.len() as u32,
+            sizes[0],
+            sizes.iter().sum(),
+            0x30,
+            0,
+            first,
+            last,
+        ]);
+        data.extend(components);
+
+        for (i, size) in sizes.iter().enumerate() {
+            let next = sizes.get(i + 1).copied().unwrap_or(0);
+            let previous = if i == 0 { 0 } else { sizes[i - 1] };
+            let mut frame = u32s(&[next, previous, size - 0x10, 0]);
+            frame.resize(*size as usize, i as u8);
+            data.extend(frame);
+        }
+        data
+    }
+
+    #[test]
+    fn header() {
+        let data = thp(picori::thp::VERSION_1_0, &[0x40, 0x20, 0x30]);
+        let thp = Thp::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(thp.header.frame_count, 3);
+        assert_eq!(thp.header.frame_rate, 29.97);
+        assert_eq!(thp.header.max_buffer_size, 0x40);
+        assert_eq!(thp.header.first_frame_offset, 0x58);
+        assert_eq!(thp.header.last_frame_offset, 0xB8);
+        assert_eq!(thp.components.len(), 2);
+        assert_eq!(
+            thp.video(),
+            Some(&Video {
+                width:      640,
+                height:     480,
+                video_type: None,
+            })
+        );
+        assert_eq!(
+            thp.components[1],
+            Component::Audio(Audio {
+                channels:     2,
+                frequency:    32000,
+                sample_count: 1000,
+                track_count:  None,
+            })
+        );
+    }
+
+    #[test]
+    fn version_1_1() {
+        let data = thp(picori::thp::VERSION_1_1, &[0x20]);
+        let thp = Thp::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(thp.video().unwrap().video_type, Some(0));
+        assert_eq!(thp.audio().unwrap().track_count, Some(1));
+        assert_eq!(thp.header.first_frame_offset, 0x60);
+    }
+
+    #[test]
+    fn frames() {
+        let data = thp(picori::thp::VERSION_1_0, &[0x40, 0x20, 0x30]);
+        let mut cursor = Cursor::new(&data);
+        let thp = Thp::from_binary(&mut cursor).unwrap();
+        let frames = thp
+            .frames(&mut cursor)
+            .collect::<picori::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(frames, vec![
+            Frame {
+                offset: 0x58,
+                size:   0x40,
+            },
+            Frame {
+                offset: 0x98,
+                size:   0x20,
+            },
+            Frame {
+                offset: 0xB8,
+                size:   0x30,
+            },
+        ]);
+        assert_eq!(frames[2].offset, thp.header.last_frame_offset as u64);
+
+        let frame = frames[1].data(&mut cursor).unwrap();
+        assert_eq!(frame.len(), 0x20);
+        assert_eq!(frame[..8], [0, 0, 0, 0x30, 0, 0, 0, 0x40]);
+        assert_eq!(frame[0x1F], 1);
+    }
+
+    #[test]
+    fn truncated_frames() {
+        let mut data = thp(picori::thp::VERSION_1_0, &[0x40, 0x20, 0x30]);
+        data.truncate(0x98);
+        let mut cursor = Cursor::new(&data);
+        let thp = Thp::from_binary(&mut cursor).unwrap();
+        let mut frames = thp.frames(&mut cursor);
+        assert!(frames.next().unwrap().is_ok());
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn frame_too_large() {
+        let mut data = thp(picori::thp::VERSION_1_0, &[0x40, 0x20, 0x30]);
+        // next frame size in the first frame
+        data[0x58..0x5C].copy_from_slice(&0x41_u32.to_be_bytes());
+        let mut cursor = Cursor::new(&data);
+        let thp = Thp::from_binary(&mut cursor).unwrap();
+        let mut frames = thp.frames(&mut cursor);
+        assert!(frames.next().unwrap().is_ok());
+        assert!(matches!(
+            frames.next().unwrap(),
+            Err(Error::Parse(picori::error::ParseProblem::LimitExceeded(_, 0x41, 0x40, _)))
+        ));
+
+        let frame = Frame {
+            offset: 0x58,
+            size:   u32::MAX,
+        };
+        assert!(frame.data(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = thp(picori::thp::VERSION_1_0, &[0x20]);
+        data[4] = 2;
+        assert!(matches!(
+            Thp::from_binary(&mut Cursor::new(&data)),
+            Err(Error::Parse(_))
+        ));
+        data[0] = 0;
+        assert!(Thp::from_binary(&mut Cursor::new(&data)).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(all(test, feature = "std"))]
+mod u8_archive {
+    use std::io::Cursor;
+
+    use picori::u8_archive::Node;
+    use picori::yaz0::CompressionLevel;
+    use picori::{yaz0, U8Reader, Yaz0Reader};
+
+    enum Entry {
+        Directory(u32, u32),
+        File(&'static [u8]),
+    }
+
+    fn align(value: usize) -> usize { (value + 0x1F) & !0x1F }
+
+    /// Build an U8 archive from a flat node table, the first node is the root
+    /// directory.
+    fn build(nodes: &[(&str, Entry)]) -> Vec<u8> {
+        let mut strings = Vec::new();
+        let mut name_offsets = Vec::new();
+        for (name, _) in nodes {
+            name_offsets.push(strings.len() as u32);
+            strings.extend_from_slice(name.as_bytes());
+            strings.push(0);
+        }
+
+        let header_size = nodes.len() * 0x0C + strings.len();
+        let data_offset = align(0x20 + header_size);
+        let mut data = Vec::new();
+        let mut table = Vec::new();
+        for ((_, entry), name_offset) in nodes.iter().zip(name_offsets) {
+            let (kind, offset, size) = match entry {
+                Entry::Directory(parent, end) => (1, *parent, *end),
+                Entry::File(file) => {
+                    data.resize(align(data.len()), 0);
+                    let offset = (data_offset + data.len()) as u32;
+                    data.extend_from_slice(file);
+                    (0, offset, file.len() as u32)
+                },
+            };
+            table.extend_from_slice(&((kind << 24) | name_offset).to_be_bytes());
+            table.extend_from_slice(&offset.to_be_bytes());
+            table.extend_from_slice(&size.to_be_bytes());
+        }
+
+        let mut archive = Vec::new();
+        archive.extend_from_slice(&0x55AA_382D_u32.to_be_bytes());
+        archive.extend_from_slice(&0x20_u32.to_be_bytes());
+        archive.extend_from_slice(&(header_size as u32).to_be_bytes());
+        archive.extend_from_slice(&(data_offset as u32).to_be_bytes());
+        archive.extend_from_slice(&[0; 16]);
+        archive.extend_from_slice(&table);
+        archive.extend_from_slice(&strings);
+        archive.resize(data_offset, 0);
+        archive.extend_from_slice(&data);
+        archive
+    }
+
+    fn archive() -> Vec<u8> {
+        build(&[
+            ("", Entry::Directory(0, 7)),
+            ("a.txt", Entry::File(b"hello")),
+            ("dir", Entry::Directory(0, 6)),
+            ("b.bin", Entry::File(&[1, 2, 3, 4, 5, 6, 7, 8, 9])),
+            ("sub", Entry::Directory(2, 6)),
+            ("c.bin", Entry::File(&[0xAA; 0x1C])),
+            ("d.bin", Entry::File(&[0x55; 0x40])),
+        ])
+    }
+
+    fn read_all<T: picori::Parser + picori::Seeker>(
+        reader: &mut U8Reader<T>,
+    ) -> Vec<(String, Vec<u8>)> {
+        let files = reader
+            .nodes()
+            .filter_map(|x| match x {
+                Node::File { name, offset, size } => Some((name, offset, size)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        files
+            .into_iter()
+            .map(|(name, offset, size)| (name, reader.file_data(offset, size).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn nodes() {
+        let reader = U8Reader::new(Cursor::new(archive())).unwrap();
+        let nodes = reader
+            .nodes()
+            .map(|x| match x {
+                Node::DirectoryBegin { name } => format!("+{name}"),
+                Node::DirectoryEnd { name } => format!("-{name}"),
+                Node::File { name, .. } => name,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [
+            "+", "a.txt", "+dir", "b.bin", "+sub", "c.bin", "-sub", "-dir", "d.bin", "-"
+        ]);
+    }
+
+    #[test]
+    fn file_data() {
+        let mut reader = U8Reader::new(Cursor::new(archive())).unwrap();
+        assert_eq!(read_all(&mut reader), [
+            ("a.txt".to_string(), b"hello".to_vec()),
+            ("b.bin".to_string(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            ("c.bin".to_string(), vec![0xAA; 0x1C]),
+            ("d.bin".to_string(), vec![0x55; 0x40]),
+        ]);
+        assert!(reader.file_data(0, 4).is_err());
+    }
+
+    #[test]
+    fn yaz0() {
+        let data = archive();
+        let compressed = yaz0::compress(&data, CompressionLevel::Fast).unwrap();
+        let reader = Yaz0Reader::new(Cursor::new(compressed)).unwrap();
+        let mut reader = U8Reader::new(reader).unwrap();
+        let mut expected = U8Reader::new(Cursor::new(data)).unwrap();
+        assert_eq!(read_all(&mut reader), read_all(&mut expected));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = archive();
+        data[0] = 0;
+        assert!(U8Reader::new(Cursor::new(data)).is_err());
+
+        let mut data = archive();
+        data.truncate(data.len() - 1);
+        assert!(U8Reader::new(Cursor::new(data)).is_err());
+
+        let data = build(&[("", Entry::Directory(0, 3)), ("a", Entry::File(b"a"))]);
+        assert!(U8Reader::new(Cursor::new(data)).is_err());
+    }
+}
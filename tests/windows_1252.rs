@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod windows_1252 {
+    use picori::{Parser, SliceParser, Windows1252, Windows1252IteratorExt};
+
+    #[test]
+    fn round_trip() {
+        let text = (0..=0xff).windows_1252().collect::<Result<String, _>>().unwrap();
+        assert_eq!(text.chars().count(), 0x100);
+        assert_eq!(text.chars().nth(0x80), Some('€'));
+        assert_eq!(text.chars().nth(0x92), Some('’'));
+        assert_eq!(text.chars().nth(0x81), Some('\u{81}'));
+        assert_eq!(text.chars().nth(0xe9), Some('é'));
+
+        let bytes = text
+            .chars()
+            .map(|c| Windows1252::encode_char(c).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, (0..=0xff).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(&Windows1252::first(b"\x93quote\x94\0def").unwrap()[..], "“quote”");
+    }
+
+    #[test]
+    fn str_fixed() {
+        let mut data = SliceParser::new(b"Z\xfcrich\x99\0rest");
+        assert_eq!(data.str_fixed::<8, Windows1252>().unwrap(), "Zürich™");
+        assert_eq!(data.str_fixed::<4, Windows1252>().unwrap(), "rest");
+    }
+}
@@ -1,8 +1,10 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod yaz0 {
-    use std::io::{Cursor, Read, Seek};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
 
-    use picori::yaz0::{self, is_yaz0, Yaz0Reader};
+    use picori::error::DecompressionProblem;
+    use picori::yaz0::{self, is_yaz0, CompressionLevel, Yaz0Reader};
+    use picori::Error;
 
     #[test]
     fn test09() {
@@ -28,6 +30,30 @@ mod yaz0 {
         assert_eq!(buf.as_slice(), d);
     }
 
+    #[test]
+    fn from_bytes() {
+        let c = include_bytes!("../assets/tests/yaz0/test1.input");
+        let d = include_bytes!("../assets/tests/yaz0/test1.output");
+        let mut reader = Yaz0Reader::from_bytes(c).unwrap();
+        let mut buf = Vec::with_capacity(reader.decompressed_size());
+        let result = reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(result, d.len());
+        assert_eq!(buf.as_slice(), d);
+    }
+
+    #[test]
+    fn header() {
+        let mut data = Vec::new();
+        yaz0::Header::new(0x1234).to_binary(&mut data).unwrap();
+        assert_eq!(data, [
+            0x59, 0x61, 0x7A, 0x30, 0x00, 0x00, 0x12, 0x34, 0, 0, 0, 0, 0, 0, 0, 0
+        ]);
+
+        let header = yaz0::Header::from_binary(&mut Cursor::new(&data)).unwrap();
+        assert!(header.is_valid());
+        assert_eq!(header.decompressed_size, 0x1234);
+    }
+
     #[test]
     fn bad_magic() {
         let data: &[u8] = &[
@@ -98,6 +124,98 @@ mod yaz0 {
     }
 
     #[test]
+    fn back_reference_before_start() {
+        let data = [
+            0x59, 0x61, 0x7A, 0x30, // magic = 'Yaz0'
+            0x00, 0x00, 0x00, 0x03, // decompressed size = 3
+            0x00, 0x00, 0x00, 0x00, // reserved0 = 0x00000000
+            0x00, 0x00, 0x00, 0x00, // reserved1 = 0x00000000
+            0x00, // code = 0000 0000
+            0x10, 0x00, // back-reference, length 3, distance 1
+        ];
+        let is_invalid_data = |error: &Error| {
+            matches!(error, Error::Decompression(DecompressionProblem::InvalidData(..)))
+        };
+
+        let mut output = [0_u8; 3];
+        let error = yaz0::decompress_into(&mut Cursor::new(&data[0x10..]), &mut output);
+        assert!(is_invalid_data(&error.unwrap_err()));
+
+        assert!(is_invalid_data(&Yaz0Reader::from_bytes(&data).err().unwrap()));
+
+        let mut reader = Yaz0Reader::streaming(Cursor::new(&data)).unwrap();
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+        assert!(is_invalid_data(&error));
+    }
+
+    fn is_invalid_size<T>(result: picori::Result<T>) -> bool {
+        matches!(
+            result,
+            Err(Error::Decompression(DecompressionProblem::InvalidDecompressedSize(_)))
+        )
+    }
+
+    #[test]
+    fn stream_too_short() {
+        let data = (0..0x100).map(|x| (x * 7) as u8).collect::<Vec<_>>();
+        let compressed = yaz0::compress(&data, CompressionLevel::Best).unwrap();
+        let truncated = &compressed[0x10..compressed.len() - 4];
+        let mut output = vec![0; data.len()];
+        assert!(is_invalid_size(yaz0::decompress_into(
+            &mut Cursor::new(truncated),
+            &mut output
+        )));
+
+        let mut output = vec![0; data.len() + 1];
+        assert!(is_invalid_size(yaz0::decompress_into(
+            &mut Cursor::new(&compressed[0x10..]),
+            &mut output
+        )));
+    }
+
+    #[test]
+    fn stream_too_long() {
+        let data = [0xAB; 0x40];
+        let cases = [
+            (CompressionLevel::None, &[4, 0x1C][..]),
+            (CompressionLevel::Best, &[4, 8, 0x20][..]),
+        ];
+        for (level, sizes) in cases {
+            let compressed = yaz0::compress(&data, level).unwrap();
+            for size in sizes {
+                let mut output = vec![0; *size];
+                assert!(is_invalid_size(yaz0::decompress_into(
+                    &mut Cursor::new(&compressed[0x10..]),
+                    &mut output
+                )));
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_data() {
+        // the last group is complete, so the next byte follows the stream
+        let data = [0xAB; 0x40];
+        let mut compressed = yaz0::compress(&data, CompressionLevel::None).unwrap();
+        compressed.extend_from_slice(b"RARC next file");
+
+        let mut output = vec![0; data.len()];
+        let mut cursor = Cursor::new(&compressed[0x10..]);
+        yaz0::decompress_into(&mut cursor, &mut output).unwrap();
+        assert_eq!(output, data);
+        assert_eq!(cursor.position(), 0x48);
+
+        let mut cursor = Cursor::new(&compressed);
+        assert_eq!(yaz0::decompress_all(&mut cursor).unwrap(), data);
+        let mut reader = Yaz0Reader::from_bytes(&compressed).unwrap();
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    #[allow(clippy::seek_from_current)]
     fn seek() {
         let c = include_bytes!("../assets/tests/yaz0/test1.input");
         let d = include_bytes!("../assets/tests/yaz0/test1.output");
@@ -124,4 +242,117 @@ mod yaz0 {
         assert_eq!(result, d.len() / 2);
         assert_eq!(buf.as_slice(), &d[d.len() / 2..]);
     }
+
+    #[test]
+    fn decompress_all() {
+        let c = include_bytes!("../assets/tests/yaz0/test1.input");
+        let d = include_bytes!("../assets/tests/yaz0/test1.output");
+        let decompressed = yaz0::decompress_all(&mut Cursor::new(c)).unwrap();
+        assert_eq!(decompressed.as_slice(), d);
+
+        assert!(matches!(
+            yaz0::decompress_all(&mut Cursor::new(d)),
+            Err(Error::Decompression(DecompressionProblem::InvalidHeader(..)))
+        ));
+    }
+
+    fn round_trip(data: &[u8], level: CompressionLevel) -> Vec<u8> {
+        let compressed = yaz0::compress(data, level).unwrap();
+        let mut cursor = Cursor::new(&compressed);
+        let size = yaz0::Header::decompressed_size(&mut cursor).unwrap();
+        assert_eq!(size, data.len());
+        let decompressed = yaz0::decompress(&mut cursor, size).unwrap();
+        assert_eq!(decompressed.as_slice(), data);
+        compressed
+    }
+
+    #[test]
+    fn compress() {
+        let d = include_bytes!("../assets/tests/yaz0/test.output");
+        let none = round_trip(d, CompressionLevel::None);
+        let fast = round_trip(d, CompressionLevel::Fast);
+        let best = round_trip(d, CompressionLevel::Best);
+        assert!(fast.len() < none.len());
+        assert!(best.len() <= fast.len());
+    }
+
+    #[test]
+    fn compress_small() {
+        for level in [
+            CompressionLevel::None,
+            CompressionLevel::Fast,
+            CompressionLevel::Best,
+        ] {
+            round_trip(&[], level);
+            round_trip(&[1], level);
+            round_trip(&[1, 2, 3, 1, 2, 3, 1, 2, 3], level);
+            round_trip(&[0; 0x1000], level);
+        }
+
+        let compressed = yaz0::compress(&[0; 0x111 + 1], CompressionLevel::Best).unwrap();
+        assert_eq!(&compressed[16..], [0x80, 0x00, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn compression_ratio() {
+        let c = include_bytes!("../assets/tests/yaz0/test.input");
+        let d = include_bytes!("../assets/tests/yaz0/test.output");
+        let reader = Yaz0Reader::new(Cursor::new(c)).unwrap();
+        assert!(reader.compressed_size() <= c.len() as u64);
+        assert!(reader.compressed_size() > 0x10);
+        let ratio = reader.compressed_size() as f32 / d.len() as f32;
+        assert_eq!(reader.compression_ratio(), ratio);
+
+        let reader = Yaz0Reader::new(Cursor::new(d)).unwrap();
+        assert_eq!(reader.compressed_size(), d.len() as u64);
+        assert_eq!(reader.compression_ratio(), 1.0);
+    }
+
+    #[test]
+    fn streaming() {
+        for (c, d) in [
+            (
+                &include_bytes!("../assets/tests/yaz0/test.input")[..],
+                &include_bytes!("../assets/tests/yaz0/test.output")[..],
+            ),
+            (
+                &include_bytes!("../assets/tests/yaz0/test1.input")[..],
+                &include_bytes!("../assets/tests/yaz0/test1.output")[..],
+            ),
+        ] {
+            let mut reader = Yaz0Reader::streaming(Cursor::new(c)).unwrap();
+            assert_eq!(reader.decompressed_size(), d.len());
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf.as_slice(), d);
+
+            let eager = Yaz0Reader::new(Cursor::new(c)).unwrap();
+            assert_eq!(reader.compressed_size(), eager.compressed_size());
+        }
+    }
+
+    #[test]
+    fn streaming_seek() {
+        let c = include_bytes!("../assets/tests/yaz0/test1.input");
+        let d = include_bytes!("../assets/tests/yaz0/test1.output");
+        let mut reader = Yaz0Reader::streaming(Cursor::new(c)).unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(reader.seek(SeekFrom::Start(0x10)).unwrap(), 0x10);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, d[0x10..0x14]);
+        assert_eq!(reader.stream_position().unwrap(), 0x14);
+        assert!(reader.seek(SeekFrom::Start(0)).is_err());
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), d.len() as u64);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn streaming_transparent() {
+        let data = b"not compressed";
+        let mut reader = Yaz0Reader::streaming(Cursor::new(data)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf.as_slice(), data);
+        assert_eq!(reader.compression_ratio(), 1.0);
+    }
 }